@@ -1,8 +1,14 @@
-use itertools::Itertools;
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::BufRead;
+use std::str::FromStr;
+
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
 
 const INPUT: &str = include_str!("../../inputs/day01.txt");
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Input {
     pub numbers: Vec<usize>,
 }
@@ -10,38 +16,274 @@ struct Input {
 impl Input {
     pub fn new(input: &str) -> Input {
         Input {
-            numbers: input
-                .lines()
-                .map(|line| line.parse::<usize>().unwrap())
-                .collect(),
+            numbers: readings(input.as_bytes()).collect(),
+        }
+    }
+}
+
+// Depth logs can be far bigger than the puzzle input, so everything below works one reading at a
+// time, keeping no more than a window of readings in memory
+
+/// Reads one depth per line.
+fn readings(log: impl BufRead) -> impl Iterator<Item = usize> {
+    log.lines()
+        .map(|line| line.expect("Failed to read the log"))
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.trim().parse::<usize>().unwrap())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Aggregate {
+    Sum,
+    Mean,
+    Min,
+    Max,
+}
+
+impl FromStr for Aggregate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sum" => Ok(Aggregate::Sum),
+            "mean" => Ok(Aggregate::Mean),
+            "min" => Ok(Aggregate::Min),
+            "max" => Ok(Aggregate::Max),
+            _ => Err(format!("Unknown aggregate {}", s)),
+        }
+    }
+}
+
+/// Every full window of readings, combined into one value each.
+struct Windows<I> {
+    readings: I,
+    size: usize,
+    aggregate: Aggregate,
+    window: VecDeque<usize>,
+    sum: usize,
+    index: usize,
+    // For min and max, the readings in the window that nothing later has beaten yet,
+    // by index, so the front is always the answer
+    candidates: VecDeque<(usize, usize)>,
+}
+
+impl<I: Iterator<Item = usize>> Windows<I> {
+    fn new(readings: I, size: usize, aggregate: Aggregate) -> Self {
+        assert!(size > 0, "A window needs at least one reading");
+        Self {
+            readings,
+            size,
+            aggregate,
+            window: VecDeque::with_capacity(size + 1),
+            sum: 0,
+            index: 0,
+            candidates: VecDeque::new(),
         }
     }
+
+    fn push(&mut self, reading: usize) {
+        self.window.push_back(reading);
+        self.sum += reading;
+        if self.window.len() > self.size {
+            self.sum -= self.window.pop_front().unwrap();
+        }
+
+        let beaten: Option<fn(usize, usize) -> bool> = match self.aggregate {
+            Aggregate::Sum | Aggregate::Mean => None,
+            Aggregate::Min => Some(|old, new| new <= old),
+            Aggregate::Max => Some(|old, new| new >= old),
+        };
+        if let Some(beaten) = beaten {
+            while matches!(self.candidates.back(), Some(&(_, old)) if beaten(old, reading)) {
+                self.candidates.pop_back();
+            }
+            self.candidates.push_back((self.index, reading));
+            while matches!(self.candidates.front(), Some(&(i, _)) if i + self.size <= self.index) {
+                self.candidates.pop_front();
+            }
+        }
+        self.index += 1;
+    }
+
+    fn value(&self) -> f64 {
+        match self.aggregate {
+            Aggregate::Sum => self.sum as f64,
+            Aggregate::Mean => self.sum as f64 / self.size as f64,
+            Aggregate::Min | Aggregate::Max => self.candidates.front().unwrap().1 as f64,
+        }
+    }
+}
+
+impl<I: Iterator<Item = usize>> Iterator for Windows<I> {
+    type Item = f64;
+
+    fn next(&mut self) -> Option<f64> {
+        loop {
+            let reading = self.readings.next()?;
+            self.push(reading);
+            if self.window.len() == self.size {
+                return Some(self.value());
+            }
+        }
+    }
+}
+
+/// Positions are those of the window, counting from the window that starts at the first reading.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Streak {
+    start: usize,
+    length: usize,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Jump {
+    /// The window that was jumped to.
+    position: usize,
+    change: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Crossing {
+    Rising(usize),
+    Falling(usize),
+}
+
+/// Everything there is to say about a stream of windows, gathered in one pass.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Analysis {
+    windows: usize,
+    increases: usize,
+    decreases: usize,
+    longest_rise: Streak,
+    longest_fall: Streak,
+    largest_rise: Option<Jump>,
+    largest_fall: Option<Jump>,
+    threshold: Option<f64>,
+    crossings: Vec<Crossing>,
+    // The streaks still going on
+    rise: Streak,
+    fall: Streak,
+    last: Option<f64>,
+}
+
+impl Analysis {
+    fn new(threshold: Option<f64>) -> Self {
+        Self {
+            threshold,
+            ..Default::default()
+        }
+    }
+
+    fn push(&mut self, value: f64) {
+        let position = self.windows;
+        self.windows += 1;
+        let Some(last) = self.last.replace(value) else {
+            return;
+        };
+
+        let change = value - last;
+        if change > 0.0 {
+            self.increases += 1;
+            extend(&mut self.rise, &mut self.longest_rise, position);
+            if self.largest_rise.is_none_or(|jump| change > jump.change) {
+                self.largest_rise = Some(Jump { position, change });
+            }
+        } else {
+            self.rise.length = 0;
+        }
+        if change < 0.0 {
+            self.decreases += 1;
+            extend(&mut self.fall, &mut self.longest_fall, position);
+            if self.largest_fall.is_none_or(|jump| change < jump.change) {
+                self.largest_fall = Some(Jump { position, change });
+            }
+        } else {
+            self.fall.length = 0;
+        }
+
+        if let Some(threshold) = self.threshold {
+            if last < threshold && value >= threshold {
+                self.crossings.push(Crossing::Rising(position));
+            } else if last >= threshold && value < threshold {
+                self.crossings.push(Crossing::Falling(position));
+            }
+        }
+    }
+}
+
+// A streak covers the changes into each of its windows, so it starts at the window before
+fn extend(streak: &mut Streak, longest: &mut Streak, position: usize) {
+    if streak.length == 0 {
+        streak.start = position - 1;
+    }
+    streak.length += 1;
+    if streak.length > longest.length {
+        *longest = *streak;
+    }
+}
+
+impl fmt::Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "windows: {}", self.windows)?;
+        writeln!(f, "increases: {}", self.increases)?;
+        writeln!(f, "decreases: {}", self.decreases)?;
+        let streak = |s: Streak| format!("{} changes from window {}", s.length, s.start);
+        writeln!(f, "longest rise: {}", streak(self.longest_rise))?;
+        writeln!(f, "longest fall: {}", streak(self.longest_fall))?;
+        let jump = |j: Option<Jump>| match j {
+            Some(j) => format!("{} into window {}", j.change, j.position),
+            None => "none".to_string(),
+        };
+        writeln!(f, "largest rise: {}", jump(self.largest_rise))?;
+        writeln!(f, "largest fall: {}", jump(self.largest_fall))?;
+        write!(f, "crossings: {:?}", self.crossings)
+    }
+}
+
+fn analyse(
+    readings: impl Iterator<Item = usize>,
+    size: usize,
+    aggregate: Aggregate,
+    threshold: Option<f64>,
+) -> Analysis {
+    let mut analysis = Analysis::new(threshold);
+    for value in Windows::new(readings, size, aggregate) {
+        analysis.push(value);
+    }
+    analysis
 }
 
 fn part1(input: &Input) -> usize {
-    input
-        .numbers
-        .iter()
-        .tuple_windows()
-        .filter(|&(a, b)| b > a)
-        .count()
+    analyse(input.numbers.iter().copied(), 1, Aggregate::Sum, None).increases
 }
 
 fn part2(input: &Input) -> usize {
-    input
-        .numbers
-        .iter()
-        .tuple_windows()
-        .filter(|&(a, _b, _c, d)| d > a)
-        .count()
+    analyse(input.numbers.iter().copied(), 3, Aggregate::Sum, None).increases
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    let parameters = [("window", "3"), ("aggregate", "sum"), ("threshold", "none")];
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        // Any window and aggregate, with the whole analysis rather than just the increases
+        .part_with(3, &parameters, |p| {
+            let threshold: String = p.get("threshold");
+            analyse(
+                input.numbers.iter().copied(),
+                p.get("window"),
+                p.get("aggregate"),
+                threshold.parse().ok(),
+            )
+        })
+        .run();
 }
 
 #[cfg(test)]
@@ -56,6 +298,74 @@ mod tests {
         assert_eq!(part2(&input), 5);
     }
 
+    #[test]
+    pub fn windows() {
+        let readings = || readings(TEST_INPUT.as_bytes());
+        let values =
+            |size, aggregate| Windows::new(readings(), size, aggregate).collect::<Vec<_>>();
+        assert_eq!(values(3, Aggregate::Sum)[..3], [607.0, 618.0, 618.0]);
+        assert_eq!(values(2, Aggregate::Mean)[..2], [199.5, 204.0]);
+        assert_eq!(
+            values(3, Aggregate::Min),
+            [199.0, 200.0, 200.0, 200.0, 200.0, 207.0, 240.0, 260.0]
+        );
+        assert_eq!(
+            values(3, Aggregate::Max),
+            [208.0, 210.0, 210.0, 210.0, 240.0, 269.0, 269.0, 269.0]
+        );
+        assert!(values(11, Aggregate::Sum).is_empty());
+    }
+
+    #[test]
+    pub fn analysis() {
+        let analysis = analyse(
+            readings(TEST_INPUT.as_bytes()),
+            1,
+            Aggregate::Sum,
+            Some(205.0),
+        );
+        assert_eq!(
+            (analysis.windows, analysis.increases, analysis.decreases),
+            (10, 7, 2)
+        );
+        assert_eq!(
+            analysis.longest_rise,
+            Streak {
+                start: 0,
+                length: 3
+            }
+        );
+        assert_eq!(
+            analysis.longest_fall,
+            Streak {
+                start: 3,
+                length: 1
+            }
+        );
+        assert_eq!(
+            analysis.largest_rise,
+            Some(Jump {
+                position: 6,
+                change: 33.0
+            })
+        );
+        assert_eq!(
+            analysis.largest_fall,
+            Some(Jump {
+                position: 4,
+                change: -10.0
+            })
+        );
+        assert_eq!(
+            analysis.crossings,
+            [
+                Crossing::Rising(2),
+                Crossing::Falling(4),
+                Crossing::Rising(5)
+            ]
+        );
+    }
+
     #[test]
     pub fn answers() {
         let input = Input::new(INPUT);
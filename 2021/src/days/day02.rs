@@ -1,37 +1,88 @@
+use std::fmt;
 use std::str::FromStr;
 
+use aoc_common::direction::Direction;
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+
 const INPUT: &str = include_str!("../../inputs/day02.txt");
 
-#[derive(Clone)]
+// The puzzle input is the simplest script there is, a list of commands. Scripts can also have
+// comments after a `#`, `repeat N` ... `end` blocks, and `model NAME` to change how the
+// submarine steers from then on. New models are written with `define NAME` ... `end`, one line
+// per command, such as `forward position += x, depth += aim*x`.
+
+/// The two ways of steering from the puzzle, written the same way as any other model.
+const MODELS: &str = "
+define travel
+    forward position += x
+    down depth += x
+    up depth -= x
+end
+define aim
+    forward position += x, depth += aim*x
+    down aim += x
+    up aim -= x
+end";
+
+#[derive(Clone, Debug)]
 struct Input {
-    pub actions: Vec<Action>,
+    pub script: Script,
 }
 
 impl Input {
     pub fn new(input: &str) -> Input {
         Input {
-            actions: input
-                .lines()
-                .map(|line| line.parse::<Action>().unwrap())
-                .collect(),
+            script: input.parse().unwrap_or_else(|e| panic!("{}", e)),
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 enum ParseError {
     InvalidDirection,
     InvalidDistance,
+    MissingDistance,
+    InvalidCount,
+    InvalidEffect,
+    UnknownModel(String),
+    /// A `repeat` or `define` without an `end`.
+    Unclosed,
+    UnexpectedEnd,
+}
+
+/// A parse error, and the line of the script it was found on, counting from 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ScriptError {
+    line: usize,
+    error: ParseError,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.error {
+            ParseError::InvalidDirection => write!(f, "invalid direction"),
+            ParseError::InvalidDistance => write!(f, "invalid distance"),
+            ParseError::MissingDistance => write!(f, "missing distance"),
+            ParseError::InvalidCount => write!(f, "invalid repeat count"),
+            ParseError::InvalidEffect => write!(f, "invalid effect"),
+            ParseError::UnknownModel(name) => write!(f, "unknown model {}", name),
+            ParseError::Unclosed => write!(f, "no end for this block"),
+            ParseError::UnexpectedEnd => write!(f, "end without a block"),
+        }
+    }
 }
 
 impl FromStr for Action {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (dir_str, dist_str) = s.split_once(' ').expect("Missing whitespace");
+        let (dir_str, dist_str) = s.split_once(' ').ok_or(ParseError::MissingDistance)?;
 
-        let direction = str_to_direction(dir_str).ok_or(ParseError::InvalidDirection)?;
+        let direction = command(dir_str)?;
         let distance = dist_str
+            .trim()
             .parse::<isize>()
             .map_err(|_| ParseError::InvalidDistance)?;
 
@@ -42,16 +93,7 @@ impl FromStr for Action {
     }
 }
 
-fn str_to_direction(c: &str) -> Option<Direction> {
-    match c {
-        "forward" => Some(Direction::Forward),
-        "down" => Some(Direction::Down),
-        "up" => Some(Direction::Up),
-        _ => None,
-    }
-}
-
-#[derive(Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 struct Submarine {
     pub position: isize,
     pub depth: isize,
@@ -63,64 +105,309 @@ impl Submarine {
         Default::default()
     }
 
-    pub fn travel(&mut self, action: &Action) {
-        match action.direction {
-            Direction::Down => self.depth += action.distance,
-            Direction::Up => self.depth -= action.distance,
-            Direction::Forward => self.position += action.distance,
+    fn field(&mut self, field: Field) -> &mut isize {
+        match field {
+            Field::Position => &mut self.position,
+            Field::Depth => &mut self.depth,
+            Field::Aim => &mut self.aim,
         }
     }
 
-    pub fn apply(&mut self, action: &Action) {
-        match action.direction {
-            Direction::Down => self.aim += action.distance,
-            Direction::Up => self.aim -= action.distance,
-            Direction::Forward => {
-                self.position += action.distance;
-                self.depth += self.aim * action.distance;
-            }
+    /// Every effect of the command sees the submarine as it was before the command.
+    pub fn steer(&mut self, model: &Model, action: &Action) {
+        let before = *self;
+        for (_, effect) in model.effects.iter().filter(|(d, _)| *d == action.direction) {
+            let aim = if effect.aimed { before.aim } else { 1 };
+            *self.field(effect.field) += effect.scale * aim * action.distance;
         }
     }
 }
 
-#[derive(Clone)]
-enum Direction {
-    Forward,
-    Down,
-    Up,
+/// Commands are written out as forward, down or up, never as a letter or an arrow.
+fn command(s: &str) -> Result<Direction, ParseError> {
+    Direction::from_word(s).map_err(|_| ParseError::InvalidDirection)
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Action {
     pub direction: Direction,
     pub distance: isize,
 }
 
-fn run_actions_on_submarine(
-    actions: &[Action],
-    update_submarine: fn(&mut Submarine, &Action),
-) -> isize {
-    let mut submarine = Submarine::new();
-    for action in actions {
-        update_submarine(&mut submarine, action);
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field {
+    Position,
+    Depth,
+    Aim,
+}
+
+/// Adds `scale * x` to a field for a command of distance `x`, times the aim if `aimed`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Effect {
+    field: Field,
+    scale: isize,
+    aimed: bool,
+}
+
+impl FromStr for Effect {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (field, sign, term) = if let Some((field, term)) = s.split_once("+=") {
+            (field, 1, term)
+        } else if let Some((field, term)) = s.split_once("-=") {
+            (field, -1, term)
+        } else {
+            return Err(ParseError::InvalidEffect);
+        };
+        let field = match field.trim() {
+            "position" => Field::Position,
+            "depth" => Field::Depth,
+            "aim" => Field::Aim,
+            _ => return Err(ParseError::InvalidEffect),
+        };
+
+        // [N*][aim*]x
+        let mut factors: Vec<&str> = term.split('*').map(str::trim).collect();
+        if factors.pop() != Some("x") {
+            return Err(ParseError::InvalidEffect);
+        }
+        let aimed = factors.last() == Some(&"aim");
+        if aimed {
+            factors.pop();
+        }
+        let scale = match factors[..] {
+            [] => 1,
+            [n] => n.parse().map_err(|_| ParseError::InvalidEffect)?,
+            _ => return Err(ParseError::InvalidEffect),
+        };
+        Ok(Effect {
+            field,
+            scale: sign * scale,
+            aimed,
+        })
+    }
+}
+
+/// How the submarine moves for each command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Model {
+    name: String,
+    effects: Vec<(Direction, Effect)>,
+}
+
+#[derive(Clone, Debug)]
+enum Statement {
+    Action(Action),
+    Repeat(usize, Vec<Statement>),
+    /// Steers with the model at this index from here on.
+    Model(usize),
+}
+
+#[derive(Clone, Debug)]
+struct Script {
+    /// Every model the script can use, with later definitions hiding earlier ones of the same name.
+    models: Vec<Model>,
+    statements: Vec<Statement>,
+}
+
+type Lines<'a> = dyn Iterator<Item = (usize, &'a str)> + 'a;
+
+impl FromStr for Script {
+    type Err = ScriptError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut script = Script {
+            models: Vec::new(),
+            statements: Vec::new(),
+        };
+        script.block(&mut lines(MODELS), None)?;
+        script.statements = script.block(&mut lines(s), None)?;
+        Ok(script)
+    }
+}
+
+/// The lines that say something, without their comments.
+fn lines(s: &str) -> impl Iterator<Item = (usize, &str)> {
+    s.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.split('#').next().unwrap().trim()))
+        .filter(|(_, line)| !line.is_empty())
+}
+
+impl Script {
+    fn model(&self, name: &str) -> Option<usize> {
+        self.models.iter().rposition(|model| model.name == name)
+    }
+
+    /// Reads statements up to the `end` of the block opened on line `opened`, or to the end of
+    /// the script if there isn't one.
+    fn block(
+        &mut self,
+        lines: &mut Lines,
+        opened: Option<usize>,
+    ) -> Result<Vec<Statement>, ScriptError> {
+        let mut statements = Vec::new();
+        while let Some((line, text)) = lines.next() {
+            let error = |error| ScriptError { line, error };
+            let words: Vec<&str> = text.split_whitespace().collect();
+            match words[..] {
+                ["end"] if opened.is_some() => return Ok(statements),
+                ["end"] => return Err(error(ParseError::UnexpectedEnd)),
+                ["repeat", times] => {
+                    let times = times.parse().map_err(|_| error(ParseError::InvalidCount))?;
+                    statements.push(Statement::Repeat(times, self.block(lines, Some(line))?));
+                }
+                ["model", name] => {
+                    let model = self
+                        .model(name)
+                        .ok_or_else(|| error(ParseError::UnknownModel(name.to_string())))?;
+                    statements.push(Statement::Model(model));
+                }
+                ["define", name] => {
+                    let model = define(name, lines, line)?;
+                    self.models.push(model);
+                }
+                _ => statements.push(Statement::Action(text.parse().map_err(error)?)),
+            }
+        }
+        match opened {
+            Some(line) => Err(ScriptError {
+                line,
+                error: ParseError::Unclosed,
+            }),
+            None => Ok(statements),
+        }
+    }
+
+    /// Runs the script from the surface, steering with the named model until told otherwise.
+    pub fn run(&self, model: &str) -> Trajectory {
+        let model = self
+            .model(model)
+            .unwrap_or_else(|| panic!("No model called {}", model));
+        let mut trajectory = Trajectory {
+            states: vec![Submarine::new()],
+        };
+        self.run_block(&self.statements, &mut &self.models[model], &mut trajectory);
+        trajectory
+    }
+
+    fn run_block<'a>(
+        &'a self,
+        statements: &'a [Statement],
+        model: &mut &'a Model,
+        trajectory: &mut Trajectory,
+    ) {
+        for statement in statements {
+            match statement {
+                Statement::Action(action) => {
+                    let mut submarine = trajectory.end();
+                    submarine.steer(model, action);
+                    trajectory.states.push(submarine);
+                }
+                Statement::Repeat(times, block) => {
+                    for _ in 0..*times {
+                        self.run_block(block, model, trajectory);
+                    }
+                }
+                Statement::Model(index) => *model = &self.models[*index],
+            }
+        }
+    }
+}
+
+fn define(name: &str, lines: &mut Lines, opened: usize) -> Result<Model, ScriptError> {
+    let mut effects = Vec::new();
+    for (line, text) in lines {
+        if text == "end" {
+            return Ok(Model {
+                name: name.to_string(),
+                effects,
+            });
+        }
+        let error = |error| ScriptError { line, error };
+        let (direction, rest) = text
+            .split_once(' ')
+            .ok_or_else(|| error(ParseError::InvalidEffect))?;
+        let direction = command(direction).map_err(error)?;
+        for effect in rest.split(',') {
+            effects.push((direction, effect.parse().map_err(error)?));
+        }
+    }
+    Err(ScriptError {
+        line: opened,
+        error: ParseError::Unclosed,
+    })
+}
+
+/// Where the submarine was at the start and after every command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Trajectory {
+    states: Vec<Submarine>,
+}
+
+impl Trajectory {
+    pub fn end(&self) -> Submarine {
+        *self.states.last().unwrap()
+    }
+
+    /// The submarine after `step` commands.
+    pub fn at(&self, step: usize) -> Option<Submarine> {
+        self.states.get(step).copied()
+    }
+
+    /// The first step that reaches the greatest depth.
+    pub fn deepest(&self) -> (usize, Submarine) {
+        self.states
+            .iter()
+            .copied()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, submarine)| submarine.depth)
+            .unwrap()
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = "step,position,depth,aim\n".to_string();
+        for (step, s) in self.states.iter().enumerate() {
+            csv += &format!("{},{},{},{}\n", step, s.position, s.depth, s.aim);
+        }
+        csv
     }
-    submarine.position * submarine.depth
 }
 
 fn part1(input: &Input) -> isize {
-    run_actions_on_submarine(&input.actions, |sub, action| sub.travel(action))
+    let end = input.script.run("travel").end();
+    end.position * end.depth
 }
 
 fn part2(input: &Input) -> isize {
-    run_actions_on_submarine(&input.actions, |sub, action| sub.apply(action))
+    let end = input.script.run("aim").end();
+    end.position * end.depth
+}
+
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        // `show` is end, deepest, csv or a step
+        .part_with(3, &[("model", "aim"), ("show", "end")], |p| {
+            let trajectory = input.script.run(&p.get::<String>("model"));
+            match p.get::<String>("show").as_str() {
+                "end" => format!("{:?}", trajectory.end()),
+                "deepest" => format!("{:?}", trajectory.deepest()),
+                "csv" => trajectory.to_csv(),
+                step => format!("{:?}", trajectory.at(step.parse().unwrap())),
+            }
+        })
+        .run();
 }
 
 #[cfg(test)]
@@ -135,6 +422,59 @@ mod tests {
         assert_eq!(part2(&input), 900);
     }
 
+    #[test]
+    pub fn scripts() {
+        let script: Script = "
+            # Dive, then level out
+            define steep
+                forward position += x, depth += 2*aim*x
+                down aim += x
+                up aim -= x
+            end
+            repeat 2
+                down 1  # twice
+                forward 3
+            end
+            model travel
+            up 4
+        "
+        .parse()
+        .unwrap();
+        let trajectory = script.run("steep");
+        assert_eq!(trajectory.states.len(), 6);
+        assert_eq!(
+            trajectory.at(4),
+            Some(Submarine {
+                position: 6,
+                depth: 18,
+                aim: 2
+            })
+        );
+        assert_eq!(trajectory.end().depth, 14);
+        assert_eq!(trajectory.deepest().0, 4);
+        assert!(trajectory
+            .to_csv()
+            .starts_with("step,position,depth,aim\n0,0,0,0\n1,0,0,1\n"));
+
+        let error = |s: &str| s.parse::<Script>().unwrap_err().to_string();
+        assert_eq!(error("forward 1\nmodel sink"), "line 2: unknown model sink");
+        assert_eq!(
+            error("repeat 2\nforward 1"),
+            "line 1: no end for this block"
+        );
+        assert_eq!(
+            error("define a\nforward depth *= x\nend"),
+            "line 2: invalid effect"
+        );
+        assert_eq!(error("forward 1\nend"), "line 2: end without a block");
+        assert_eq!(error("sideways 1"), "line 1: invalid direction");
+        assert_eq!(error("forward 1\nL 5"), "line 2: invalid direction");
+        assert_eq!(
+            error("define a\n> position += x\nend"),
+            "line 2: invalid direction"
+        );
+    }
+
     #[test]
     pub fn answers() {
         let input = Input::new(INPUT);
@@ -1,127 +1,250 @@
+use std::fmt;
+use std::str::FromStr;
+
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+
 const INPUT: &str = include_str!("../../inputs/day03.txt");
 
 #[derive(Clone, Debug)]
-// Any excuse to use const generics
-// This type is parametrised over the length of each binary in the input file
-// In retrospect it would probably have been better to just use a Vec and pass the length as a second input parameter
-struct Input<const N: usize> {
-    pub numbers: Vec<[bool; N]>,
+struct Input {
+    pub report: Report,
 }
 
-impl<const N: usize> Input<N> {
-    pub fn new(input: &str) -> Input<N> {
+impl Input {
+    pub fn new(input: &str) -> Input {
         Input {
-            numbers: input
-                .lines()
-                .map(|line| {
-                    // Initialise the array of bools to false
-                    let mut array: [bool; N] = [false; N];
-                    // For each char in the string, if the value is 1, we update the array value to true
-                    line.chars().enumerate().for_each(|(i, char)| match char {
-                        '0' => {}
-                        '1' => array[i] = true,
-                        _ => panic!("Unexpected non-binary character in input"),
-                    });
-                    array
-                })
-                .collect(),
+            report: Report::new(input),
         }
     }
 }
 
-fn part1<const N: usize>(input: &Input<N>) -> i64 {
-    let input_length = input.numbers.len();
+// Binaries can be any width, so they are packed 64 bits to a word, with the first bit of each
+// binary at the top of its first word. A report keeps every binary in one buffer.
+
+fn bit(words: &[u64], i: usize) -> bool {
+    words[i / 64] >> (63 - i % 64) & 1 == 1
+}
+
+fn pack(bits: impl Iterator<Item = bool>, words: &mut [u64]) {
+    for (i, bit) in bits.enumerate() {
+        if bit {
+            words[i / 64] |= 1 << (63 - i % 64);
+        }
+    }
+}
+
+/// The lines of a diagnostic report.
+#[derive(Clone, Debug)]
+struct Report {
+    /// The number of bits in each binary, which is taken from the input.
+    pub width: usize,
+    pub len: usize,
+    /// The words each binary takes up.
+    words: usize,
+    bits: Vec<u64>,
+}
+
+impl Report {
+    pub fn new(input: &str) -> Self {
+        let width = input.lines().next().map_or(0, |line| line.trim().len());
+        let words = width.div_ceil(64);
+        let mut report = Report {
+            width,
+            len: 0,
+            words,
+            bits: Vec::new(),
+        };
+        for line in input.lines().map(str::trim) {
+            assert_eq!(
+                line.len(),
+                width,
+                "Every binary in the input should have the same width"
+            );
+            let start = report.bits.len();
+            report.bits.resize(start + words, 0);
+            let bits = line.chars().map(|char| match char {
+                '0' => false,
+                '1' => true,
+                _ => panic!("Unexpected non-binary character in input"),
+            });
+            pack(bits, &mut report.bits[start..]);
+            report.len += 1;
+        }
+        report
+    }
+
+    fn row(&self, row: usize) -> &[u64] {
+        &self.bits[row * self.words..(row + 1) * self.words]
+    }
+
+    pub fn bit(&self, row: usize, column: usize) -> bool {
+        bit(self.row(row), column)
+    }
+
+    pub fn binary(&self, row: usize) -> Binary {
+        Binary {
+            width: self.width,
+            words: self.row(row).to_vec(),
+        }
+    }
 
-    // Count the number of 1s in each column
-    let counts = input
-        .numbers
-        .iter()
-        .fold([0usize; N], |mut counts, &number| {
-            for (i, value) in number.iter().enumerate() {
-                if *value {
-                    counts[i] += 1;
-                }
+    /// The number of 1s in each column.
+    pub fn histogram(&self) -> Vec<usize> {
+        let mut ones = vec![0; self.width];
+        for row in 0..self.len {
+            for (column, count) in ones.iter_mut().enumerate() {
+                *count += usize::from(self.bit(row, column));
             }
-            counts
-        });
+        }
+        ones
+    }
 
-    // Build gamma as a binary array
-    let gamma_binary =
-        counts
+    /// The bit the rule picks for each column of the whole report.
+    pub fn summary(&self, rule: Rule) -> Binary {
+        let mut words = vec![0; self.words];
+        let bits = self
+            .histogram()
             .into_iter()
-            .enumerate()
-            .fold([false; N], |mut gamma_binary, (i, count)| {
-                gamma_binary[i] = count >= input_length / 2;
-                gamma_binary
-            });
+            .map(|ones| rule.pick(ones, self.len));
+        pack(bits, &mut words);
+        Binary {
+            width: self.width,
+            words,
+        }
+    }
 
-    let gamma = binary_to_integer(gamma_binary);
+    /// Keeps the binaries with the bit the rule picks, a column at a time, until one is left.
+    /// A column where every binary left has the same bit is skipped, rather than leaving none.
+    pub fn filter(&self, rule: Rule) -> Option<Binary> {
+        // The binaries themselves never move, only the list of those still in
+        let mut rows: Vec<usize> = (0..self.len).collect();
+        for column in 0..self.width {
+            if rows.len() <= 1 {
+                break;
+            }
+            let ones = rows.iter().filter(|&&row| self.bit(row, column)).count();
+            if ones == 0 || ones == rows.len() {
+                continue;
+            }
+            let keep = rule.pick(ones, rows.len());
+            rows.retain(|&row| self.bit(row, column) == keep);
+        }
+        // Several copies of the same binary can be left at the end
+        rows.first().map(|&row| self.binary(row))
+    }
+}
 
-    let mask = 2i64.pow(N.try_into().unwrap()) - 1;
-    let epsilon = mask - gamma;
-    gamma * epsilon
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Common {
+    Most,
+    Least,
 }
 
-fn part2<const N: usize>(input: &Input<N>) -> i64 {
-    let oxygen_generator = filter_numbers(input.numbers.clone(), true);
-    let co2_scrubber = filter_numbers(input.numbers.clone(), false);
+impl FromStr for Common {
+    type Err = String;
 
-    oxygen_generator * co2_scrubber
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "most" => Ok(Common::Most),
+            "least" => Ok(Common::Least),
+            _ => Err(format!("Expected most or least, not {}", s)),
+        }
+    }
 }
 
-fn filter_numbers<const N: usize>(numbers: Vec<[bool; N]>, more_ones_than_zeros: bool) -> i64 {
-    // Iterate through columns
-    let mut column: usize = 0;
-    let mut current_input = numbers;
-    while column < N {
-        // Count the number of 1s in this column
-        let count = current_input
-            .iter()
-            .filter(|&number| number[column])
-            .count();
-
-        // Work out the target count (half of the remaining size, rounded up)
-        let target_count = if current_input.len() % 2 == 0 {
-            current_input.len() / 2
+/// Picks the most or least common bit of a column, or `tie` when there are as many of each.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Rule {
+    keep: Common,
+    tie: bool,
+}
+
+impl Rule {
+    pub const fn new(keep: Common, tie: bool) -> Self {
+        Self { keep, tie }
+    }
+
+    pub fn pick(&self, ones: usize, total: usize) -> bool {
+        let zeros = total - ones;
+        if ones == zeros {
+            self.tie
         } else {
-            (current_input.len() + 1) / 2
-        };
+            (ones > zeros) == (self.keep == Common::Most)
+        }
+    }
+}
 
-        let target = (count >= target_count) == more_ones_than_zeros;
+const GAMMA: Rule = Rule::new(Common::Most, true);
+const EPSILON: Rule = Rule::new(Common::Least, false);
+const OXYGEN: Rule = Rule::new(Common::Most, true);
+const CO2: Rule = Rule::new(Common::Least, false);
 
-        // Filter the existing input based on this target
-        current_input.retain(|number| number[column] == target);
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Binary {
+    width: usize,
+    words: Vec<u64>,
+}
 
-        // If only one left, we are done
-        if current_input.len() == 1 {
-            break;
-        }
+impl Binary {
+    /// The binary as a number, if it fits.
+    pub fn value(&self) -> Option<u128> {
+        let bits = (0..self.width).map(|i| bit(&self.words, i));
+        bits.map(u128::from)
+            .try_fold(0u128, |value, bit| value.checked_mul(2)?.checked_add(bit))
+    }
+}
 
-        column += 1;
+impl fmt::Display for Binary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for i in 0..self.width {
+            write!(f, "{}", u8::from(bit(&self.words, i)))?;
+        }
+        Ok(())
     }
+}
 
-    // We have only one of the input numbers left
-    assert!(current_input.len() == 1);
+fn rating(binary: Option<Binary>) -> u128 {
+    binary
+        .and_then(|binary| binary.value())
+        .expect("Rating is too wide for a number")
+}
 
-    binary_to_integer(*current_input.first().unwrap())
+fn part1(input: &Input) -> u128 {
+    let gamma = rating(Some(input.report.summary(GAMMA)));
+    let epsilon = rating(Some(input.report.summary(EPSILON)));
+    gamma * epsilon
 }
 
-fn binary_to_integer<const N: usize>(binary: [bool; N]) -> i64 {
-    let digits = binary.into_iter().map(i64::from);
+fn part2(input: &Input) -> u128 {
+    let oxygen_generator = rating(input.report.filter(OXYGEN));
+    let co2_scrubber = rating(input.report.filter(CO2));
+    oxygen_generator * co2_scrubber
+}
 
-    digits.into_iter().fold(0_i64, |mut sum: i64, number| {
-        sum *= 2;
-        sum += number;
-        sum
-    })
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
 }
 
-pub fn main() {
-    let input = Input::<12>::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    let rule = |p: &repl::Parameters| Rule::new(p.get("keep"), p.get::<u8>("tie") == 1);
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        // The binaries and summaries can be wider than any number
+        .part_with(3, &[("keep", "most"), ("tie", "1")], |p| {
+            let filtered = input.report.filter(rule(p)).unwrap();
+            format!(
+                "summary {}\nfiltered {}",
+                input.report.summary(rule(p)),
+                filtered
+            )
+        })
+        .part(4, || format!("{:?}", input.report.histogram()))
+        .run();
 }
 
 #[cfg(test)]
@@ -131,14 +254,45 @@ mod tests {
 
     #[test]
     pub fn examples() {
-        let input = Input::<5>::new(TEST_INPUT);
+        let input = Input::new(TEST_INPUT);
+        assert_eq!(input.report.width, 5);
+        assert_eq!(input.report.histogram(), [7, 5, 8, 7, 5]);
         assert_eq!(part1(&input), 198);
         assert_eq!(part2(&input), 230);
     }
 
+    #[test]
+    pub fn wide() {
+        // 100 bits, where the 1s pick out the 65th bit
+        let line = |first: char, last: char| {
+            format!(
+                "{}{}{}{}",
+                first,
+                "0".repeat(63),
+                "1",
+                last.to_string().repeat(35)
+            )
+        };
+        let text = [line('1', '0'), line('0', '1'), line('0', '0')].join("\n");
+        let report = Report::new(&text);
+        assert_eq!(report.width, 100);
+        assert_eq!(report.histogram()[64], 3);
+
+        let gamma = report.summary(GAMMA);
+        assert_eq!(gamma.to_string(), line('0', '0'));
+        assert_eq!(gamma.value(), Some(1 << 35));
+        assert_eq!(report.filter(OXYGEN).unwrap().to_string(), line('0', '1'));
+        assert_eq!(report.filter(CO2).unwrap().to_string(), line('1', '0'));
+        assert_eq!(
+            report.filter(Rule::new(Common::Least, true)).unwrap(),
+            report.binary(0)
+        );
+        assert_eq!(Report::new(&"1".repeat(200)).binary(0).value(), None);
+    }
+
     #[test]
     pub fn answers() {
-        let input = Input::<12>::new(INPUT);
+        let input = Input::new(INPUT);
         assert_eq!(part1(&input), 845186);
         assert_eq!(part2(&input), 4636702);
     }
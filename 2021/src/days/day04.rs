@@ -1,24 +1,33 @@
 use std::collections::{HashMap, HashSet};
+use std::fmt;
 
-use lazy_static::lazy_static;
-use regex::Regex;
+use aoc_common::parse::{comma_list, integer, lines, parse_all, sections, space_list};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+use nom::{
+    character::complete::line_ending,
+    sequence::{pair, separated_pair},
+};
 
 const INPUT: &str = include_str!("../../inputs/day04.txt");
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Input {
     pub numbers: Vec<usize>,
     pub boards: Vec<Board>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Board {
-    // This is a mapping from the numbers on the board, to the lines each of them is in the
-    // Each number is part of two lines: horizontal and vertical
-    numbers_data: HashMap<usize, [usize; 2]>,
-    // There are 10 lines in the board: 5 horizontal and 5 vertical
-    // We track the number of filled entries in each line
-    found_count_per_line: [usize; 10],
+    pub width: usize,
+    pub height: usize,
+    // This is a mapping from the numbers on the board, to the lines each of them is in
+    // Each number is part of a row and a column, and maybe a diagonal or two
+    numbers_data: HashMap<usize, Vec<usize>>,
+    // The rows come first, then the columns, then any diagonals
+    // We track how many entries each line has, and how many of them have been found
+    line_lengths: Vec<usize>,
+    found_count_per_line: Vec<usize>,
     // Keep track of the numbers we have found
     found: HashSet<usize>,
     // True if the board is complete
@@ -26,23 +35,52 @@ struct Board {
 }
 
 impl Board {
-    /// Initialise a board with a slice of 25 numbers
-    pub fn new(input: &[usize]) -> Self {
+    /// Initialise a board from its rows, which can be any size as long as they are all the same
+    pub fn new(rows: &[Vec<usize>]) -> Self {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+        assert!(
+            rows.iter().all(|row| row.len() == width),
+            "Every row of a board should be the same length"
+        );
+
         let mut numbers_data = HashMap::new();
-        for (index, &n) in input.iter().enumerate() {
-            // Find the lines this particular number is in, based on the index
-            let first = index / 5; // integer divison
-            let second = 5 + index % 5; // remainder
-            numbers_data.insert(n, [first, second]);
+        for (y, row) in rows.iter().enumerate() {
+            for (x, &n) in row.iter().enumerate() {
+                numbers_data.insert(n, vec![y, height + x]);
+            }
         }
+        let line_lengths = [vec![width; height], vec![height; width]].concat();
         Board {
+            width,
+            height,
             numbers_data,
             found: HashSet::new(),
-            found_count_per_line: [0; 10],
+            found_count_per_line: vec![0; line_lengths.len()],
+            line_lengths,
             is_complete: false,
         }
     }
 
+    /// Lets the two diagonals win as well, which only square boards have
+    pub fn with_diagonals(mut self) -> Self {
+        assert_eq!(self.width, self.height, "Only square boards have diagonals");
+        let size = self.width;
+        let (leading, trailing) = (self.line_lengths.len(), self.line_lengths.len() + 1);
+        for lines in self.numbers_data.values_mut() {
+            let (y, x) = (lines[0], lines[1] - size);
+            if x == y {
+                lines.push(leading);
+            }
+            if x + y == size - 1 {
+                lines.push(trailing);
+            }
+        }
+        self.line_lengths.extend([size, size]);
+        self.found_count_per_line.extend([0, 0]);
+        self
+    }
+
     /// Attempt to find a number on the board
     /// Returns true if this number completes a line
     pub fn update_and_check_if_complete(&mut self, number: usize) -> bool {
@@ -51,41 +89,27 @@ impl Board {
             return false;
         }
 
-        let lines = self.numbers_data.get(&number);
-        match lines {
+        match self.numbers_data.get(&number) {
             None => {
                 // This number is not on the board
                 false
             }
-            Some(&[first, second]) => {
-                // This number is on the board, and is part of these two lines
-
-                // Add it to the found set
+            Some(lines) => {
+                // This number is on the board, and is part of these lines
                 self.found.insert(number);
 
-                // If we have completed either line, return true
-                if self.update_line_and_check_if_complete(first) {
-                    self.is_complete = true;
-                    return true;
-                }
-                if self.update_line_and_check_if_complete(second) {
-                    self.is_complete = true;
-                    return true;
+                // Every line still needs counting, even once one of them is complete
+                let mut complete = false;
+                for &line in lines {
+                    self.found_count_per_line[line] += 1;
+                    complete |= self.found_count_per_line[line] == self.line_lengths[line];
                 }
-
-                false
+                self.is_complete |= complete;
+                complete
             }
         }
     }
 
-    fn update_line_and_check_if_complete(&mut self, line: usize) -> bool {
-        // Increment the found count for this line
-        self.found_count_per_line[line] += 1;
-
-        // If it has 5 items found, then it's complete
-        self.found_count_per_line[line] == 5
-    }
-
     pub fn score(&self, number: usize) -> usize {
         let sum_of_unmarked: usize = self
             .numbers_data
@@ -97,87 +121,126 @@ impl Board {
     }
 }
 
-impl Input {
-    pub fn new(input: &str) -> Input {
-        lazy_static! {
-            static ref RE: Regex = Regex::new("(\\d+)").unwrap();
-        }
-        let mut input_iter = input.lines();
-
-        // The numbers are on the first line
-        let numbers = input_iter
-            .next()
-            .unwrap()
-            .split(',')
-            .map(|line| line.parse::<usize>().unwrap())
-            .collect();
-
-        // Skip blank line
-        input_iter.next();
-
-        let mut boards = Vec::new();
-        while let Some(mut row) = input_iter.next() {
-            // Parse and insert a single 5x5 board
-            let mut numbers = Vec::new();
-
-            // Loop until we fill a board
-            loop {
-                for capture in RE.captures_iter(row) {
-                    let num = capture[0].parse::<usize>().unwrap();
-                    numbers.push(num);
-                }
-
-                // Board is full
-                if numbers.len() == 25 {
-                    break;
-                }
+/// A board completing its first line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Finish {
+    pub board: usize,
+    /// How many numbers had been drawn, counting the winning one.
+    pub draws: usize,
+    pub number: usize,
+    pub score: usize,
+}
 
-                row = input_iter.next().unwrap();
-            }
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Outcome {
+    /// In the order they finished, and in board order for the same draw.
+    pub finishes: Vec<Finish>,
+    /// The boards still without a line once every number has been drawn.
+    pub never_win: Vec<usize>,
+}
 
-            // We have 25 numbers in the board, add it to the list
-            boards.push(Board::new(&numbers));
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for finish in &self.finishes {
+            writeln!(
+                f,
+                "board {} wins on draw {} ({}) scoring {}",
+                finish.board, finish.draws, finish.number, finish.score
+            )?;
         }
-
-        Input { numbers, boards }
+        write!(f, "never win: {:?}", self.never_win)
     }
 }
 
-fn part1(mut input: Input) -> usize {
-    for number in input.numbers {
-        for board in &mut input.boards {
-            if board.update_and_check_if_complete(number) {
-                // Return the score of the first board completed
-                return board.score(number);
+/// Draws every number, keeping track of each board as it finishes.
+fn play(numbers: &[usize], mut boards: Vec<Board>) -> Outcome {
+    let mut finishes = Vec::new();
+    for (draw, &number) in numbers.iter().enumerate() {
+        for (index, board) in boards.iter_mut().enumerate() {
+            if !board.is_complete && board.update_and_check_if_complete(number) {
+                finishes.push(Finish {
+                    board: index,
+                    draws: draw + 1,
+                    number,
+                    score: board.score(number),
+                });
             }
         }
+        if finishes.len() == boards.len() {
+            break;
+        }
+    }
+    let never_win = (0..boards.len())
+        .filter(|&index| !boards[index].is_complete)
+        .collect();
+    Outcome {
+        finishes,
+        never_win,
     }
-    panic!("Failed to complete any boards");
 }
 
-fn part2(mut input: Input) -> usize {
-    let number_of_boards = input.boards.len();
-    let mut completed_boards: usize = 0;
-    for number in input.numbers {
-        for board in &mut input.boards {
-            if !board.is_complete && board.update_and_check_if_complete(number) {
-                completed_boards += 1;
-                if completed_boards == number_of_boards {
-                    // Return the score of the final board completed
-                    return board.score(number);
-                }
-            }
-        }
+impl Input {
+    pub fn new(input: &str) -> Input {
+        // The numbers are on the first line, followed by boards separated by blank lines
+        let (numbers, boards) = parse_all(
+            input,
+            separated_pair(
+                comma_list(integer),
+                pair(line_ending, line_ending),
+                sections(lines(space_list(integer))),
+            ),
+        )
+        .unwrap();
+
+        let boards = boards
+            .iter()
+            .map(|rows: &Vec<Vec<usize>>| Board::new(rows))
+            .collect();
+
+        Input { numbers, boards }
     }
-    panic!("Failed to complete all boards");
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(input.clone());
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(input);
-    println!("Part 2: {}", answer2);
+fn part1(input: &Input) -> usize {
+    // Return the score of the first board completed
+    let outcome = play(&input.numbers, input.boards.clone());
+    outcome
+        .finishes
+        .first()
+        .expect("Failed to complete any boards")
+        .score
+}
+
+fn part2(input: &Input) -> usize {
+    // Return the score of the final board completed
+    let outcome = play(&input.numbers, input.boards.clone());
+    assert!(
+        outcome.never_win.is_empty(),
+        "Failed to complete all boards"
+    );
+    outcome.finishes.last().unwrap().score
+}
+
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        // Every finish rather than just the first and last
+        .part_with(3, &[("diagonals", "false")], |p| {
+            let mut boards = input.boards.clone();
+            if p.get("diagonals") {
+                boards = boards.into_iter().map(Board::with_diagonals).collect();
+            }
+            play(&input.numbers, boards)
+        })
+        .run();
 }
 
 #[cfg(test)]
@@ -188,14 +251,51 @@ mod tests {
     #[test]
     pub fn examples() {
         let input = Input::new(TEST_INPUT);
-        assert_eq!(part1(input.clone()), 4512);
-        assert_eq!(part2(input), 1924);
+        assert_eq!(part1(&input), 4512);
+        assert_eq!(part2(&input), 1924);
+    }
+
+    #[test]
+    pub fn shapes() {
+        let rows = |text: &str| -> Vec<Vec<usize>> {
+            text.lines()
+                .map(|line| {
+                    line.split_whitespace()
+                        .map(|n| n.parse().unwrap())
+                        .collect()
+                })
+                .collect()
+        };
+        let wide = Board::new(&rows("1 2 3\n4 5 6"));
+        let square = Board::new(&rows("1 2 3\n4 5 6\n7 8 9"));
+        let outcome = play(
+            &[5, 3, 7, 2, 6],
+            vec![wide, square.clone(), square.with_diagonals()],
+        );
+        assert_eq!(
+            outcome.finishes,
+            [
+                Finish {
+                    board: 2,
+                    draws: 3,
+                    number: 7,
+                    score: 7 * (1 + 2 + 4 + 6 + 8 + 9)
+                },
+                Finish {
+                    board: 0,
+                    draws: 4,
+                    number: 2,
+                    score: 2 * (1 + 4 + 6)
+                },
+            ]
+        );
+        assert_eq!(outcome.never_win, [1]);
     }
 
     #[test]
     pub fn answers() {
         let input = Input::new(INPUT);
-        assert_eq!(part1(input.clone()), 10374);
-        assert_eq!(part2(input), 24742);
+        assert_eq!(part1(&input), 10374);
+        assert_eq!(part2(&input), 24742);
     }
 }
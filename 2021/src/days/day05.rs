@@ -1,21 +1,33 @@
-use serde_scan::scan;
 use std::cmp::{max, min};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
+
+use aoc_common::animate::Colour;
+use aoc_common::export::{self, picture, Palette};
+use aoc_common::grid::Grid;
+use aoc_common::parse::{edge, integer, lines, parse_all};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+use itertools::Itertools;
+use nom::{character::complete::char, sequence::separated_pair, IResult};
 
 const INPUT: &str = include_str!("../../inputs/day05.txt");
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Input {
     pub lines: Vec<Line>,
 }
 
+fn parse_point(i: &str) -> IResult<&str, (isize, isize)> {
+    separated_pair(integer, char(','), integer)(i)
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 struct Point {
     x: isize,
     y: isize,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 enum Line {
     // Row : Col From : Col To
     Horizontal(isize, isize, isize),
@@ -60,10 +72,10 @@ impl Line {
 
 impl Input {
     pub fn new(input: &str) -> Input {
-        let lines = input
-            .lines()
-            .map(|line| {
-                let (from_x, from_y, to_x, to_y) = scan!("{},{} -> {},{}" <- line).unwrap();
+        let lines = parse_all(input, lines(edge(" -> ", parse_point)))
+            .unwrap()
+            .into_iter()
+            .map(|((from_x, from_y), (to_x, to_y))| {
                 if from_x == to_x {
                     Line::Vertical(from_x, min(from_y, to_y), max(from_y, to_y))
                 } else if from_y == to_y {
@@ -81,35 +93,288 @@ impl Input {
     }
 }
 
-fn count_intersections(lines: &[Line], get_points_for_line: fn(&Line) -> HashSet<Point>) -> usize {
-    let mut visited = HashSet::new();
-    let mut intersections = HashSet::new();
-    for line in lines {
-        for point in get_points_for_line(line) {
-            if visited.contains(&point) && !intersections.contains(&point) {
-                intersections.insert(point);
-            } else {
-                visited.insert(point);
+// Every segment lies on one of four families of parallel lines. Within a line, a sweep along it
+// finds where two or more segments overlap. Between families, two lines meet at no more than one
+// point, so only the points where segments of different families cross are ever looked at.
+
+/// Which way a family of parallel lines runs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Family {
+    Horizontal,
+    Vertical,
+    /// Down and to the right, where `x - y` is the same all along.
+    Diagonal,
+    /// Up and to the right, where `x + y` is the same all along.
+    AntiDiagonal,
+}
+
+impl Family {
+    const ALL: [Family; 4] = [
+        Family::Horizontal,
+        Family::Vertical,
+        Family::Diagonal,
+        Family::AntiDiagonal,
+    ];
+
+    /// Which line of the family a point is on, and how far along it.
+    fn locate(self, Point { x, y }: Point) -> (isize, isize) {
+        match self {
+            Family::Horizontal => (y, x),
+            Family::Vertical => (x, y),
+            Family::Diagonal => (x - y, x),
+            Family::AntiDiagonal => (x + y, x),
+        }
+    }
+
+    fn point(self, line: isize, t: isize) -> Point {
+        match self {
+            Family::Horizontal => Point { x: t, y: line },
+            Family::Vertical => Point { x: line, y: t },
+            Family::Diagonal => Point { x: t, y: t - line },
+            Family::AntiDiagonal => Point { x: t, y: line - t },
+        }
+    }
+}
+
+impl Line {
+    /// The family the segment belongs to, its line, and where it starts and ends along it.
+    fn placement(&self) -> (Family, isize, (isize, isize)) {
+        match *self {
+            Line::Horizontal(y, from_col, to_col) => (Family::Horizontal, y, (from_col, to_col)),
+            Line::Vertical(x, from_row, to_row) => (Family::Vertical, x, (from_row, to_row)),
+            Line::Diagonal((from_x, from_y), (to_x, to_y)) => {
+                let family = if (to_x - from_x).signum() == (to_y - from_y).signum() {
+                    Family::Diagonal
+                } else {
+                    Family::AntiDiagonal
+                };
+                let (line, _) = family.locate(Point {
+                    x: from_x,
+                    y: from_y,
+                });
+                (family, line, (min(from_x, to_x), max(from_x, to_x)))
             }
         }
     }
-    intersections.len()
+}
+
+/// Where one line is covered by at least one segment and by at least two, as sorted ranges.
+#[derive(Clone, Debug, Default)]
+struct Coverage {
+    once: Vec<(isize, isize)>,
+    twice: Vec<(isize, isize)>,
+}
+
+impl Coverage {
+    fn new(segments: &[(isize, isize)]) -> Self {
+        let mut events: Vec<(isize, isize)> = segments
+            .iter()
+            .flat_map(|&(from, to)| [(from, 1), (to + 1, -1)])
+            .collect();
+        events.sort_unstable();
+
+        let mut coverage = Coverage::default();
+        let mut open = 0;
+        let mut starts = [0; 2];
+        for (t, change) in events {
+            let before = open;
+            open += change;
+            for (depth, ranges) in [(1, &mut coverage.once), (2, &mut coverage.twice)] {
+                if before < depth && open >= depth {
+                    starts[depth as usize - 1] = t;
+                } else if before >= depth && open < depth {
+                    ranges.push((starts[depth as usize - 1], t - 1));
+                }
+            }
+        }
+        coverage
+    }
+
+    fn contains(ranges: &[(isize, isize)], t: isize) -> bool {
+        let i = ranges.partition_point(|&(_, to)| to < t);
+        ranges.get(i).is_some_and(|&(from, _)| from <= t)
+    }
+}
+
+/// The vents, as the coverage of each line they lie on.
+struct Vents {
+    // In the same order as `Family::ALL`
+    families: [BTreeMap<isize, Coverage>; 4],
+}
+
+impl Vents {
+    fn new(lines: &[Line], diagonals: bool) -> Self {
+        let mut segments: [BTreeMap<isize, Vec<(isize, isize)>>; 4] = Default::default();
+        for line in lines {
+            if !diagonals && matches!(line, Line::Diagonal(_, _)) {
+                continue;
+            }
+            let (family, line, range) = line.placement();
+            segments[family as usize]
+                .entry(line)
+                .or_default()
+                .push(range);
+        }
+        Vents {
+            families: segments.map(|lines| {
+                lines
+                    .into_iter()
+                    .map(|(line, segments)| (line, Coverage::new(&segments)))
+                    .collect()
+            }),
+        }
+    }
+
+    fn covered(&self, family: Family, p: Point, twice: bool) -> bool {
+        let (line, t) = family.locate(p);
+        self.families[family as usize]
+            .get(&line)
+            .is_some_and(|coverage| {
+                Coverage::contains(
+                    if twice {
+                        &coverage.twice
+                    } else {
+                        &coverage.once
+                    },
+                    t,
+                )
+            })
+    }
+
+    /// The points covered by segments from more than one family.
+    fn crossings(&self) -> HashSet<Point> {
+        let mut crossings = HashSet::new();
+        for (i, &family) in Family::ALL.iter().enumerate() {
+            for &other in &Family::ALL[i + 1..] {
+                for (&line, coverage) in &self.families[family as usize] {
+                    for &(from, to) in &coverage.once {
+                        // Walking along the line crosses the other family's lines in order
+                        let (first, _) = other.locate(family.point(line, from));
+                        let step = other.locate(family.point(line, from + 1)).0 - first;
+                        let last = first + step * (to - from);
+                        let lines = &self.families[other as usize];
+                        for &crossed in lines
+                            .range(min(first, last)..=max(first, last))
+                            .map(|(l, _)| l)
+                        {
+                            // Diagonals meet between points when their lines differ by an odd amount
+                            if (crossed - first) % step != 0 {
+                                continue;
+                            }
+                            let p = family.point(line, from + (crossed - first) / step);
+                            if self.covered(other, p, false) {
+                                crossings.insert(p);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        crossings
+    }
+
+    /// The number of points covered by at least two segments.
+    pub fn overlaps(&self) -> usize {
+        let overlapping: isize = self
+            .families
+            .iter()
+            .flat_map(|lines| lines.values())
+            .flat_map(|coverage| &coverage.twice)
+            .map(|(from, to)| to - from + 1)
+            .sum();
+        // Crossings count once each, however many families already counted them
+        let crossings = self.crossings();
+        let counted = crossings
+            .iter()
+            .map(|&p| {
+                Family::ALL
+                    .iter()
+                    .filter(|&&family| self.covered(family, p, true))
+                    .count()
+            })
+            .sum::<usize>();
+        overlapping as usize - counted + crossings.len()
+    }
+}
+
+/// How many segments cover each point, over the smallest area holding all of them.
+fn density(lines: &[Line], diagonals: bool) -> Grid<usize> {
+    let points: Vec<Point> = lines
+        .iter()
+        .filter(|line| diagonals || !matches!(line, Line::Diagonal(_, _)))
+        .flat_map(Line::points_all)
+        .collect();
+    let mut grid = Grid::new(0, 0, 0);
+    let (min_x, max_x) = points
+        .iter()
+        .map(|p| p.x)
+        .minmax()
+        .into_option()
+        .unwrap_or((0, -1));
+    let (min_y, max_y) = points
+        .iter()
+        .map(|p| p.y)
+        .minmax()
+        .into_option()
+        .unwrap_or((0, -1));
+    grid.refill((min_x, min_y), (max_x + 1, max_y + 1), 0, |_| 0);
+    for p in points {
+        let count = *grid.get((p.x, p.y));
+        grid.set((p.x, p.y), count + 1);
+    }
+    grid
+}
+
+/// Draws the density as the puzzle does, with `#` for ten or more.
+fn draw(density: &Grid<usize>) -> String {
+    density.render(|&count| match count {
+        0 => '.',
+        1..=9 => char::from_digit(count as u32, 10).unwrap(),
+        _ => '#',
+    })
+}
+
+const PALETTE: &Palette = &[
+    ('1', Colour::Grey),
+    ('2', Colour::Blue),
+    ('3', Colour::Cyan),
+    ('4', Colour::Green),
+    ('5', Colour::Yellow),
+    ('6', Colour::Red),
+    ('7', Colour::Red),
+    ('8', Colour::Red),
+    ('9', Colour::Red),
+    ('#', Colour::Magenta),
+];
+
+pub fn export(options: &export::Options) {
+    let input = Input::new(INPUT);
+    picture(&draw(&density(&input.lines, true)), PALETTE, options);
 }
 
 fn part1(input: &Input) -> usize {
-    count_intersections(&input.lines, Line::points_orthogonal)
+    Vents::new(&input.lines, false).overlaps()
 }
 
 fn part2(input: &Input) -> usize {
-    count_intersections(&input.lines, Line::points_all)
+    Vents::new(&input.lines, true).overlaps()
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .part_with(3, &[("diagonals", "true")], |p| {
+            draw(&density(&input.lines, p.get("diagonals")))
+        })
+        .run();
 }
 
 #[cfg(test)]
@@ -124,6 +389,38 @@ mod tests {
         assert_eq!(part2(&input), 12);
     }
 
+    #[test]
+    pub fn overlaps() {
+        let input = Input::new(TEST_INPUT);
+        let expected = "\
+1.1....11.
+.111...2..
+..2.1.111.
+...1.2.2..
+.112313211
+...1.2....
+..1...1...
+.1.....1..
+1.......1.
+222111....
+";
+        assert_eq!(draw(&density(&input.lines, true)), expected);
+
+        // Every way segments can meet, checked against counting every point
+        let input = Input::new(
+            "0,0 -> 6,6\n6,0 -> 0,6\n3,0 -> 3,9\n0,3 -> 8,3\n2,2 -> 4,4\n0,1 -> 5,6\n1,0 -> 1,8\n5,5 -> 5,5",
+        );
+        for diagonals in [false, true] {
+            let counted = density(&input.lines, diagonals).count(|&count| count >= 2);
+            assert_eq!(Vents::new(&input.lines, diagonals).overlaps(), counted);
+        }
+
+        // Far too big to count point by point
+        let input =
+            Input::new("0,0 -> 0,2000000000\n0,5 -> 0,3000000000\n-7,1000000000 -> 7,1000000000");
+        assert_eq!(Vents::new(&input.lines, false).overlaps(), 1999999996);
+    }
+
     #[test]
     pub fn answers() {
         let input = Input::new(INPUT);
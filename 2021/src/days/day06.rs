@@ -1,12 +1,14 @@
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+use itertools::Itertools;
+use num_bigint::BigUint;
+
 const INPUT: &str = include_str!("../../inputs/day06.txt");
 
 const NEW_TIMER: usize = 8;
 const AGE_AFTER_BIRTH: usize = 6;
 
-// Array where index = fish timer, value = number of fish with this timer
-type Fish = [usize; NEW_TIMER + 1];
-
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Input {
     pub fish_timers: Vec<usize>,
 }
@@ -23,52 +25,231 @@ impl Input {
     }
 }
 
-fn group_fish_by_timer(input: &Input) -> Fish {
-    let mut fish: Fish = [0; NEW_TIMER + 1];
-    for &age in input.fish_timers.iter() {
-        fish[age] += 1;
+/// How the timers work: a new fish starts at `new_timer`, and a fish that has just given birth
+/// goes back to `age_after_birth`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Rules {
+    pub new_timer: usize,
+    pub age_after_birth: usize,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            new_timer: NEW_TIMER,
+            age_after_birth: AGE_AFTER_BIRTH,
+        }
     }
-    fish
 }
 
-fn tick(state: &mut Fish) {
-    let number_of_zeros = state[0];
-    rotate(state);
-    add_new_fish(state, number_of_zeros);
+// A day moves every fish from one timer to another, which is the same linear map each day. So
+// rather than ticking day by day, the map is raised to the number of days by repeated squaring.
+
+/// The numbers a projection is worked out in.
+trait Arithmetic {
+    type Value: Clone;
+    fn value(&self, n: usize) -> Self::Value;
+    fn add(&self, a: &Self::Value, b: &Self::Value) -> Self::Value;
+    fn mul(&self, a: &Self::Value, b: &Self::Value) -> Self::Value;
 }
 
-fn rotate(state: &mut Fish) {
-    state.rotate_left(1);
-    state[AGE_AFTER_BIRTH] += state[NEW_TIMER];
+/// Exact counts, however big they get.
+struct Exact;
+
+impl Arithmetic for Exact {
+    type Value = BigUint;
+
+    fn value(&self, n: usize) -> BigUint {
+        BigUint::from(n)
+    }
+
+    fn add(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        a + b
+    }
+
+    fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        a * b
+    }
 }
 
-fn add_new_fish(state: &mut Fish, new_fish: usize) {
-    state[NEW_TIMER] = new_fish;
+/// Counts modulo some number, for when only the last few digits matter.
+struct Modulo(u64);
+
+impl Modulo {
+    fn new(modulus: u64) -> Self {
+        assert!(modulus > 0, "Can't count modulo 0");
+        Self(modulus)
+    }
 }
 
-fn run_iterations(input: &Input, n: usize) -> usize {
-    let mut fish = group_fish_by_timer(input);
-    for _ in 0..n {
-        tick(&mut fish);
+impl Arithmetic for Modulo {
+    type Value = u64;
+
+    fn value(&self, n: usize) -> u64 {
+        n as u64 % self.0
+    }
+
+    fn add(&self, a: &u64, b: &u64) -> u64 {
+        ((*a as u128 + *b as u128) % self.0 as u128) as u64
     }
 
-    fish.iter().sum()
+    fn mul(&self, a: &u64, b: &u64) -> u64 {
+        ((*a as u128 * *b as u128) % self.0 as u128) as u64
+    }
 }
 
-fn part1(input: &Input) -> usize {
-    run_iterations(input, 80)
+type Matrix<V> = Vec<Vec<V>>;
+
+fn multiply<A: Arithmetic>(
+    arithmetic: &A,
+    a: &Matrix<A::Value>,
+    b: &Matrix<A::Value>,
+) -> Matrix<A::Value> {
+    let size = a.len();
+    (0..size)
+        .map(|i| {
+            (0..size)
+                .map(|j| {
+                    (0..size).fold(arithmetic.value(0), |sum, k| {
+                        arithmetic.add(&sum, &arithmetic.mul(&a[i][k], &b[k][j]))
+                    })
+                })
+                .collect()
+        })
+        .collect()
 }
 
-fn part2(input: &Input) -> usize {
-    run_iterations(input, 256)
+fn identity<A: Arithmetic>(arithmetic: &A, size: usize) -> Matrix<A::Value> {
+    (0..size)
+        .map(|i| {
+            (0..size)
+                .map(|j| arithmetic.value(usize::from(i == j)))
+                .collect()
+        })
+        .collect()
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+/// Where the fish with each timer are the next day: `day[i][j]` of them end up at timer `i`
+/// for each fish at timer `j`. Timers go up to `size - 1`, which can be past a new fish's.
+fn day<A: Arithmetic>(arithmetic: &A, rules: Rules, size: usize) -> Matrix<A::Value> {
+    let mut day = vec![vec![0; size]; size];
+    for timer in 1..size {
+        day[timer - 1][timer] = 1;
+    }
+    // Giving birth puts a fish back to its age after birth, and adds a new one
+    day[rules.age_after_birth][0] += 1;
+    day[rules.new_timer][0] += 1;
+    day.into_iter()
+        .map(|row| row.into_iter().map(|n| arithmetic.value(n)).collect())
+        .collect()
+}
+
+/// How many fish there are with each timer after `days`.
+fn project<A: Arithmetic>(arithmetic: &A, input: &Input, rules: Rules, days: u64) -> Vec<A::Value> {
+    assert!(
+        rules.age_after_birth <= rules.new_timer,
+        "A fish can't go back to a timer later than a new fish"
+    );
+    // The input can start fish later than any new one would
+    let latest = input.fish_timers.iter().copied().max().unwrap_or(0);
+    let size = rules.new_timer.max(latest) + 1;
+    let mut fish = vec![0; size];
+    for &timer in &input.fish_timers {
+        fish[timer] += 1;
+    }
+
+    let mut power = day(arithmetic, rules, size);
+    let mut days_map = identity(arithmetic, size);
+    let mut remaining = days;
+    while remaining > 0 {
+        if remaining & 1 == 1 {
+            days_map = multiply(arithmetic, &days_map, &power);
+        }
+        power = multiply(arithmetic, &power, &power);
+        remaining >>= 1;
+    }
+
+    days_map
+        .iter()
+        .map(|row| {
+            row.iter()
+                .zip(&fish)
+                .fold(arithmetic.value(0), |sum, (n, &count)| {
+                    arithmetic.add(&sum, &arithmetic.mul(n, &arithmetic.value(count)))
+                })
+        })
+        .collect()
+}
+
+fn total<A: Arithmetic>(arithmetic: &A, by_timer: &[A::Value]) -> A::Value {
+    by_timer
+        .iter()
+        .fold(arithmetic.value(0), |sum, n| arithmetic.add(&sum, n))
+}
+
+fn part1(input: &Input) -> BigUint {
+    total(&Exact, &project(&Exact, input, Rules::default(), 80))
+}
+
+fn part2(input: &Input) -> BigUint {
+    total(&Exact, &project(&Exact, input, Rules::default(), 256))
+}
+
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        // The count for each timer, then the total, worked out exactly or modulo a number
+        .part_with(
+            3,
+            &[
+                ("days", "256"),
+                ("new_timer", "8"),
+                ("age_after_birth", "6"),
+                ("modulo", "none"),
+            ],
+            |p| {
+                let rules = Rules {
+                    new_timer: p.get("new_timer"),
+                    age_after_birth: p.get("age_after_birth"),
+                };
+                let days = p.get("days");
+                let breakdown = |by_timer: Vec<String>, total: String| {
+                    let lines = by_timer
+                        .iter()
+                        .enumerate()
+                        .map(|(timer, n)| format!("timer {}: {}", timer, n));
+                    lines.chain([format!("total: {}", total)]).join("\n")
+                };
+                match p.get::<String>("modulo").parse() {
+                    Ok(modulus) => {
+                        let arithmetic = Modulo::new(modulus);
+                        let by_timer = project(&arithmetic, &input, rules, days);
+                        let total = total(&arithmetic, &by_timer);
+                        breakdown(
+                            by_timer.iter().map(u64::to_string).collect(),
+                            total.to_string(),
+                        )
+                    }
+                    Err(_) => {
+                        let by_timer = project(&Exact, &input, rules, days);
+                        let total = total(&Exact, &by_timer);
+                        breakdown(
+                            by_timer.iter().map(BigUint::to_string).collect(),
+                            total.to_string(),
+                        )
+                    }
+                }
+            },
+        )
+        .run();
 }
 
 #[cfg(test)]
@@ -79,14 +260,51 @@ mod tests {
     #[test]
     pub fn examples() {
         let input = Input::new(TEST_INPUT);
-        assert_eq!(part1(&input), 5934);
-        assert_eq!(part2(&input), 26984457539);
+        assert_eq!(part1(&input), BigUint::from(5934u32));
+        assert_eq!(part2(&input), BigUint::from(26984457539u64));
+    }
+
+    #[test]
+    pub fn projections() {
+        let input = Input::new(TEST_INPUT);
+        // After 18 days: 6,0,6,4,5,6,0,1,1,2,6,0,1,1,1,2,2,3,3,4,6,7,8,8,8,8
+        let by_timer = project(&Exact, &input, Rules::default(), 18);
+        assert_eq!(by_timer, [3u32, 5, 3, 2, 2, 1, 5, 1, 4].map(BigUint::from));
+
+        // Far past where a usize overflows
+        let exact = total(&Exact, &project(&Exact, &input, Rules::default(), 2000));
+        assert!(exact.bits() > 64);
+        let modulus = 1_000_000_007;
+        let reduced = total(
+            &Modulo::new(modulus),
+            &project(&Modulo::new(modulus), &input, Rules::default(), 2000),
+        );
+        assert_eq!(BigUint::from(reduced), exact % modulus);
+
+        // Fish that breed every day double every day
+        let rules = Rules {
+            new_timer: 0,
+            age_after_birth: 0,
+        };
+        let input = Input::new("0,0,0");
+        assert_eq!(
+            project(&Exact, &input, rules, 100),
+            [BigUint::from(3u32) << 100]
+        );
+
+        // A fish starting later than a new one counts down until it first gives birth
+        let rules = Rules {
+            new_timer: 1,
+            age_after_birth: 0,
+        };
+        let by_timer = project(&Exact, &Input::new("4"), rules, 5);
+        assert_eq!(by_timer, [1u32, 1, 0, 0, 0].map(BigUint::from));
     }
 
     #[test]
     pub fn answers() {
         let input = Input::new(INPUT);
-        assert_eq!(part1(&input), 385391);
-        assert_eq!(part2(&input), 1728611055389);
+        assert_eq!(part1(&input), BigUint::from(385391u32));
+        assert_eq!(part2(&input), BigUint::from(1728611055389u64));
     }
 }
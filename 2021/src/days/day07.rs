@@ -1,6 +1,12 @@
+use std::ops::RangeInclusive;
+
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+use itertools::Itertools;
+
 const INPUT: &str = include_str!("../../inputs/day07.txt");
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Input {
     pub positions: Vec<usize>,
 }
@@ -17,76 +23,188 @@ impl Input {
     }
 }
 
-fn unsigned_abs_diff(a: usize, b: usize) -> usize {
-    if a > b {
-        a - b
-    } else {
-        b - a
+/// How much fuel a crab burns to move a distance.
+/// Costs have to be convex, so the total for all the crabs has a single valley.
+trait CostModel {
+    fn cost(&self, distance: usize) -> usize;
+
+    /// The positions the best one is proven to be among, if the model knows, given the crabs in
+    /// order.
+    fn shortcut(&self, _sorted: &[usize]) -> Option<(Method, RangeInclusive<usize>)> {
+        None
     }
 }
 
-fn required_fuel_simple(positions: &[usize], chosen: usize) -> usize {
-    positions
-        .iter()
-        .map(|&p| unsigned_abs_diff(p, chosen))
-        .sum()
+/// One fuel per step, which is best at a median.
+struct Linear;
+
+impl CostModel for Linear {
+    fn cost(&self, distance: usize) -> usize {
+        distance
+    }
+
+    fn shortcut(&self, sorted: &[usize]) -> Option<(Method, RangeInclusive<usize>)> {
+        let median = sorted[(sorted.len() - 1) / 2];
+        Some((Method::Median, median..=median))
+    }
 }
 
-fn required_fuel_complex(positions: &[usize], chosen: usize) -> usize {
-    positions
-        .iter()
-        .map(|&p| {
-            let distance = unsigned_abs_diff(p, chosen);
-            required_fuel_complex_distance(distance)
-        })
-        .sum()
+/// One more fuel for each step than the last.
+/// The best position is within half a step of the mean.
+struct Triangular;
+
+impl CostModel for Triangular {
+    fn cost(&self, distance: usize) -> usize {
+        distance * (distance + 1) / 2
+    }
+
+    fn shortcut(&self, sorted: &[usize]) -> Option<(Method, RangeInclusive<usize>)> {
+        let mean = mean(sorted);
+        Some((Method::Mean, mean.saturating_sub(1)..=mean + 1))
+    }
+}
+
+/// The square of the distance, which is best at the mean.
+struct Quadratic;
+
+impl CostModel for Quadratic {
+    fn cost(&self, distance: usize) -> usize {
+        distance * distance
+    }
+
+    fn shortcut(&self, sorted: &[usize]) -> Option<(Method, RangeInclusive<usize>)> {
+        let mean = mean(sorted);
+        Some((Method::Mean, mean..=mean + 1))
+    }
+}
+
+/// Any convex cost, found by searching.
+impl<F: Fn(usize) -> usize> CostModel for F {
+    fn cost(&self, distance: usize) -> usize {
+        self(distance)
+    }
+}
+
+/// Rounded down.
+fn mean(positions: &[usize]) -> usize {
+    positions.iter().sum::<usize>() / positions.len()
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Method {
+    Median,
+    Mean,
+    Search,
 }
 
-fn required_fuel_complex_distance(distance: usize) -> usize {
-    distance * (distance + 1) / 2
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Alignment {
+    pub position: usize,
+    pub fuel: usize,
+    /// How the position was found.
+    pub method: Method,
+}
+
+struct Solver<'a> {
+    sorted: Vec<usize>,
+    model: &'a dyn CostModel,
+}
+
+impl<'a> Solver<'a> {
+    pub fn new(positions: &[usize], model: &'a dyn CostModel) -> Self {
+        assert!(!positions.is_empty(), "There are no crabs to align");
+        let mut sorted = positions.to_vec();
+        sorted.sort_unstable();
+        Self { sorted, model }
+    }
+
+    pub fn fuel(&self, chosen: usize) -> usize {
+        self.sorted
+            .iter()
+            .map(|&p| self.model.cost(p.abs_diff(chosen)))
+            .sum()
+    }
+
+    pub fn best(&self) -> Alignment {
+        let (method, candidates) = self.model.shortcut(&self.sorted).unwrap_or_else(|| {
+            let position = self.search();
+            (Method::Search, position..=position)
+        });
+        let (position, fuel) = candidates
+            .map(|position| (position, self.fuel(position)))
+            .min_by_key(|&(_, fuel)| fuel)
+            .unwrap();
+        Alignment {
+            position,
+            fuel,
+            method,
+        }
+    }
+
+    /// A ternary search over the crabs' positions, done as a binary search for where the total
+    /// stops going down, which copes with flat stretches in the valley.
+    fn search(&self) -> usize {
+        let (mut low, mut high) = (self.sorted[0], *self.sorted.last().unwrap());
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if self.fuel(middle + 1) < self.fuel(middle) {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+        low
+    }
+
+    /// The fuel for every position from the first crab to the last, for plotting.
+    pub fn curve(&self) -> Vec<(usize, usize)> {
+        (self.sorted[0]..=*self.sorted.last().unwrap())
+            .map(|position| (position, self.fuel(position)))
+            .collect()
+    }
 }
 
 fn part1(input: &Input) -> usize {
-    // Take the median value of the input, by sorting it and finding the middle
-    let mut sorted = input.positions.clone();
-    sorted.sort_unstable();
-
-    let count = sorted.len();
-    // If an even number, the median is one of two. If odd, there is a middle one
-    let possible_median_indexes = if count % 2 == 0 {
-        vec![count / 2 - 2, count / 2]
-    } else {
-        vec![(count - 1) / 2]
-    };
-
-    // The answer is the fuel used to get to the median value in the input
-    possible_median_indexes
-        .iter()
-        .map(|&i| sorted[i])
-        .map(|v| required_fuel_simple(&input.positions, v))
-        .min()
-        .unwrap()
+    Solver::new(&input.positions, &Linear).best().fuel
 }
 
 fn part2(input: &Input) -> usize {
-    // Calculate the mean value of the input
-    let sum = input.positions.iter().sum::<usize>();
-    let count = input.positions.len();
-    let mean = sum / count;
+    Solver::new(&input.positions, &Triangular).best().fuel
+}
 
-    // Check the values from 1 below to 1 above the mean, and take the best
-    (mean - 1..=mean + 1)
-        .map(|v| required_fuel_complex(&input.positions, v))
-        .min()
-        .unwrap()
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        // `model` is linear, triangular, quadratic or a power such as `4`
+        .part_with(3, &[("model", "triangular"), ("curve", "false")], |p| {
+            let power = |n: u32| move |distance: usize| distance.pow(n);
+            let model: Box<dyn CostModel> = match p.get::<String>("model").as_str() {
+                "linear" => Box::new(Linear),
+                "triangular" => Box::new(Triangular),
+                "quadratic" => Box::new(Quadratic),
+                _ => Box::new(power(p.get("model"))),
+            };
+            let solver = Solver::new(&input.positions, model.as_ref());
+            let best = format!("{:?}", solver.best());
+            if !p.get::<bool>("curve") {
+                return best;
+            }
+            let curve = solver.curve().into_iter();
+            let rows = curve.map(|(position, fuel)| format!("{},{}", position, fuel));
+            [best, "position,fuel".to_string()]
+                .into_iter()
+                .chain(rows)
+                .join("\n")
+        })
+        .run();
 }
 
 #[cfg(test)]
@@ -101,6 +219,35 @@ mod tests {
         assert_eq!(part2(&input), 168);
     }
 
+    #[test]
+    pub fn models() {
+        let input = Input::new(TEST_INPUT);
+        let best = |model: &dyn CostModel| Solver::new(&input.positions, model).best();
+        let searched = |cost: fn(usize) -> usize| Solver::new(&input.positions, &cost).best();
+
+        // The shortcuts agree with searching
+        for (model, cost) in [
+            (&Linear as &dyn CostModel, (|d| d) as fn(usize) -> usize),
+            (&Triangular, |d| d * (d + 1) / 2),
+            (&Quadratic, |d| d * d),
+        ] {
+            let (shortcut, search) = (best(model), searched(cost));
+            assert_ne!(shortcut.method, Method::Search);
+            assert_eq!(search.method, Method::Search);
+            assert_eq!(shortcut.fuel, search.fuel);
+        }
+        assert_eq!(best(&Linear).position, 2);
+        assert_eq!(best(&Triangular).position, 5);
+
+        let solver = Solver::new(&input.positions, &Linear);
+        let curve = solver.curve();
+        assert_eq!(curve.len(), 17);
+        assert_eq!(curve[1], (1, 41));
+        assert_eq!(curve[10], (10, 71));
+        let lowest = curve.iter().map(|&(_, fuel)| fuel).min();
+        assert_eq!(lowest, Some(37));
+    }
+
     #[test]
     pub fn answers() {
         let input = Input::new(INPUT);
@@ -1,37 +1,78 @@
-use std::collections::{HashMap, HashSet};
-
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
 use itertools::Itertools;
 
 use crate::helpers::digits::from_digits;
 
 const INPUT: &str = include_str!("../../inputs/day08.txt");
 
-const POSSIBLES: [u8; 8] = [0, 0, 1, 1, 1, 3, 3, 1];
-
-type Segment = usize;
-type Digit = HashSet<Segment>;
-
-fn from_char(char: char) -> Segment {
-    match char {
-        'a' => 0,
-        'b' => 1,
-        'c' => 2,
-        'd' => 3,
-        'e' => 4,
-        'f' => 5,
-        'g' => 6,
-        _ => panic!("Unexpected char {}", char),
+/*
+ * Segments are lettered as follows on a seven-segment display:
+ *
+ *   aaa
+ *  b   c
+ *  b   c
+ *   ddd
+ *  e   f
+ *  e   f
+ *   ggg
+ */
+const SEVEN_SEGMENT: &str = "abcefg cf acdeg acdfg bcdf abdfg abdefg acf abcdefg abcdfg";
+/// The same display showing A, b, C, d, E and F after the ten digits.
+const HEXADECIMAL: &str =
+    "abcefg cf acdeg acdfg bcdf abdfg abdefg acf abcdefg abcdfg abcdef bdefg abeg cdefg abdeg abde";
+
+/// The segments or wires that are lit, one bit for each letter.
+type Pattern = u32;
+
+fn pattern(letters: &str) -> Pattern {
+    letters.chars().fold(0, |pattern, char| {
+        assert!(char.is_ascii_lowercase(), "Unexpected char {}", char);
+        pattern | 1 << (char as u32 - 'a' as u32)
+    })
+}
+
+/// Which segments each digit lights up, where a digit's value is its place in the table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Table {
+    pub segments: usize,
+    pub digits: Vec<Pattern>,
+}
+
+impl Table {
+    /// Reads the digits in order, each as the letters of its segments, such as `cf` for 1.
+    pub fn parse(digits: &str) -> Self {
+        let digits: Vec<Pattern> = digits.split_whitespace().map(pattern).collect();
+        let all = digits.iter().fold(0, |all, digit| all | digit);
+        Table {
+            segments: (Pattern::BITS - all.leading_zeros()) as usize,
+            digits,
+        }
+    }
+
+    fn all(&self) -> Pattern {
+        (1 << self.segments) - 1
+    }
+
+    /// The digits with as many segments as the pattern, which is all that's known before
+    /// deducing the wiring.
+    fn same_size(&self, pattern: Pattern) -> impl Iterator<Item = Pattern> + '_ {
+        let lit = pattern.count_ones();
+        self.digits
+            .iter()
+            .copied()
+            .filter(move |d| d.count_ones() == lit)
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Sample {
-    // Numbers 1-10 in some order
-    pub inputs: [Digit; 10],
-    pub outputs: [Digit; 4],
+    // Every digit, in some order
+    pub inputs: Vec<Pattern>,
+    pub outputs: Vec<Pattern>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Input {
     pub lines: Vec<Sample>,
 }
@@ -41,23 +82,11 @@ impl Input {
         let lines = input
             .lines()
             .map(|line| {
-                let mut split = line.split(" | ").map(|s| s.split(' '));
-                let inputs = split
-                    .next()
-                    .unwrap()
-                    .map(|s| s.chars().map(from_char).collect::<Digit>());
-
-                let outputs = split
-                    .next()
-                    .unwrap()
-                    .map(|s| s.chars().map(from_char).collect::<Digit>());
-
-                assert!(split.next().is_none());
-
-                let inputs = inputs.collect::<Vec<Digit>>().try_into().unwrap();
-                let outputs = outputs.collect::<Vec<Digit>>().try_into().unwrap();
-
-                Sample { inputs, outputs }
+                let (inputs, outputs) = line.split_once(" | ").unwrap();
+                Sample {
+                    inputs: inputs.split(' ').map(pattern).collect(),
+                    outputs: outputs.split(' ').map(pattern).collect(),
+                }
             })
             .collect();
 
@@ -65,129 +94,203 @@ impl Input {
     }
 }
 
-fn part1(input: &Input) -> usize {
-    input
-        .lines
-        .iter()
-        .flat_map(|line| {
-            // For each line, only include the output digits that can only be a single number
-            line.outputs.iter().filter(|s| POSSIBLES[s.len()] == 1)
-        })
-        .count()
-}
+/// Which segment each wire lights, by wire.
+type Wiring = Vec<usize>;
 
-fn digits() -> [Digit; 10] {
-    /*
-     * Segments that are turned on for each digit, numbered as follows:
-     *
-     *   000
-     *  1   2
-     *  1   2
-     *   333
-     *  4   5
-     *  4   5
-     *   666
-     */
-    [
-        HashSet::from_iter([0, 1, 2, 4, 5, 6]),    // 0
-        HashSet::from_iter([2, 5]),                // 1
-        HashSet::from_iter([0, 2, 3, 4, 6]),       // 2
-        HashSet::from_iter([0, 2, 3, 5, 6]),       // 3
-        HashSet::from_iter([1, 2, 3, 5]),          // 4
-        HashSet::from_iter([0, 1, 3, 5, 6]),       // 5
-        HashSet::from_iter([0, 1, 3, 4, 5, 6]),    // 6
-        HashSet::from_iter([0, 2, 5]),             // 7
-        HashSet::from_iter([0, 1, 2, 3, 4, 5, 6]), // 8
-        HashSet::from_iter([0, 1, 2, 3, 5, 6]),    // 9
-    ]
-}
-
-// Count of digits in which each segment occurs
-fn count_uses_of_each_segment(digits: &[Digit; 10]) -> [usize; 7] {
-    (0usize..7)
-        .map(|segment| {
-            digits
-                .iter()
-                .filter(|&digit| digit.contains(&segment))
-                .count()
-        })
-        .collect::<Vec<Segment>>()
-        .try_into()
-        .unwrap()
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Deduction {
+    Unique(Wiring),
+    /// More than one wiring fits what was seen, such as these two.
+    Ambiguous(Wiring, Wiring),
+    /// No wiring could show what was seen.
+    Contradiction,
 }
 
-// Calculate a unique score for each digit
-fn digit_unique_scores() -> [usize; 10] {
-    let digits = digits();
-    let uses_of_each_segment = count_uses_of_each_segment(&digits);
-    let scores = score_each_digit(&digits, uses_of_each_segment);
+// Each wire starts out able to light any segment. Each pattern seen must be one of the digits
+// of its size, so a wire in it can only light a segment of one of those digits, and a wire out
+// of it a segment outside one. Narrowing this down for every pattern in turn, and taking a
+// segment that is settled away from every other wire, repeats until nothing changes. If that
+// doesn't settle every wire, each choice for the least certain wire is tried in turn.
 
-    // Ensure each digit has a unique score
-    assert!(scores.iter().unique().count() == 10);
-    scores
+struct Solver<'a> {
+    table: &'a Table,
+    seen: &'a [Pattern],
 }
 
-// Sum the segment count for each segment in each digit
-fn score_each_digit<const N: usize>(
-    digits: &[Digit; N],
-    uses_of_each_segment: [usize; 7],
-) -> [usize; N] {
-    digits
-        .iter()
-        .map(|digit| {
-            digit
-                .iter()
-                .map(|&segment| uses_of_each_segment[segment])
-                .sum()
-        })
-        .collect::<Vec<usize>>()
-        .try_into()
-        .unwrap()
-}
+impl Solver<'_> {
+    /// Narrows down the segments each wire could light, returning false if a wire has none left.
+    fn propagate(&self, candidates: &mut [Pattern]) -> bool {
+        let all = self.table.all();
+        loop {
+            let before = candidates.to_vec();
+            for &seen in self.seen {
+                let (mut inside, mut outside) = (0, 0);
+                for digit in self.table.same_size(seen) {
+                    let fits = (0..candidates.len()).all(|wire| {
+                        let segments = if seen >> wire & 1 == 1 {
+                            digit
+                        } else {
+                            all & !digit
+                        };
+                        candidates[wire] & segments != 0
+                    });
+                    if fits {
+                        inside |= digit;
+                        outside |= all & !digit;
+                    }
+                }
+                for (wire, segments) in candidates.iter_mut().enumerate() {
+                    *segments &= if seen >> wire & 1 == 1 {
+                        inside
+                    } else {
+                        outside
+                    };
+                }
+            }
+
+            for wire in 0..candidates.len() {
+                if candidates[wire].count_ones() == 1 {
+                    let settled = candidates[wire];
+                    for (other, segments) in candidates.iter_mut().enumerate() {
+                        if other != wire {
+                            *segments &= !settled;
+                        }
+                    }
+                }
+            }
 
-fn digit_scores_map() -> HashMap<usize, usize> {
-    let digit_scores = digit_unique_scores();
+            if candidates.contains(&0) {
+                return false;
+            }
+            if before == candidates {
+                return true;
+            }
+        }
+    }
 
-    // Invert this array, so we can look up a score to get the original digit
-    let mut digit_scores_map = HashMap::new();
-    for (digit, &score) in digit_scores.iter().enumerate() {
-        digit_scores_map.insert(score, digit);
+    /// Finds wirings that fit, stopping once there are two.
+    fn search(&self, mut candidates: Vec<Pattern>, found: &mut Vec<Wiring>) {
+        if found.len() >= 2 || !self.propagate(&mut candidates) {
+            return;
+        }
+        let unsettled = (0..candidates.len())
+            .filter(|&wire| candidates[wire].count_ones() > 1)
+            .min_by_key(|&wire| candidates[wire].count_ones());
+        match unsettled {
+            None => found.push(
+                candidates
+                    .iter()
+                    .map(|segment| segment.trailing_zeros() as usize)
+                    .collect(),
+            ),
+            Some(wire) => {
+                for segment in 0..self.table.segments {
+                    if candidates[wire] >> segment & 1 == 1 {
+                        let mut guess = candidates.clone();
+                        guess[wire] = 1 << segment;
+                        self.search(guess, found);
+                    }
+                }
+            }
+        }
     }
-    digit_scores_map
 }
 
-fn part2(input: &Input) -> i64 {
-    // On each line, the input includes all 10 digits
-    // We need to decode the outputs
+/// Works out which wire goes to which segment from the patterns seen on the display.
+fn deduce(table: &Table, seen: &[Pattern]) -> Deduction {
+    let solver = Solver { table, seen };
+    let mut found = Vec::new();
+    solver.search(vec![table.all(); table.segments], &mut found);
+    let mut found = found.into_iter();
+    match (found.next(), found.next()) {
+        (None, _) => Deduction::Contradiction,
+        (Some(wiring), None) => Deduction::Unique(wiring),
+        (Some(first), Some(second)) => Deduction::Ambiguous(first, second),
+    }
+}
 
-    // First calculate a score for each digit. This is unique, which is lucky.
-    // We score each segment based on how many digits use it, then score each digit based on the scores of each segment in it
-    let digit_scores = digit_scores_map();
+/// The digit a pattern of wires shows, if it shows one.
+fn decode(table: &Table, wiring: &Wiring, seen: Pattern) -> Option<usize> {
+    let segments = (0..wiring.len())
+        .filter(|wire| seen >> wire & 1 == 1)
+        .fold(0, |segments, wire| segments | 1 << wiring[wire]);
+    table.digits.iter().position(|&digit| digit == segments)
+}
 
+fn part1(input: &Input) -> usize {
+    let table = Table::parse(SEVEN_SEGMENT);
     input
         .lines
         .iter()
-        .map(|line| {
-            // Count the number of times each segment occurs in the input
-            let uses_of_each_segment = count_uses_of_each_segment(&line.inputs);
-
-            // Now we have enough information to decode any digit (but we only bother with the output)
-            let digits = score_each_digit(&line.outputs, uses_of_each_segment).map(|score| {
-                // This is the same unique score we got earlier, so we can just look it up to find the digit
-                *digit_scores.get(&score).unwrap() as isize
-            });
+        .flat_map(|line| {
+            // For each line, only include the output digits that can only be a single number
+            line.outputs
+                .iter()
+                .filter(|&&output| table.same_size(output).count() == 1)
+        })
+        .count()
+}
 
-            from_digits(digits.into_iter())
+fn part2(input: &Input) -> i64 {
+    let table = Table::parse(SEVEN_SEGMENT);
+    input
+        .lines
+        .iter()
+        .map(|line| {
+            let seen = [&line.inputs[..], &line.outputs[..]].concat();
+            let Deduction::Unique(wiring) = deduce(&table, &seen) else {
+                panic!("Failed to work out the wiring");
+            };
+            let digits = line
+                .outputs
+                .iter()
+                .map(|&output| decode(&table, &wiring, output).unwrap() as isize);
+            from_digits(digits)
         })
         .sum()
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        // The wiring of one line, shown as the segment each of the wires a, b, c... lights
+        .part_with(3, &[("line", "0"), ("table", "seven")], |p| {
+            let table = match p.get::<String>("table").as_str() {
+                "seven" => Table::parse(SEVEN_SEGMENT),
+                "hex" => Table::parse(HEXADECIMAL),
+                digits => Table::parse(&digits.replace(',', " ")),
+            };
+            let line = &input.lines[p.get::<usize>("line")];
+            let letters = |wiring: &Wiring| {
+                wiring
+                    .iter()
+                    .map(|&segment| (b'a' + segment as u8) as char)
+                    .join("")
+            };
+            match deduce(&table, &[&line.inputs[..], &line.outputs[..]].concat()) {
+                Deduction::Unique(wiring) => {
+                    let outputs = line
+                        .outputs
+                        .iter()
+                        .map(|&output| decode(&table, &wiring, output).unwrap())
+                        .join(" ");
+                    format!("wiring {}, showing {}", letters(&wiring), outputs)
+                }
+                Deduction::Ambiguous(first, second) => {
+                    format!("ambiguous: {} or {}", letters(&first), letters(&second))
+                }
+                Deduction::Contradiction => "contradiction".to_string(),
+            }
+        })
+        .run();
 }
 
 #[cfg(test)]
@@ -202,6 +305,48 @@ mod tests {
         assert_eq!(part2(&input), 61229);
     }
 
+    #[test]
+    pub fn deductions() {
+        let seven = Table::parse(SEVEN_SEGMENT);
+        assert_eq!(seven.segments, 7);
+        let seen = |letters: &str| letters.split(' ').map(pattern).collect::<Vec<_>>();
+
+        // The wiring from the puzzle's explanation
+        let wiring = deduce(
+            &seven,
+            &seen("acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab"),
+        );
+        let expected = Deduction::Unique(vec![2, 5, 6, 0, 1, 3, 4]);
+        assert_eq!(wiring, expected);
+
+        // 1 and 7 alone can't tell most of the wires apart
+        assert!(matches!(
+            deduce(&seven, &seen("ab dab")),
+            Deduction::Ambiguous(_, _)
+        ));
+        // No digit lights exactly one segment
+        assert_eq!(deduce(&seven, &seen("ab c")), Deduction::Contradiction);
+
+        // Hexadecimal, with the wires swapped in pairs: ab, cd, ef, and g left alone
+        let hex = Table::parse(HEXADECIMAL);
+        let swap = |digit: &Pattern| {
+            let pairs = [(0, 1), (2, 3), (4, 5)];
+            pairs.iter().fold(digit & 1 << 6, |wires, &(a, b)| {
+                wires | (digit >> a & 1) << b | (digit >> b & 1) << a
+            })
+        };
+        let scrambled: Vec<Pattern> = hex.digits.iter().map(swap).collect();
+        let Deduction::Unique(wiring) = deduce(&hex, &scrambled) else {
+            panic!("Expected a single wiring");
+        };
+        assert_eq!(wiring, [1, 0, 3, 2, 5, 4, 6]);
+        let shown: Vec<_> = scrambled
+            .iter()
+            .map(|&seen| decode(&hex, &wiring, seen))
+            .collect();
+        assert_eq!(shown, (0..16).map(Some).collect::<Vec<_>>());
+    }
+
     #[test]
     pub fn answers() {
         let input = Input::new(INPUT);
@@ -1,11 +1,14 @@
-use std::collections::{HashSet, VecDeque};
+use std::collections::HashMap;
 
+use aoc_common::animate::Colour;
+use aoc_common::export::{self, picture, Palette};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
 use itertools::Itertools;
-use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
 const INPUT: &str = include_str!("../../inputs/day09.txt");
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Input {
     pub grid: Grid,
 }
@@ -40,25 +43,6 @@ impl Grid {
     fn iter(&self) -> GridIter {
         self.into_iter()
     }
-
-    fn basin_size(&self, p: &Point) -> usize {
-        let mut visited: HashSet<Point> = HashSet::new();
-        let mut queue: VecDeque<Point> = VecDeque::from([p.clone()]);
-
-        // BFS through the points
-        while !queue.is_empty() {
-            let point = queue.pop_front().unwrap();
-            visited.insert(point.clone());
-            for neighbour in self.neighbours(&point) {
-                // Stop at points of height 9, and skip if we've already seen them
-                if neighbour.value < 9 && !visited.contains(&neighbour) {
-                    queue.push_back(neighbour);
-                }
-            }
-        }
-
-        visited.len()
-    }
 }
 
 fn neighbouring_points(row: usize, col: usize, height: usize, width: usize) -> Vec<(usize, usize)> {
@@ -138,38 +122,189 @@ impl Input {
     }
 }
 
-fn part1(input: &Input) -> (usize, Vec<Point>) {
-    let low_points: Vec<Point> = input
+/// Merges cells into sets, keeping each set's size.
+struct UnionFind {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+            sizes: vec![1; len],
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parents[i] != i {
+            // Halve the path on the way up, so later finds are quicker
+            self.parents[i] = self.parents[self.parents[i]];
+            i = self.parents[i];
+        }
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        let (big, small) = if self.sizes[a] >= self.sizes[b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        self.parents[small] = big;
+        self.sizes[big] += self.sizes[small];
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Basin {
+    pub id: usize,
+    pub size: usize,
+    /// The lowest cell, or the first of them when several are as low.
+    pub low_point: Point,
+    /// The cells next to a wall or the edge of the map.
+    pub boundary: Vec<Point>,
+}
+
+#[derive(Clone, Debug)]
+struct Basins {
+    /// The basin each cell is in, row by row, with walls in none.
+    pub labels: Vec<Option<usize>>,
+    /// In order of their first cell, row by row.
+    pub basins: Vec<Basin>,
+}
+
+/// Finds the basins, which are divided by cells at least as high as the wall.
+fn label(grid: &Grid, wall: usize) -> Basins {
+    let index = |p: &Point| p.row * grid.width + p.col;
+    let mut sets = UnionFind::new(grid.values.len());
+    for p in grid.iter().filter(|p| p.value < wall) {
+        for n in grid.neighbours(&p) {
+            if n.value < wall {
+                sets.union(index(&p), index(&n));
+            }
+        }
+    }
+
+    let mut ids = HashMap::new();
+    let mut labels = Vec::with_capacity(grid.values.len());
+    let mut basins: Vec<Basin> = Vec::new();
+    for p in grid.iter() {
+        if p.value >= wall {
+            labels.push(None);
+            continue;
+        }
+        let root = sets.find(index(&p));
+        let id = *ids.entry(root).or_insert_with(|| {
+            basins.push(Basin {
+                id: basins.len(),
+                size: sets.sizes[root],
+                low_point: p.clone(),
+                boundary: Vec::new(),
+            });
+            basins.len() - 1
+        });
+        let basin = &mut basins[id];
+        if p.value < basin.low_point.value {
+            basin.low_point = p.clone();
+        }
+        let neighbours = grid.neighbours(&p);
+        if neighbours.len() < 4 || neighbours.iter().any(|n| n.value >= wall) {
+            basin.boundary.push(p);
+        }
+        labels.push(Some(id));
+    }
+    Basins { labels, basins }
+}
+
+/// Draws each basin in one of a few letters, so neighbouring basins usually differ, and walls
+/// as `#`.
+fn draw(grid: &Grid, basins: &Basins) -> String {
+    let letters: Vec<char> = PALETTE.iter().map(|&(c, _)| c).collect();
+    basins
+        .labels
+        .chunks(grid.width)
+        .map(|row| {
+            row.iter()
+                .map(|label| match label {
+                    Some(id) => letters[1 + id % (letters.len() - 1)],
+                    None => '#',
+                })
+                .collect::<String>()
+        })
+        .join("\n")
+}
+
+const PALETTE: &Palette = &[
+    ('#', Colour::Grey),
+    ('a', Colour::Red),
+    ('b', Colour::Green),
+    ('c', Colour::Yellow),
+    ('d', Colour::Blue),
+    ('e', Colour::Magenta),
+    ('f', Colour::Cyan),
+];
+
+pub fn export(options: &export::Options) {
+    let input = Input::new(INPUT);
+    picture(&draw(&input.grid, &label(&input.grid, 9)), PALETTE, options);
+}
+
+fn part1(input: &Input) -> usize {
+    input
         .grid
         .iter()
         .filter(|p| input.grid.neighbours(p).iter().all(|n| n.value > p.value))
-        .collect();
-
-    let score = low_points.iter().map(|p| p.value + 1).sum();
-
-    (score, low_points)
+        .map(|p| p.value + 1)
+        .sum()
 }
 
-fn part2(input: &Input, low_points: &[Point]) -> usize {
-    low_points
-        // The DFS can be parallelised
-        .par_iter()
-        .map(|low_point| input.grid.basin_size(low_point))
-        .collect::<Vec<usize>>()
-        // Take the three largest basins, by inverting and taking the three smallest, then inverting again
-        .into_iter()
-        .map(|size| -(size as isize))
-        .k_smallest(3)
-        .map(|size| (-size) as usize)
+fn part2(input: &Input) -> usize {
+    label(&input.grid, 9)
+        .basins
+        .iter()
+        .map(|basin| basin.size)
+        .sorted_unstable_by(|a, b| b.cmp(a))
+        .take(3)
         .product()
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let (answer1, low_points) = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input, &low_points);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        // The basin map, and every basin by size
+        .part_with(3, &[("wall", "9")], |p| {
+            let basins = label(&input.grid, p.get("wall"));
+            let sizes = basins.basins.iter().map(|basin| {
+                let low = &basin.low_point;
+                format!(
+                    "basin {}: {} cells, {} on the boundary, lowest {} at ({}, {})",
+                    basin.id,
+                    basin.size,
+                    basin.boundary.len(),
+                    low.value,
+                    low.row,
+                    low.col
+                )
+            });
+            [draw(&input.grid, &basins)]
+                .into_iter()
+                .chain(sizes)
+                .join("\n")
+        })
+        .run();
 }
 
 #[cfg(test)]
@@ -180,16 +315,36 @@ mod tests {
     #[test]
     pub fn examples() {
         let input = Input::new(TEST_INPUT);
-        let (answer1, low_points) = part1(&input);
-        assert_eq!(answer1, 15);
-        assert_eq!(part2(&input, &low_points), 1134);
+        assert_eq!(part1(&input), 15);
+        assert_eq!(part2(&input), 1134);
+    }
+
+    #[test]
+    pub fn basins() {
+        let input = Input::new(TEST_INPUT);
+        let basins = label(&input.grid, 9);
+        let sizes: Vec<usize> = basins.basins.iter().map(|basin| basin.size).collect();
+        assert_eq!(sizes, [3, 9, 14, 9]);
+        let top_left = &basins.basins[0];
+        assert_eq!((top_left.low_point.row, top_left.low_point.col), (0, 1));
+        assert_eq!(top_left.boundary.len(), 3);
+        assert_eq!(
+            draw(&input.grid, &basins).lines().next(),
+            Some("aa###bbbbb")
+        );
+
+        // Lower walls leave less in the basins
+        let basins = label(&input.grid, 5);
+        assert_eq!(basins.basins.len(), 2);
+        assert_eq!(basins.labels.iter().flatten().count(), 12);
+        // And no walls leave a single basin
+        assert_eq!(label(&input.grid, 10).basins.len(), 1);
     }
 
     #[test]
     pub fn answers() {
         let input = Input::new(INPUT);
-        let (answer1, low_points) = part1(&input);
-        assert_eq!(answer1, 456);
-        assert_eq!(part2(&input, &low_points), 1047744);
+        assert_eq!(part1(&input), 456);
+        assert_eq!(part2(&input), 1047744);
     }
 }
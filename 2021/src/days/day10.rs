@@ -1,8 +1,10 @@
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
 use itertools::Itertools;
 
 const INPUT: &str = include_str!("../../inputs/day10.txt");
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Input {
     pub lines: Vec<Vec<char>>,
 }
@@ -15,120 +17,304 @@ impl Input {
     }
 }
 
-enum ParseResult {
-    Incomplete(Vec<char>),
-    Corrupted(char),
+/// The puzzle's chunks, as pairs of opening and closing characters.
+const CHUNKS: &str = "() [] {} <>";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Delimiters {
+    pub pairs: Vec<(char, char)>,
 }
 
-impl ParseResult {
-    fn corrupted_score(self) -> Option<usize> {
-        match self {
-            ParseResult::Incomplete(_) => None,
-            ParseResult::Corrupted(char) => {
-                let score = corrupted_score(&char)
-                    .unwrap_or_else(|| panic!("Unexpected illegal char {}", char));
-                Some(score)
-            }
-        }
+impl Delimiters {
+    /// Reads pairs such as `() []`.
+    pub fn parse(pairs: &str) -> Self {
+        let pairs = pairs
+            .split_whitespace()
+            .map(|pair| {
+                let chars: Vec<char> = pair.chars().collect();
+                assert_eq!(chars.len(), 2, "Delimiters come in pairs, not {}", pair);
+                (chars[0], chars[1])
+            })
+            .collect();
+        Delimiters { pairs }
     }
 
-    fn completion_score(self) -> Option<usize> {
-        match self {
-            ParseResult::Corrupted(_) => None,
-            ParseResult::Incomplete(stack) => stack
-                .iter()
-                .rev()
-                .map(completion_score)
-                .reduce(|acc, score| acc * 5 + score),
-        }
+    fn opening(&self, char: char) -> Option<usize> {
+        self.pairs.iter().position(|&(open, _)| open == char)
+    }
+
+    fn closing(&self, char: char) -> Option<usize> {
+        self.pairs.iter().position(|&(_, close)| close == char)
     }
 }
 
-fn corrupted_score(char: &char) -> Option<usize> {
-    let score = match char {
-        ')' => 3,
-        ']' => 57,
-        '}' => 1197,
-        '>' => 25137,
-        _ => return None,
-    };
-    Some(score)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct SyntaxError {
+    /// Counting from 1.
+    pub column: usize,
+    pub found: char,
+    /// What should have been there instead, or `None` if nothing should.
+    pub expected: Option<char>,
 }
 
-fn completion_score(char: &char) -> usize {
-    match char {
-        '(' => 1,
-        '[' => 2,
-        '{' => 3,
-        '<' => 4,
-        _ => panic!("Unexpected char in stack"),
+/// Reads a line up to its first illegal closing character, as the puzzle does, returning the
+/// pairs still open. Anything that isn't a delimiter is passed over.
+fn scan(delimiters: &Delimiters, line: &[char]) -> Result<Vec<usize>, SyntaxError> {
+    let mut open = Vec::new();
+    for (i, &char) in line.iter().enumerate() {
+        if let Some(pair) = delimiters.opening(char) {
+            open.push(pair);
+        } else if let Some(pair) = delimiters.closing(char) {
+            // A closing char must match the top of the stack
+            if open.last() != Some(&pair) {
+                return Err(SyntaxError {
+                    column: i + 1,
+                    found: char,
+                    expected: open.last().map(|&open| delimiters.pairs[open].1),
+                });
+            }
+            open.pop();
+        }
     }
+    Ok(open)
+}
+
+fn closers(delimiters: &Delimiters, open: &[usize]) -> String {
+    open.iter()
+        .rev()
+        .map(|&pair| delimiters.pairs[pair].1)
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Check {
+    /// Each character the repair changes or drops.
+    pub errors: Vec<SyntaxError>,
+    /// The line with as few characters changed or dropped as leaves no chunk closed wrongly.
+    pub repaired: String,
+    /// What closes every chunk still open at the end of the repaired line.
+    pub completion: String,
 }
 
-fn parse(line: &[char]) -> ParseResult {
-    let mut stack: Vec<char> = Vec::new();
+// A repaired line is a series of balanced runs, with chunks left open between them. For the
+// delimiters i..j, `balanced[i][j]` is the fewest changes that make a balanced run, either
+// dropping the first delimiter or pairing it with a later one, changing either or both to make
+// the pair. `prefix[j]` is the fewest changes for the first j delimiters, ending in a run or in
+// a chunk left open. Adding a closing character is never needed, as dropping what it would
+// close costs the same.
 
-    for char in line {
-        // If char is a closing char, it must match the top of the stack
-        if is_closing(char) {
-            if stack.is_empty() {
-                return ParseResult::Corrupted(*char);
+#[derive(Clone, Copy, Debug)]
+enum Step {
+    Drop,
+    /// With the delimiter at the index, as the pair at the index.
+    Pair(usize, usize),
+    Open,
+    /// Ending a run that starts at the index.
+    Run(usize),
+}
+
+/// Checks a whole line, finding the smallest repair. Anything that isn't a delimiter is kept.
+fn check(delimiters: &Delimiters, line: &[char]) -> Check {
+    let columns: Vec<usize> = (0..line.len())
+        .filter(|&i| delimiters.opening(line[i]).is_some() || delimiters.closing(line[i]).is_some())
+        .collect();
+    let chars: Vec<char> = columns.iter().map(|&i| line[i]).collect();
+    let n = chars.len();
+    let pairing = |i: usize, k: usize| {
+        (0..delimiters.pairs.len())
+            .map(|pair| {
+                let (open, close) = delimiters.pairs[pair];
+                let changes = usize::from(chars[i] != open) + usize::from(chars[k] != close);
+                (changes, pair)
+            })
+            .min()
+            .unwrap()
+    };
+
+    let mut balanced = vec![vec![(0, Step::Drop); n + 1]; n + 1];
+    for length in 1..=n {
+        for i in 0..=n - length {
+            let j = i + length;
+            let mut best = (balanced[i + 1][j].0 + 1, Step::Drop);
+            for k in i + 1..j {
+                let (changes, pair) = pairing(i, k);
+                let changes = changes + balanced[i + 1][k].0 + balanced[k + 1][j].0;
+                if changes < best.0 {
+                    best = (changes, Step::Pair(k, pair));
+                }
             }
-            if matching_char(char) == *stack.last().unwrap() {
-                // Pop the top of the stack
-                stack.pop();
-            } else {
-                return ParseResult::Corrupted(*char);
+            balanced[i][j] = best;
+        }
+    }
+    let mut prefix = vec![(0, Step::Open); n + 1];
+    for j in 1..=n {
+        let mut best = match delimiters.opening(chars[j - 1]) {
+            Some(_) => (prefix[j - 1].0, Step::Open),
+            None => (prefix[j - 1].0 + 1, Step::Drop),
+        };
+        for i in 0..j {
+            let changes = prefix[i].0 + balanced[i][j].0;
+            if changes < best.0 {
+                best = (changes, Step::Run(i));
             }
-        } else {
-            stack.push(*char);
         }
+        prefix[j] = best;
     }
 
-    ParseResult::Incomplete(stack)
+    // What each delimiter becomes, if it's kept
+    let mut repairs: Vec<Option<char>> = chars.iter().copied().map(Some).collect();
+    let mut runs = Vec::new();
+    let mut j = n;
+    while j > 0 {
+        match prefix[j].1 {
+            Step::Run(i) => {
+                runs.push((i, j));
+                j = i;
+            }
+            step => {
+                if let Step::Drop = step {
+                    repairs[j - 1] = None;
+                }
+                j -= 1;
+            }
+        }
+    }
+    while let Some((i, j)) = runs.pop() {
+        if i == j {
+            continue;
+        }
+        match balanced[i][j].1 {
+            Step::Pair(k, pair) => {
+                let (open, close) = delimiters.pairs[pair];
+                repairs[i] = Some(open);
+                repairs[k] = Some(close);
+                runs.push((i + 1, k));
+                runs.push((k + 1, j));
+            }
+            _ => {
+                repairs[i] = None;
+                runs.push((i + 1, j));
+            }
+        }
+    }
+
+    let errors = columns
+        .iter()
+        .zip(&chars)
+        .zip(&repairs)
+        .filter(|&((_, &found), &repair)| repair != Some(found))
+        .map(|((&column, &found), &expected)| SyntaxError {
+            column: column + 1,
+            found,
+            expected,
+        })
+        .collect();
+    let mut kept: Vec<Option<char>> = line.iter().copied().map(Some).collect();
+    for (&column, &repair) in columns.iter().zip(&repairs) {
+        kept[column] = repair;
+    }
+    let repaired: Vec<char> = kept.into_iter().flatten().collect();
+    let open = scan(delimiters, &repaired).expect("A repaired line has no errors");
+    Check {
+        errors,
+        repaired: repaired.into_iter().collect(),
+        completion: closers(delimiters, &open),
+    }
 }
 
-fn is_closing(char: &char) -> bool {
-    corrupted_score(char).is_some()
+/// Points for each pair of delimiters, in the same order as the pairs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Scoring {
+    /// For the first illegal closing character of a corrupted line.
+    pub corrupted: Vec<usize>,
+    /// For each closing character of a completion, after multiplying what came before by `base`.
+    pub completion: Vec<usize>,
+    pub base: usize,
 }
 
-fn matching_char(char: &char) -> char {
-    match char {
-        ')' => '(',
-        ']' => '[',
-        '}' => '{',
-        '>' => '<',
-        _ => panic!("Unexpected char {}", char),
+impl Scoring {
+    pub fn puzzle() -> Self {
+        Scoring {
+            corrupted: vec![3, 57, 1197, 25137],
+            completion: vec![1, 2, 3, 4],
+            base: 5,
+        }
+    }
+
+    /// Scores the first illegal character the puzzle finds, which needn't be one the smallest
+    /// repair changes.
+    pub fn corrupted(&self, delimiters: &Delimiters, line: &[char]) -> Option<usize> {
+        let error = scan(delimiters, line).err()?;
+        Some(self.corrupted[delimiters.closing(error.found).unwrap()])
+    }
+
+    /// Only lines that are incomplete but not corrupted have a completion score.
+    pub fn completion(&self, delimiters: &Delimiters, line: &[char]) -> Option<usize> {
+        let open = scan(delimiters, line).ok()?;
+        open.iter()
+            .rev()
+            .map(|&pair| self.completion[pair])
+            .reduce(|acc, score| acc * self.base + score)
     }
 }
 
 fn part1(input: &Input) -> usize {
+    let (delimiters, scoring) = (Delimiters::parse(CHUNKS), Scoring::puzzle());
     input
         .lines
         .iter()
-        .map(|line| parse(line))
-        .filter_map(|result| result.corrupted_score())
+        .filter_map(|line| scoring.corrupted(&delimiters, line))
         .sum()
 }
 
 fn part2(input: &Input) -> usize {
+    let (delimiters, scoring) = (Delimiters::parse(CHUNKS), Scoring::puzzle());
     let sorted = input
         .lines
         .iter()
-        .map(|line| parse(line))
-        .filter_map(|result| result.completion_score())
+        .filter_map(|line| scoring.completion(&delimiters, line))
         .sorted()
         .collect::<Vec<usize>>();
     let middle_index = (sorted.len() - 1) / 2;
     sorted[middle_index]
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        // Every error in a line, and the line repaired and completed
+        .part_with(3, &[("line", "0"), ("delimiters", "()[]{}<>")], |p| {
+            let pairs: String = p.get("delimiters");
+            let pairs = pairs
+                .chars()
+                .chunks(2)
+                .into_iter()
+                .map(|mut pair| pair.join(""))
+                .join(" ");
+            let check = check(
+                &Delimiters::parse(&pairs),
+                &input.lines[p.get::<usize>("line")],
+            );
+            let errors = check.errors.iter().map(|error| match error.expected {
+                Some(expected) => format!(
+                    "column {}: expected {}, found {}",
+                    error.column, expected, error.found
+                ),
+                None => format!("column {}: unexpected {}", error.column, error.found),
+            });
+            errors
+                .chain([format!("{}{}", check.repaired, check.completion)])
+                .join("\n")
+        })
+        .run();
 }
 
 #[cfg(test)]
@@ -143,6 +329,74 @@ mod tests {
         assert_eq!(part2(&input), 288957);
     }
 
+    #[test]
+    pub fn checks() {
+        let chunks = Delimiters::parse(CHUNKS);
+        let puzzle = Scoring::puzzle();
+        let line = |s: &str| s.chars().collect::<Vec<char>>();
+        let errors = |check: &Check| {
+            let errors = check.errors.iter();
+            errors.map(|e| (e.column, e.expected)).collect::<Vec<_>>()
+        };
+
+        let corrupted = line("{([(<{}[<>[]}>{[]{[(<()>");
+        let checked = check(&chunks, &corrupted);
+        assert_eq!(
+            checked.errors,
+            [SyntaxError {
+                column: 13,
+                found: '}',
+                expected: Some(']')
+            }]
+        );
+        assert_eq!(checked.repaired, "{([(<{}[<>[]]>{[]{[(<()>");
+        assert_eq!(checked.completion, ")]}})])}");
+        assert_eq!(puzzle.corrupted(&chunks, &corrupted), Some(1197));
+        assert_eq!(puzzle.completion(&chunks, &corrupted), None);
+
+        // One stray character is one error, not two
+        let stray = check(&chunks, &line("([)]"));
+        assert_eq!(errors(&stray), [(3, None)]);
+        assert_eq!(stray.repaired, "([]");
+        assert_eq!(stray.completion, ")");
+
+        // Changing a closing character can save dropping several
+        let changed = check(&chunks, &line("[(]]"));
+        assert_eq!(errors(&changed), [(3, Some(')'))]);
+        assert_eq!(changed.repaired, "[()]");
+
+        // Closing what each chunk expects, left to right, would take three changes here
+        let several = check(&chunks, &line("(]>[<)"));
+        assert_eq!(errors(&several), [(2, Some('<')), (6, None)]);
+        assert_eq!(several.repaired, "(<>[<");
+        assert_eq!(several.completion, ">])");
+
+        let incomplete = line("<{([{{}}[<[[[<>{}]]]>[]]");
+        assert!(check(&chunks, &incomplete).errors.is_empty());
+        assert_eq!(check(&chunks, &incomplete).completion, "])}>");
+        assert_eq!(puzzle.completion(&chunks, &incomplete), Some(294));
+
+        // Anything that isn't a delimiter is left alone
+        let words = line("(a [b] c]");
+        let checked = check(&chunks, &words);
+        assert_eq!(errors(&checked), [(9, None)]);
+        assert_eq!(checked.repaired, "(a [b] c");
+        assert_eq!(puzzle.corrupted(&chunks, &words), Some(57));
+
+        // Other delimiters, and scoring the completion by its length
+        let quotes = Delimiters::parse("«» ‹›");
+        let checked = check(&quotes, &line("«‹›»»«‹"));
+        assert_eq!(errors(&checked), [(5, None)]);
+        assert_eq!(checked.repaired, "«‹›»«‹");
+        assert_eq!(checked.completion, "›»");
+        let length = Scoring {
+            corrupted: vec![0, 0],
+            completion: vec![1, 1],
+            base: 1,
+        };
+        assert_eq!(length.completion(&quotes, &line("«‹")), Some(2));
+    }
+
     #[test]
     pub fn answers() {
         let input = Input::new(INPUT);
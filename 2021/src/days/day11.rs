@@ -1,131 +1,185 @@
-use std::{
-    collections::{HashSet, VecDeque},
-    fmt,
-};
+use std::fmt;
+
+use aoc_common::animate::{colour_chars, play, Animation, Colour, Options};
+use aoc_common::automaton::{Automaton, CellRule, Synchronous, UntilStable};
+use aoc_common::export::{self, record, Palette};
+use aoc_common::grid::{moore, Grid, Point};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
 
 const INPUT: &str = include_str!("../../inputs/day11.txt");
 
-const WIDTH: usize = 10;
-const HEIGHT: usize = 10;
+/// Marks an octopus that has already flashed this step
+const FLASHED: u8 = u8::MAX;
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Input {
-    pub octopodes: Octopodes<WIDTH, HEIGHT>,
+    pub octopodes: Octopodes,
 }
 
 impl Input {
     pub fn new(input: &str) -> Input {
-        let energy: [[u8; WIDTH]; HEIGHT] = input
-            .lines()
-            .map(|line| {
-                line.chars()
-                    .map(|c| c.to_digit(10).unwrap().try_into().unwrap())
-                    .collect::<Vec<u8>>()
-                    .try_into()
-                    .unwrap()
-            })
-            .collect::<Vec<[u8; WIDTH]>>()
-            .try_into()
-            .unwrap();
-        let octopodes = Octopodes { energy, flashes: 0 };
+        let energy = Grid::parse(input.trim(), 0, |c| c.to_digit(10).unwrap() as u8);
+        let octopodes = Octopodes {
+            automaton: Automaton::new(
+                energy,
+                (
+                    Synchronous(Charge),
+                    UntilStable(Synchronous(Flash)),
+                    Synchronous(Reset),
+                ),
+            ),
+            flashes: 0,
+        };
         Input { octopodes }
     }
 }
 
+/// Every octopus gains one energy
 #[derive(Clone, Debug)]
-struct Octopodes<const W: usize, const H: usize> {
-    energy: [[u8; W]; H],
-    pub flashes: usize,
+struct Charge;
+
+impl CellRule<u8> for Charge {
+    fn next(&self, grid: &Grid<u8>, p: Point) -> u8 {
+        grid.get(p) + 1
+    }
+
+    fn next_background(&self, background: &u8) -> u8 {
+        *background
+    }
 }
 
-impl<const W: usize, const H: usize> fmt::Display for Octopodes<W, H> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for row in 0..H {
-            for col in 0..W {
-                write!(f, "{}", self.energy[row][col])?;
-            }
-            writeln!(f)?;
+/// Octopuses over 9 energy flash, and give one energy to each of their neighbours.
+/// This is repeated until no more octopuses are ready to flash.
+#[derive(Clone, Debug)]
+struct Flash;
+
+fn ready_to_flash(energy: u8) -> bool {
+    energy > 9 && energy != FLASHED
+}
+
+impl CellRule<u8> for Flash {
+    fn next(&self, grid: &Grid<u8>, p: Point) -> u8 {
+        let energy = *grid.get(p);
+        if energy == FLASHED || ready_to_flash(energy) {
+            FLASHED
+        } else {
+            let flashing_neighbours = moore(p)
+                .iter()
+                .filter(|n| ready_to_flash(*grid.get(**n)))
+                .count();
+            energy + flashing_neighbours as u8
         }
-        Ok(())
+    }
+
+    fn next_background(&self, background: &u8) -> u8 {
+        *background
     }
 }
 
-impl<const W: usize, const H: usize> Octopodes<W, H> {
-    pub fn tick(&mut self) -> bool {
-        let mut ready_to_flash = VecDeque::new();
-
-        for row in 0..H {
-            for col in 0..W {
-                self.energy[row][col] += 1;
-                if self.energy[row][col] > 9 {
-                    ready_to_flash.push_back((row, col));
-                }
-            }
-        }
+/// Set the energy of all flashed octopuses back to zero
+#[derive(Clone, Debug)]
+struct Reset;
 
-        // We need to do a BFS through the octopuses
-        // Start with octopuses of energy 10 (already added to queue)
-        let mut flashed = HashSet::new();
-        while !ready_to_flash.is_empty() {
-            let (row, col) = ready_to_flash.pop_front().unwrap();
-            flashed.insert((row, col));
-            for (r, c) in neighbouring_points(row, col, H, W) {
-                if !flashed.contains(&(r, c)) && self.increase_energy_and_ready(r, c) {
-                    ready_to_flash.push_back((r, c));
-                }
-            }
+impl CellRule<u8> for Reset {
+    fn next(&self, grid: &Grid<u8>, p: Point) -> u8 {
+        match grid.get(p) {
+            &FLASHED => 0,
+            energy => *energy,
         }
+    }
 
-        let number_flashes = flashed.len();
-        self.flashes += number_flashes;
+    fn next_background(&self, background: &u8) -> u8 {
+        *background
+    }
+}
 
-        // Set the energy of all flashes octopuses back to zero
-        for (row, col) in flashed.into_iter() {
-            self.energy[row][col] = 0;
-        }
+type OctopusRules = (
+    Synchronous<Charge>,
+    UntilStable<Synchronous<Flash>>,
+    Synchronous<Reset>,
+);
+
+#[derive(Clone, Debug)]
+struct Octopodes {
+    automaton: Automaton<Grid<u8>, OctopusRules>,
+    pub flashes: usize,
+}
+
+impl fmt::Display for Octopodes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let energy = self.automaton.world();
+        write!(
+            f,
+            "{}",
+            energy.render(|e| char::from_digit(*e as u32, 10).unwrap())
+        )
+    }
+}
+
+impl Octopodes {
+    fn len(&self) -> usize {
+        let energy = self.automaton.world();
+        energy.width() * energy.height()
+    }
+
+    pub fn tick(&mut self) -> bool {
+        self.automaton.step();
+
+        // Only the octopuses that flashed have zero energy after a step
+        let number_flashes = self.automaton.world().count(|e| *e == 0);
+        self.flashes += number_flashes;
 
         // Have all octopuses flashed?
-        number_flashes == W * H
+        number_flashes == self.len()
     }
+}
 
-    /// Increase the energy of an octopus
-    /// Returns true if this increase causes the octopus to flash
-    fn increase_energy_and_ready(&mut self, row: usize, col: usize) -> bool {
-        if self.energy[row][col] > 9 {
-            // If it has already flashed, no need to increase energy further
-            false
-        } else {
-            // Otherwise increase energy and check if it is ready to flash
-            self.energy[row][col] += 1;
-            self.energy[row][col] == 10
+impl Animation for Octopodes {
+    fn step(&mut self) -> bool {
+        // Stop once they are all flashing in sync
+        let energy = self.automaton.world();
+        if energy.count(|e| *e == 0) == self.len() {
+            return false;
         }
+        self.tick();
+        true
+    }
+
+    fn frame(&self) -> String {
+        let energy = colour_chars(&self.to_string(), |c| match c {
+            '0' => Some(Colour::Yellow),
+            '\n' => None,
+            _ => Some(Colour::Grey),
+        });
+        format!("{}{} flashes\n", energy, self.flashes)
     }
 }
 
-fn neighbouring_points(row: usize, col: usize, height: usize, width: usize) -> Vec<(usize, usize)> {
-    // Need signed integers to get around the bound checks cleanly
-    let x = row as isize;
-    let y = col as isize;
-    [
-        (x - 1, y),
-        (x + 1, y),
-        (x, y - 1),
-        (x, y + 1),
-        (x - 1, y - 1),
-        (x - 1, y + 1),
-        (x + 1, y - 1),
-        (x + 1, y + 1),
-    ]
-    .into_iter()
-    // Filter out anything < 0 before converting back to unsigned integers
-    .filter(|&(a, b)| a >= 0 && b >= 0)
-    .map(|(a, b)| (a as usize, b as usize))
-    // Then filter out anything too large
-    .filter(|&(a, b)| a < height && b < width)
-    .collect()
-}
-
-fn part1(input: Input) -> Octopodes<WIDTH, HEIGHT> {
+const PALETTE: &Palette = &[
+    ('0', Colour::Yellow),
+    ('1', Colour::Grey),
+    ('2', Colour::Grey),
+    ('3', Colour::Grey),
+    ('4', Colour::Grey),
+    ('5', Colour::Grey),
+    ('6', Colour::Grey),
+    ('7', Colour::Grey),
+    ('8', Colour::Grey),
+    ('9', Colour::Grey),
+];
+
+pub fn animate(options: &Options) {
+    let mut octopodes = Input::new(INPUT).octopodes;
+    play(&mut octopodes, options);
+}
+
+pub fn export(options: &export::Options) {
+    let mut octopodes = Input::new(INPUT).octopodes;
+    record(&mut octopodes, ToString::to_string, PALETTE, options);
+}
+
+fn part1(input: Input) -> Octopodes {
     let mut octopodes = input.octopodes;
     for _ in 0..100 {
         octopodes.tick();
@@ -133,7 +187,7 @@ fn part1(input: Input) -> Octopodes<WIDTH, HEIGHT> {
     octopodes
 }
 
-fn part2(mut octopodes: Octopodes<WIDTH, HEIGHT>) -> usize {
+fn part2(mut octopodes: Octopodes) -> usize {
     let mut steps = 100;
     while !octopodes.tick() {
         steps += 1;
@@ -141,12 +195,22 @@ fn part2(mut octopodes: Octopodes<WIDTH, HEIGHT>) -> usize {
     steps + 1
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let octopodes = part1(input);
-    println!("Part 1: {}", octopodes.flashes);
-    let answer2 = part2(octopodes);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    let octopodes = run.part_with(
+        1,
+        || part1(input),
+        |octopodes| octopodes.flashes.to_string(),
+    );
+    run.part(2, || part2(octopodes));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(input.clone()).flashes)
+        .part(2, || part2(part1(input.clone())))
+        .run();
 }
 
 #[cfg(test)]
@@ -1,8 +1,10 @@
 use std::collections::HashMap;
 
-use serde_scan::scan;
-
-use crate::helpers::increment::Increment;
+use aoc_common::memo::Memo;
+use aoc_common::parse::{edge, lines, parse_all};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+use nom::character::complete::alpha1;
 
 const INPUT: &str = include_str!("../../inputs/day12.txt");
 
@@ -35,21 +37,17 @@ impl<'a> Cave<'a> {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Input<'a> {
     pub adjacency: Adjacency<'a>,
 }
 
 impl Input<'_> {
     pub fn new(input: &str) -> Input {
-        let adjacency = input
-            .lines()
-            .map(|line| {
-                let (from_str, to_str) = scan!("{}-{}" <- line).unwrap();
-                let from = Cave::new(from_str);
-                let to = Cave::new(to_str);
-                (from, to)
-            })
+        let adjacency = parse_all(input, lines(edge("-", alpha1)))
+            .unwrap()
+            .into_iter()
+            .map(|(from, to)| (Cave::new(from), Cave::new(to)))
             .fold(HashMap::new(), |mut adjacency: Adjacency, (from, to)| {
                 if to != Cave::Start && from != Cave::End {
                     adjacency.entry(from.clone()).or_default().push(to.clone());
@@ -65,67 +63,71 @@ impl Input<'_> {
 
 type Adjacency<'a> = HashMap<Cave<'a>, Vec<Cave<'a>>>;
 
+// The current cave, the set of small caves visited so far, and whether we can still revisit one
+type Route<'a> = (Cave<'a>, u64, bool);
+
 struct Graph<'a> {
     adjacency: &'a Adjacency<'a>,
-    visits: HashMap<Cave<'a>, usize>,
+    // Each small cave gets its own bit, so that a set of visited caves fits in a u64
+    small_caves: HashMap<&'a Cave<'a>, u64>,
 }
 
-impl Graph<'_> {
-    pub fn new<'a>(adjacency: &'a Adjacency) -> Graph<'a> {
+impl<'a> Graph<'a> {
+    pub fn new(adjacency: &'a Adjacency) -> Graph<'a> {
+        let small_caves: HashMap<_, _> = adjacency
+            .keys()
+            .filter(|cave| cave.is_small())
+            .enumerate()
+            .map(|(i, cave)| (cave, 1 << i))
+            .collect();
+        assert!(small_caves.len() <= 64, "Too many small caves");
         Graph {
             adjacency,
-            visits: HashMap::with_capacity(adjacency.len()),
+            small_caves,
         }
     }
 
-    pub fn count_routes(&mut self, allow_revisit_one_small: bool) -> usize {
-        self.count_dfs(Cave::Start, 0, allow_revisit_one_small)
+    pub fn count_routes(&self, allow_revisit_one_small: bool) -> usize {
+        // Routes from the same cave with the same visited caves always continue the same way
+        let mut memo = Memo::new();
+        let routes = memo.solve(
+            &(Cave::Start, 0, allow_revisit_one_small),
+            &|route, recurse| self.count_from(route, recurse),
+        );
+        memo.stats().trace();
+        routes
     }
 
-    fn count_visits(&self, cave: &Cave) -> usize {
-        *self.visits.get(cave).unwrap_or(&0)
-    }
-
-    fn already_visited(&self, cave: &Cave) -> bool {
-        self.count_visits(cave) > 0
-    }
-
-    fn count_dfs(&mut self, start: Cave, count: usize, allow_revisit_one_small: bool) -> usize {
-        // If this is the end, then this path has completed and we can increment the current count
-        if start == Cave::End {
-            return count + 1;
+    fn count_from(
+        &self,
+        (start, visited, allow_revisit_one_small): &Route<'a>,
+        recurse: &mut dyn FnMut(&Route<'a>) -> usize,
+    ) -> usize {
+        // If this is the end, then this path has completed
+        if *start == Cave::End {
+            return 1;
         }
 
-        let mut next_count: usize = count;
-
         // Check all neighbouring caves
-        for cave in self.adjacency.get(&start).unwrap() {
-            // We can visit any non-small cave, or any non-visited small cave
-            let is_small = cave.is_small();
-            let already_visited = self.already_visited(cave);
-            // If `allow_revisit_one_small` is set, we can visit any cave
-            let can_visit = !is_small || !already_visited || allow_revisit_one_small;
-            if can_visit {
-                // If this is the first time we've made a second visit to this cave, then we no longer allow revisits
-                let this_is_second_visit = is_small && already_visited;
-                let allow_revisit = allow_revisit_one_small && !this_is_second_visit;
-
-                // Increment the visit count
-                self.visits.increment(cave.clone(), 1);
-
-                next_count = self.count_dfs(cave.clone(), next_count, allow_revisit);
-
-                // Decrement the visit count
-                self.visits.decrement(cave.clone(), 1);
+        let mut count = 0;
+        for cave in &self.adjacency[start] {
+            // Large caves have no bit, so they never count as visited
+            let bit = self.small_caves.get(cave).copied().unwrap_or(0);
+            if visited & bit == 0 {
+                // We can visit any non-small cave, or any non-visited small cave
+                count += recurse(&(cave.clone(), visited | bit, *allow_revisit_one_small));
+            } else if *allow_revisit_one_small {
+                // This is the first time we've made a second visit to a small cave, so we no longer allow revisits
+                count += recurse(&(cave.clone(), *visited, false));
             }
         }
 
-        next_count
+        count
     }
 }
 
 fn count_routes_start(adjacency: &Adjacency, allow_revisit_one_small: bool) -> usize {
-    let mut graph = Graph::new(adjacency);
+    let graph = Graph::new(adjacency);
     graph.count_routes(allow_revisit_one_small)
 }
 
@@ -137,12 +139,18 @@ fn part2(input: &Input) -> usize {
     count_routes_start(&input.adjacency, true)
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
@@ -1,16 +1,26 @@
 use std::{collections::HashSet, fmt};
 
-use serde_scan::scan;
+use aoc_common::parse::{integer, lines, parse_all};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, line_ending},
+    combinator::map,
+    sequence::{pair, preceded, separated_pair},
+    IResult,
+};
 
 const INPUT: &str = include_str!("../../inputs/day13.txt");
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Input {
     pub points: Points,
     pub instructions: Vec<Fold>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Points(HashSet<(usize, usize)>);
 
 /*
@@ -19,8 +29,8 @@ struct Points(HashSet<(usize, usize)>);
  * The second value, y, increases downward.
 */
 impl Points {
-    pub fn new() -> Self {
-        Self(HashSet::new())
+    pub fn new(points: impl IntoIterator<Item = (usize, usize)>) -> Self {
+        Self(points.into_iter().collect())
     }
 
     pub fn fold(&mut self, fold: &Fold) {
@@ -80,30 +90,32 @@ impl fmt::Display for Points {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 enum Fold {
     X(usize),
     Y(usize),
 }
 
+fn parse_point(i: &str) -> IResult<&str, (usize, usize)> {
+    separated_pair(integer, char(','), integer)(i)
+}
+
+fn parse_fold(i: &str) -> IResult<&str, Fold> {
+    let x = map(preceded(tag("x="), integer), Fold::X);
+    let y = map(preceded(tag("y="), integer), Fold::Y);
+    preceded(tag("fold along "), alt((x, y)))(i)
+}
+
 impl Input {
     pub fn new(input: &str) -> Input {
-        let mut points = Points::new();
-        let mut instructions = Vec::new();
-        for line in input.lines() {
-            if let Ok((x, y)) = scan!("{},{}" <- line) {
-                points.insert((x, y));
-            } else if let Ok((axis, value)) = scan!("fold along {}={}" <- line) {
-                let fold = match axis {
-                    "x" => Fold::X,
-                    "y" => Fold::Y,
-                    _ => unreachable!(),
-                };
-                instructions.push(fold(value));
-            }
-        }
+        let blank_line = pair(line_ending, line_ending);
+        let (points, instructions) = parse_all(
+            input,
+            separated_pair(lines(parse_point), blank_line, lines(parse_fold)),
+        )
+        .unwrap();
         Input {
-            points,
+            points: Points::new(points),
             instructions,
         }
     }
@@ -117,21 +129,27 @@ fn part1(input: &Input) -> usize {
     points.len()
 }
 
-fn part2(input: &Input) {
+fn part2(input: &Input) -> String {
     let mut points = input.points.clone();
     for fold in &input.instructions {
         points.fold(fold);
     }
 
-    println!("{}", points);
+    points.to_string()
+}
+
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    println!("Part 2");
-    part2(&input);
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
@@ -149,5 +167,22 @@ mod tests {
     pub fn answers() {
         let input = Input::new(INPUT);
         assert_eq!(part1(&input), 781);
+        // The letters are drawn with trailing spaces, which don't matter
+        let drawing: Vec<String> = part2(&input)
+            .trim_end()
+            .lines()
+            .map(|line| line.trim_end().to_string())
+            .collect();
+        assert_eq!(
+            drawing,
+            [
+                "XXX  XXXX XXX   XX   XX    XX XXX  XXX",
+                "X  X X    X  X X  X X  X    X X  X X  X",
+                "X  X XXX  X  X X    X       X X  X XXX",
+                "XXX  X    XXX  X    X XX    X XXX  X  X",
+                "X    X    X X  X  X X  X X  X X    X  X",
+                "X    XXXX X  X  XX   XXX  XX  X    XXX",
+            ]
+        );
     }
 }
@@ -1,14 +1,26 @@
 use std::collections::HashMap;
 
 use crate::helpers::increment::Increment;
+use aoc_common::parse::{lines, parse_all};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
 use itertools::Itertools;
-use serde_scan::scan;
+use nom::{
+    bytes::complete::tag,
+    character::complete::{alpha1, line_ending, satisfy},
+    sequence::{pair, separated_pair},
+    IResult,
+};
 
 const INPUT: &str = include_str!("../../inputs/day14.txt");
 
+fn element(i: &str) -> IResult<&str, char> {
+    satisfy(|c| c.is_ascii_uppercase())(i)
+}
+
 type CountOfEachPair = HashMap<(char, char), usize>;
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Input {
     pub count_of_each_pair: CountOfEachPair,
     pub instructions: HashMap<(char, char), char>,
@@ -18,10 +30,16 @@ struct Input {
 
 impl Input {
     pub fn new(input: &str) -> Input {
-        let mut input_iter = input.lines();
-
-        // The starting polymers are on the first line
-        let polymers = input_iter.next().unwrap();
+        // The starting polymers are on the first line, then a blank line before the rules
+        let (polymers, instructions) = parse_all(
+            input,
+            separated_pair(
+                alpha1,
+                pair(line_ending, line_ending),
+                lines(separated_pair(pair(element, element), tag(" -> "), element)),
+            ),
+        )
+        .unwrap();
         let polymer_chars: Vec<char> = polymers.chars().collect();
         let first_char = *polymer_chars.first().unwrap();
         let last_char = *polymer_chars.last().unwrap();
@@ -33,22 +51,9 @@ impl Input {
                 acc
             });
 
-        // Skip blank line
-        input_iter.next();
-
-        let instructions = input_iter
-            .map(|line| {
-                let (text, c): (&str, char) = scan!("{} -> {}" <- line).unwrap();
-                let char_vec: Vec<char> = text.chars().collect();
-                let a = char_vec[0];
-                let b = char_vec[1];
-                ((a, b), c)
-            })
-            .collect();
-
         Input {
             count_of_each_pair: pairs_count,
-            instructions,
+            instructions: instructions.into_iter().collect(),
             first_char,
             last_char,
         }
@@ -117,12 +122,22 @@ fn part2(input: &Input) -> usize {
     run_insertions_and_count(input, 40)
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part_with(1, &[("steps", "10")], |p| {
+            run_insertions_and_count(&input, p.get("steps"))
+        })
+        .part_with(2, &[("steps", "40")], |p| {
+            run_insertions_and_count(&input, p.get("steps"))
+        })
+        .run();
 }
 
 #[cfg(test)]
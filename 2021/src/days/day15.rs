@@ -3,6 +3,9 @@ use std::{
     collections::{BinaryHeap, HashMap},
 };
 
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+
 const INPUT: &str = include_str!("../../inputs/day15.txt");
 
 #[derive(Clone, Eq, PartialEq, Debug)]
@@ -26,7 +29,7 @@ impl PartialOrd for Point {
 type Position = (usize, usize);
 type Node = (usize, usize);
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Grid {
     grid: Vec<Vec<usize>>,
 }
@@ -51,7 +54,7 @@ impl Grid {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Input {
     pub grid: Grid,
 }
@@ -171,12 +174,18 @@ fn part2(input: &Input) -> usize {
     shortest_distance(&input.grid, height, width)
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
@@ -1,8 +1,10 @@
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
 use itertools::Itertools;
 
 const INPUT: &str = include_str!("../../inputs/day16.txt");
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Input {
     pub bits: Vec<u8>,
 }
@@ -190,12 +192,22 @@ fn part2(packet: Packet) -> usize {
     packet.value()
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let packet = part1(input);
-    println!("Part 1: {}", packet.total_versions());
-    let answer2 = part2(packet);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    let packet = run.part_with(
+        1,
+        || part1(input),
+        |packet| packet.total_versions().to_string(),
+    );
+    run.part(2, || part2(packet));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(input.clone()).total_versions())
+        .part(2, || part2(part1(input.clone())))
+        .run();
 }
 
 #[cfg(test)]
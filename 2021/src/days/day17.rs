@@ -1,13 +1,21 @@
-use serde_scan::scan;
+use aoc_common::parse::{integer, parse_all};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+use nom::{
+    bytes::complete::tag,
+    character::complete::space0,
+    sequence::{delimited, preceded, separated_pair, tuple},
+    IResult,
+};
 
 const INPUT: &str = include_str!("../../inputs/day17.txt");
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Input {
     pub target: Target,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Target {
     x: (isize, isize),
     y: (isize, isize),
@@ -74,15 +82,24 @@ enum Result {
     Miss(usize),
 }
 
+// Spaces around the punctuation are optional, as in `x=20..30` or `x = 20 .. 30`
+fn spaced<'a>(punctuation: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+    delimited(space0, tag(punctuation), space0)
+}
+
+fn parse_range<'a>(axis: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, (isize, isize)> {
+    preceded(
+        tuple((tag(axis), spaced("="))),
+        separated_pair(integer, spaced(".."), integer),
+    )
+}
+
 impl Input {
     pub fn new(input: &str) -> Input {
-        let line = input.trim();
-        let (x1, x2, y1, y2) = scan!("target area: x = {} .. {}, y = {} .. {}" <- line).unwrap();
+        let area = separated_pair(parse_range("x"), spaced(","), parse_range("y"));
+        let (x, y) = parse_all(input, preceded(tag("target area: "), area)).unwrap();
         Input {
-            target: Target {
-                x: (x1, x2),
-                y: (y1, y2),
-            },
+            target: Target { x, y },
         }
     }
 }
@@ -131,12 +148,18 @@ fn part2(input: &Input) -> usize {
     hits
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
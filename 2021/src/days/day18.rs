@@ -1,10 +1,13 @@
 use std::{fmt, ops::Add};
 
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+use aoc_common::tree::{NodeId, Tree};
 use itertools::Itertools;
 
 const INPUT: &str = include_str!("../../inputs/day18.txt");
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Input {
     pub numbers: Vec<SnailNumber>,
 }
@@ -20,182 +23,62 @@ impl Input {
     }
 }
 
-// This struct and enum basically describe a binary tree
-#[derive(Clone, PartialEq)]
+// Each snail number owns a binary tree, where only pairs have children
+#[derive(Clone)]
 struct SnailNumber {
-    left: SnailItem,
-    right: SnailItem,
+    tree: Tree<SnailItem>,
+    root: NodeId<SnailItem>,
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 enum SnailItem {
     Number(usize),
-    // Has to be boxed to avoid an infinitely-sized type
-    SnailNumber(Box<SnailNumber>),
+    Pair,
+}
+
+impl PartialEq for SnailNumber {
+    fn eq(&self, other: &Self) -> bool {
+        self.tree.subtree_eq(self.root, &other.tree, other.root)
+    }
 }
 
 // Useful for test failures
 impl fmt::Debug for SnailNumber {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "[{},{}]", self.left, self.right)
+        write!(f, "{}", self)
     }
 }
 
 impl fmt::Display for SnailNumber {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "[{},{}]", self.left, self.right)
+        self.fmt_item(f, self.root)
     }
 }
 
-enum ExplodeStatus {
-    Exploding(usize),
-    No,
-    Done,
-}
-
-enum SplitStatus {
-    Splitting(SnailNumber),
-    No,
-    Done,
-}
-
 impl SnailNumber {
+    /// Reads either a pair or a plain number, returning how many bytes were read
     pub fn read(input: &[u8]) -> (SnailNumber, usize) {
-        // The first char of any Snailfish number is a [
-        assert_eq!(input[0] as char, '[');
-
-        // Read the left hand side, then a comma
-        let (left, left_read) = SnailItem::read(&input[1..]);
-        assert_eq!(input[1 + left_read] as char, ',');
-
-        // Read the right hand side, then a ]
-        let (right, right_read) = SnailItem::read(&input[2 + left_read..]);
-        assert_eq!(input[2 + left_read + right_read] as char, ']');
-
-        let result = SnailNumber { left, right };
-        (result, 3 + left_read + right_read)
-    }
-
-    pub fn reduce(&mut self) {
-        if self.try_explode() {
-            return self.reduce();
-        }
-        if self.try_split() {
-            self.reduce();
-        }
-    }
-
-    pub fn try_explode(&mut self) -> bool {
-        !matches!(self.try_explode_rec(0, None), ExplodeStatus::No)
-    }
-
-    pub fn try_explode_rec(&mut self, depth: usize, prev: Option<&mut SnailItem>) -> ExplodeStatus {
-        match self.left.try_explode(depth, prev) {
-            ExplodeStatus::Exploding(right) => {
-                // Add the right side of the exploding pair to the number to the right
-                self.right.inc_left(right);
-
-                if depth == 3 {
-                    // Set the exploding pair to 0
-                    self.left = SnailItem::Number(0);
-                }
-
-                return ExplodeStatus::Done;
-            }
-            ExplodeStatus::Done => return ExplodeStatus::Done,
-            ExplodeStatus::No => {}
-        }
-
-        match self.right.try_explode(depth, Some(&mut self.left)) {
-            ExplodeStatus::Exploding(right) => {
-                if depth == 3 {
-                    // Set the exploding pair to 0
-                    self.right = SnailItem::Number(0);
-                }
-
-                // Add the right side of the exploding pair to the number to the right
-                return ExplodeStatus::Exploding(right);
-            }
-            ExplodeStatus::Done => return ExplodeStatus::Done,
-            ExplodeStatus::No => {}
-        }
-
-        ExplodeStatus::No
-    }
-
-    pub fn try_split(&mut self) -> bool {
-        !matches!(self.try_split_rec(), SplitStatus::No)
-    }
-
-    pub fn try_split_rec(&mut self) -> SplitStatus {
-        match self.left.try_split() {
-            SplitStatus::Splitting(number) => {
-                self.left = SnailItem::SnailNumber(number.into());
-                return SplitStatus::Done;
-            }
-            SplitStatus::Done => return SplitStatus::Done,
-            SplitStatus::No => {}
-        }
-
-        match self.right.try_split() {
-            SplitStatus::Splitting(number) => {
-                self.right = SnailItem::SnailNumber(number.into());
-                return SplitStatus::Done;
-            }
-            SplitStatus::Done => return SplitStatus::Done,
-            SplitStatus::No => {}
-        }
-
-        SplitStatus::No
+        let mut tree = Tree::new();
+        let (root, read) = Self::read_item(&mut tree, input);
+        (SnailNumber { tree, root }, read)
     }
 
-    pub fn mag(&self) -> usize {
-        let left_mag = self.left.mag();
-        let right_mag = self.right.mag();
-        left_mag * 3 + right_mag * 2
-    }
-}
-
-impl Add for SnailNumber {
-    type Output = SnailNumber;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        let mut result = SnailNumber {
-            left: SnailItem::SnailNumber(self.into()),
-            right: SnailItem::SnailNumber(rhs.into()),
-        };
-        result.reduce();
-        result
-    }
-}
+    fn read_item(tree: &mut Tree<SnailItem>, input: &[u8]) -> (NodeId<SnailItem>, usize) {
+        match input[0] as char {
+            '[' => {
+                let id = tree.add(SnailItem::Pair);
 
-impl fmt::Debug for SnailItem {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::Number(n) => write!(f, "{}", n),
-            Self::SnailNumber(n) => write!(f, "{}", n),
-        }
-    }
-}
+                // Read the left hand side, then a comma
+                let (left, left_read) = Self::read_item(tree, &input[1..]);
+                assert_eq!(input[1 + left_read] as char, ',');
 
-impl fmt::Display for SnailItem {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Self::Number(n) => write!(f, "{}", n)?,
-            Self::SnailNumber(n) => write!(f, "{}", n)?,
-        }
-        Ok(())
-    }
-}
+                // Read the right hand side, then a ]
+                let (right, right_read) = Self::read_item(tree, &input[2 + left_read..]);
+                assert_eq!(input[2 + left_read + right_read] as char, ']');
 
-impl SnailItem {
-    pub fn read(input: &[u8]) -> (SnailItem, usize) {
-        match input[0] as char {
-            '[' => {
-                // It's a nested snail number
-                let (snail_number, read) = SnailNumber::read(&input[0..]);
-                let item = SnailItem::SnailNumber(snail_number.into());
-                (item, read)
+                tree.append(id, left);
+                tree.append(id, right);
+                (id, 3 + left_read + right_read)
             }
             _ => {
                 // It's a number
@@ -204,90 +87,119 @@ impl SnailItem {
                 let index = input
                     .iter()
                     .position(|&c| (c as char) == ']' || (c as char) == ',')
-                    .unwrap();
+                    .unwrap_or(input.len());
 
                 // Find the substring before the ending character
                 let number_str = std::str::from_utf8(&input[0..index]).unwrap();
 
                 // Turn it into an integer
                 let number = number_str.parse::<usize>().unwrap();
-                let item = SnailItem::Number(number);
-                (item, index)
+                (tree.add(SnailItem::Number(number)), index)
             }
         }
     }
 
-    pub fn mag(&self) -> usize {
-        match self {
-            Self::Number(n) => *n,
-            Self::SnailNumber(n) => n.mag(),
+    fn fmt_item(&self, f: &mut fmt::Formatter, id: NodeId<SnailItem>) -> fmt::Result {
+        match self.tree[id] {
+            SnailItem::Number(n) => write!(f, "{}", n),
+            SnailItem::Pair => {
+                let children = self.tree.children(id);
+                write!(f, "[")?;
+                self.fmt_item(f, children[0])?;
+                write!(f, ",")?;
+                self.fmt_item(f, children[1])?;
+                write!(f, "]")
+            }
         }
     }
 
-    pub fn get_number(&self) -> usize {
-        match self {
-            SnailItem::Number(number) => *number,
-            SnailItem::SnailNumber(_) => unreachable!(),
-        }
+    pub fn reduce(&mut self) {
+        while self.try_explode() || self.try_split() {}
     }
 
-    pub fn try_explode(&mut self, depth: usize, prev: Option<&mut SnailItem>) -> ExplodeStatus {
-        match self {
-            SnailItem::Number(_) => ExplodeStatus::No,
-            SnailItem::SnailNumber(nested) => {
-                if depth == 3 {
-                    // The nested snail number will explode
-                    let left = nested.left.get_number();
-                    let right = nested.right.get_number();
-
-                    // Add the left side to the number immediately to the left
-                    match prev {
-                        None => {}
-                        Some(item) => {
-                            item.inc_right(left);
-                        }
-                    }
-
-                    // Return the right number, so it can be added by the parent of this
-                    ExplodeStatus::Exploding(right)
-                } else {
-                    nested.try_explode_rec(depth + 1, prev)
-                }
-            }
+    pub fn try_explode(&mut self) -> bool {
+        // Pre-order visits the regular numbers from left to right
+        let order = self.tree.pre_order(self.root).collect_vec();
+        let Some(&pair) = order
+            .iter()
+            .find(|&&id| self.tree[id] == SnailItem::Pair && self.tree.depth(id) == 4)
+        else {
+            return false;
+        };
+        let numbers = order
+            .into_iter()
+            .filter(|&id| self.tree.is_leaf(id))
+            .collect_vec();
+
+        // Add each side of the exploding pair to the number immediately beside it
+        let (left, right) = (self.tree.children(pair)[0], self.tree.children(pair)[1]);
+        let left_index = numbers.iter().position(|&id| id == left).unwrap();
+        if left_index > 0 {
+            let value = self.value(left);
+            self.add_to(numbers[left_index - 1], value);
         }
+        if let Some(&next) = numbers.get(left_index + 2) {
+            let value = self.value(right);
+            self.add_to(next, value);
+        }
+
+        // Set the exploding pair to 0
+        self.tree.clear_children(pair);
+        self.tree[pair] = SnailItem::Number(0);
+        true
+    }
+
+    pub fn try_split(&mut self) -> bool {
+        let Some(id) = self
+            .tree
+            .pre_order(self.root)
+            .find(|&id| matches!(self.tree[id], SnailItem::Number(n) if n > 9))
+        else {
+            return false;
+        };
+
+        let n = self.value(id);
+        self.tree[id] = SnailItem::Pair;
+        self.tree.add_child(id, SnailItem::Number(n / 2));
+        self.tree.add_child(id, SnailItem::Number(n - n / 2));
+        true
     }
 
-    pub fn inc_left(&mut self, num: usize) {
-        match self {
-            SnailItem::Number(n) => *n += num,
-            SnailItem::SnailNumber(nested) => nested.left.inc_left(num),
+    fn value(&self, id: NodeId<SnailItem>) -> usize {
+        match self.tree[id] {
+            SnailItem::Number(n) => n,
+            SnailItem::Pair => unreachable!(),
         }
     }
 
-    pub fn inc_right(&mut self, num: usize) {
-        match self {
-            SnailItem::Number(n) => *n += num,
-            SnailItem::SnailNumber(nested) => nested.right.inc_right(num),
+    fn add_to(&mut self, id: NodeId<SnailItem>, num: usize) {
+        if let SnailItem::Number(n) = &mut self.tree[id] {
+            *n += num;
         }
     }
 
-    pub fn try_split(&mut self) -> SplitStatus {
-        match self {
-            SnailItem::Number(n) => {
-                if *n > 9 {
-                    let new_left = *n / 2;
-                    let new_right = if *n % 2 == 0 { *n / 2 } else { *n / 2 + 1 };
-                    let new = SnailNumber {
-                        left: SnailItem::Number(new_left),
-                        right: SnailItem::Number(new_right),
-                    };
-                    SplitStatus::Splitting(new)
-                } else {
-                    SplitStatus::No
-                }
-            }
-            SnailItem::SnailNumber(n) => n.try_split_rec(),
+    pub fn mag(&self) -> usize {
+        self.tree.aggregate(self.root, |item, children| match item {
+            SnailItem::Number(n) => *n,
+            SnailItem::Pair => children[0] * 3 + children[1] * 2,
+        })[&self.root]
+    }
+}
+
+impl Add for SnailNumber {
+    type Output = SnailNumber;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        // Copying both sides into a fresh tree also leaves behind any nodes that were detached
+        let mut tree = Tree::new();
+        let root = tree.add(SnailItem::Pair);
+        for side in [self, rhs] {
+            let child = tree.graft(&side.tree, side.root);
+            tree.append(root, child);
         }
+        let mut result = SnailNumber { tree, root };
+        result.reduce();
+        result
     }
 }
 
@@ -316,12 +228,18 @@ fn part2(input: &Input) -> usize {
         .unwrap()
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
@@ -331,21 +249,18 @@ mod tests {
 
     #[test]
     pub fn parsing() {
-        let (single, read) = SnailItem::read("1,2]".as_bytes());
-        assert_eq!(single, SnailItem::Number(1));
+        let (single, read) = SnailNumber::read("1,2]".as_bytes());
+        assert_eq!(single.tree[single.root], SnailItem::Number(1));
         assert_eq!(read, 1);
 
-        let (nested, read2) = SnailItem::read("[1,2],3]".as_bytes());
-        let expected = SnailNumber {
-            left: SnailItem::Number(1),
-            right: SnailItem::Number(2),
-        };
-        assert_eq!(nested, SnailItem::SnailNumber(expected.into()));
+        let (nested, read2) = SnailNumber::read("[1,2],3]".as_bytes());
+        assert_eq!(nested.to_string(), "[1,2]");
+        assert_eq!(nested.tree.children(nested.root).len(), 2);
         assert_eq!(read2, 5);
     }
 
     fn assert_mag(input: &str, expected: usize) {
-        let (num, _) = SnailItem::read(input.as_bytes());
+        let (num, _) = SnailNumber::read(input.as_bytes());
         assert_eq!(num.mag(), expected);
     }
 
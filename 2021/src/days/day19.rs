@@ -1,10 +1,16 @@
 use std::collections::{HashMap, HashSet};
 
+use aoc_common::animate::Colour;
+use aoc_common::export::Options;
+use aoc_common::mesh::PointCloud;
+use aoc_common::parallel;
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
 use itertools::Itertools;
 
 const INPUT: &str = include_str!("../../inputs/day19.txt");
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Input<const D: usize> {
     pub scanners: Vec<Scanner<D>>,
 }
@@ -228,23 +234,33 @@ fn part1(input: Input<3>) -> (Vec<Scanner<3>>, usize) {
     starting.place([0; 3]);
     located_scanners.push(starting);
 
-    let mut located_already = vec![false; input.scanners.len()];
-    located_already[0] = true;
+    let mut unlocated: Vec<usize> = (1..input.scanners.len()).collect();
+    // Scanners that didn't match anything last time can only match ones located since
+    let mut newly_located = 0..1;
 
     // We can locate at least one scanner on each loop
-    while located_scanners.len() < input.scanners.len() {
-        for (i, scanner) in input.scanners.iter().enumerate() {
-            if located_already[i] {
-                continue;
-            }
-            for found_scanner in &located_scanners {
-                if let Some(final_scanner) = found_scanner.find_match_with(scanner) {
-                    located_scanners.push(final_scanner);
-                    located_already[i] = true;
-                    break;
-                }
+    while !unlocated.is_empty() {
+        // Every unlocated scanner can be checked on its own
+        let matches = parallel::map(&unlocated, |&i| {
+            located_scanners[newly_located.clone()]
+                .iter()
+                .find_map(|found_scanner| found_scanner.find_match_with(&input.scanners[i]))
+        });
+        assert!(
+            matches.iter().any(Option::is_some),
+            "Some scanners don't overlap any others"
+        );
+
+        let start = located_scanners.len();
+        let mut still_unlocated = Vec::new();
+        for (i, found) in unlocated.into_iter().zip(matches) {
+            match found {
+                Some(final_scanner) => located_scanners.push(final_scanner),
+                None => still_unlocated.push(i),
             }
         }
+        newly_located = start..located_scanners.len();
+        unlocated = still_unlocated;
     }
 
     let mut beacon_positions = HashSet::new();
@@ -271,12 +287,36 @@ fn part2(scanners: Vec<Scanner<3>>) -> isize {
         .unwrap()
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let (scanners, answer1) = part1(input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(scanners);
-    println!("Part 2: {}", answer2);
+// Every beacon, plus the scanners in a different colour
+fn point_cloud(scanners: &[Scanner<3>]) -> PointCloud {
+    let mut cloud = PointCloud::new();
+    let beacons: HashSet<_> = scanners.iter().flat_map(|s| s.points.iter()).collect();
+    for &beacon in beacons {
+        cloud.add(beacon, Colour::Yellow);
+    }
+    for scanner in scanners {
+        cloud.add(scanner.coords.unwrap(), Colour::Red);
+    }
+    cloud
+}
+
+pub fn export(options: &Options) {
+    let (scanners, _) = part1(Input::new(INPUT));
+    point_cloud(&scanners).save(options);
+}
+
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    let (scanners, _) = run.part_with(1, || part1(input), |(_, answer)| answer.to_string());
+    run.part(2, || part2(scanners));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(input.clone()).1)
+        .part(2, || part2(part1(input.clone()).0))
+        .run();
 }
 
 #[cfg(test)]
@@ -296,6 +336,7 @@ mod tests {
         assert_eq!(input.scanners.len(), 5);
         let (scanners, answer1) = part1(input);
         assert_eq!(answer1, 79);
+        assert_eq!(point_cloud(&scanners).len(), 79 + 5);
         assert_eq!(part2(scanners), 3621);
     }
 
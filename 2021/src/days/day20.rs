@@ -1,8 +1,22 @@
-use std::{collections::HashSet, fmt};
+use std::fmt;
+
+use aoc_common::animate::{colour_chars, play, Animation, Colour, Generations, Options};
+use aoc_common::automaton::{Automaton, CellRule, Synchronous};
+use aoc_common::export::{self, record, Palette};
+use aoc_common::grid::{Grid, Point};
+use aoc_common::parse::{grid, parse_all};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+use nom::{
+    character::complete::{anychar, line_ending},
+    combinator::map_opt,
+    multi::many1,
+    sequence::{pair, separated_pair},
+};
 
 const INPUT: &str = include_str!("../../inputs/day20.txt");
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Input {
     pub algorithm: Vec<bool>,
     pub image: Image,
@@ -10,143 +24,110 @@ struct Input {
 
 #[derive(Clone, Debug)]
 struct Image {
-    data: HashSet<(isize, isize)>,
-    min_r: isize,
-    min_c: isize,
-    max_r: isize,
-    max_c: isize,
-    background_black: bool,
+    pixels: Grid<bool>,
 }
 
 impl Image {
-    fn is_lit(&self, position: &(isize, isize)) -> usize {
-        if position.0 < self.min_r
-            || position.0 >= self.max_r
-            || position.1 < self.min_c
-            || position.1 >= self.max_c
-        {
-            // This cell is out of bounds
-            return usize::from(!self.background_black);
-        }
-        usize::from(self.data.contains(position))
-    }
-
-    fn total_capacity(&self) -> usize {
-        ((self.max_r - self.min_r) * (self.max_c - self.min_c)) as usize
-    }
-
-    fn next_capacity(&self) -> usize {
-        ((self.max_r - self.min_r + 2) * (self.max_c - self.min_c + 2)) as usize
-    }
-
     fn total_lit(&self) -> usize {
-        if self.background_black {
-            self.data.len()
-        } else {
-            // All pixels outside the range are lit
-            40000 - self.total_capacity() + self.data.len()
-        }
+        assert!(
+            !self.pixels.background(),
+            "An infinite number of pixels are lit"
+        );
+        self.pixels.count(|lit| *lit)
     }
 }
 
 impl fmt::Display for Image {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for r in self.min_r..self.max_r {
-            for c in self.min_c..self.max_c {
-                if self.data.contains(&(r, c)) {
-                    write!(f, "#")?;
-                } else {
-                    write!(f, ".")?;
-                }
-            }
-            writeln!(f)?;
-        }
-        Ok(())
+        let rendered = self.pixels.render(|lit| if *lit { '#' } else { '.' });
+        write!(f, "{}", rendered)
+    }
+}
+
+fn parse_pixel(c: char) -> Option<bool> {
+    match c {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None,
     }
 }
 
 impl Input {
     pub fn new(input: &str) -> Input {
-        let mut iter = input.lines();
-        let algorithm = iter
-            .next()
-            .unwrap()
-            .chars()
-            .map(|char| match char {
-                '#' => true,
-                '.' => false,
-                _ => unreachable!(),
-            })
-            .collect();
-
-        // Skip empty line
-        iter.next();
-
-        let mut data = HashSet::new();
-        let lines = iter.clone().count() as isize;
-        let mut cols: isize = 0;
-        for (r, line) in iter.enumerate() {
-            if cols == 0 {
-                cols = line.len() as isize;
-            }
-            for (c, char) in line.chars().enumerate() {
-                if char == '#' {
-                    data.insert((r as isize, c as isize));
-                }
-            }
+        let (algorithm, pixels) = parse_all(
+            input,
+            separated_pair(
+                many1(map_opt(anychar, parse_pixel)),
+                pair(line_ending, line_ending),
+                grid(false, parse_pixel),
+            ),
+        )
+        .unwrap();
+        Input {
+            algorithm,
+            image: Image { pixels },
         }
-        let image = Image {
-            data,
-            min_r: 0,
-            max_r: lines,
-            min_c: 0,
-            max_c: cols,
-            background_black: true,
-        };
-        Input { algorithm, image }
     }
 }
 
-fn run_algorithm(image: &Image, algorithm: &[bool]) -> Image {
-    let mut data = HashSet::with_capacity(image.next_capacity());
-    for r in (image.min_r - 1)..(image.max_r + 1) {
-        for c in (image.min_c - 1)..(image.max_c + 1) {
-            let index = image.is_lit(&(r - 1, c - 1)) << 8
-                | image.is_lit(&(r - 1, c)) << 7
-                | image.is_lit(&(r - 1, c + 1)) << 6
-                | image.is_lit(&(r, c - 1)) << 5
-                | image.is_lit(&(r, c)) << 4
-                | image.is_lit(&(r, c + 1)) << 3
-                | image.is_lit(&(r + 1, c - 1)) << 2
-                | image.is_lit(&(r + 1, c)) << 1
-                | image.is_lit(&(r + 1, c + 1));
-
-            if algorithm[index] {
-                data.insert((r, c));
-            }
-        }
+#[derive(Clone, Debug)]
+struct Enhance<'a> {
+    algorithm: &'a [bool],
+}
+
+impl CellRule<bool> for Enhance<'_> {
+    fn next(&self, image: &Grid<bool>, (c, r): Point) -> bool {
+        let index = (r - 1..=r + 1)
+            .flat_map(|r| (c - 1..=c + 1).map(move |c| (c, r)))
+            .fold(0, |index, p| index << 1 | usize::from(*image.get(p)));
+        self.algorithm[index]
     }
-    Image {
-        data,
-        min_r: image.min_r - 1,
-        max_r: image.max_r + 1,
-        min_c: image.min_c - 1,
-        max_c: image.max_c + 1,
-        background_black: if algorithm[0] {
-            // If the first bit of the algorithm is set, then the background flips back and forth each run
-            !image.background_black
+
+    // The image grows by one pixel in each direction every time it is enhanced
+    fn margin(&self) -> usize {
+        1
+    }
+
+    fn next_background(&self, background_lit: &bool) -> bool {
+        // If the first bit of the algorithm is set, then the background can flip back and forth each run
+        if *background_lit {
+            self.algorithm[511]
         } else {
-            image.background_black
-        },
+            self.algorithm[0]
+        }
     }
 }
 
 fn run_times(image: &Image, algorithm: &[bool], number: usize) -> Image {
-    let mut image = image.clone();
-    for _ in 0..number {
-        image = run_algorithm(&image, algorithm);
+    let mut automaton = Automaton::new(image.pixels.clone(), Synchronous(Enhance { algorithm }));
+    automaton.run(number);
+    Image {
+        pixels: automaton.into_world(),
     }
-    image
+}
+
+const PALETTE: &Palette = &[('#', Colour::White)];
+
+fn enhancing(input: &Input) -> impl Animation + '_ {
+    let rule = Synchronous(Enhance {
+        algorithm: &input.algorithm,
+    });
+    let automaton = Automaton::new(input.image.pixels.clone(), rule);
+    Generations::new(automaton, |pixels: &Grid<bool>| {
+        let image = pixels.render(|lit| if *lit { '#' } else { '.' });
+        colour_chars(&image, |c| (c == '#').then_some(Colour::White))
+    })
+    .with_limit(50)
+}
+
+pub fn animate(options: &Options) {
+    let input = Input::new(INPUT);
+    play(&mut enhancing(&input), options);
+}
+
+pub fn export(options: &export::Options) {
+    let input = Input::new(INPUT);
+    record(&mut enhancing(&input), Animation::frame, PALETTE, options);
 }
 
 fn part1(input: &Input) -> usize {
@@ -159,12 +140,18 @@ fn part2(input: &Input) -> usize {
     result.total_lit()
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
@@ -1,24 +1,31 @@
-use std::collections::HashMap;
-
-use serde_scan::scan;
-
-use crate::helpers::increment::Increment;
+use aoc_common::memo::Memo;
+use aoc_common::parse::{integer, parse_all};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+use nom::{
+    bytes::complete::tag,
+    character::complete::{char, line_ending},
+    sequence::{preceded, separated_pair, tuple},
+    IResult,
+};
 
 const INPUT: &str = include_str!("../../inputs/day21.txt");
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Input {
     pub p1: usize,
     pub p2: usize,
 }
 
+fn parse_player<'a>(player: char) -> impl FnMut(&'a str) -> IResult<&'a str, usize> {
+    let label = tuple((tag("Player "), char(player), tag(" starting position: ")));
+    preceded(label, integer)
+}
+
 impl Input {
     pub fn new(input: &str) -> Input {
-        let mut iter = input.lines();
-        let p1_str = iter.next().unwrap();
-        let p2_str = iter.next().unwrap();
-        let p1 = scan!("Player 1 starting position: {}" <- p1_str).unwrap();
-        let p2 = scan!("Player 2 starting position: {}" <- p2_str).unwrap();
+        let players = separated_pair(parse_player('1'), line_ending, parse_player('2'));
+        let (p1, p2) = parse_all(input, players).unwrap();
         Input { p1, p2 }
     }
 }
@@ -142,51 +149,51 @@ impl GameState {
     }
 }
 
+/// The number of universes in which each player wins, starting from this game state
+fn count_wins(
+    game_state: &GameState,
+    recurse: &mut dyn FnMut(&GameState) -> (usize, usize),
+) -> (usize, usize) {
+    // This game state results in 7 possible new game states, each with a count to reflect how likely it is
+    game_state
+        .progress()
+        .into_iter()
+        .map(|state| match state {
+            // If the game has ended, the player who just went has won
+            GameUpdate::Finished(count) if game_state.2 => (count, 0),
+            GameUpdate::Finished(count) => (0, count),
+            GameUpdate::Live(game, count) => {
+                let (w1, w2) = recurse(&game);
+                (w1 * count, w2 * count)
+            }
+        })
+        .fold((0, 0), |(w1, w2), (a, b)| (w1 + a, w2 + b))
+}
+
 fn part2(input: &Input) -> usize {
-    // Keep track of the number of ongoing games in each state
-    let mut live_games = HashMap::new();
     let p1 = Player::new(input.p1);
     let p2 = Player::new(input.p2);
-    live_games.increment(GameState(p1, p2, true), 1);
-
-    // And the number of wins for each player
-    let mut w1: usize = 0;
-    let mut w2: usize = 0;
-
-    while !live_games.is_empty() {
-        // For each ongoing game state, we need to progress it `count` times
-        for (game_state, count) in live_games.clone().iter() {
-            live_games.decrement_delete(game_state.clone(), *count);
-
-            // This game state results in 7 possible new game states, each with an additional count to reflect how likely it is
-            for state in game_state.progress() {
-                match state {
-                    GameUpdate::Finished(new_count) => {
-                        // If the game has ended, update the win counts
-                        if game_state.2 {
-                            w1 += count * new_count;
-                        } else {
-                            w2 += count * new_count;
-                        }
-                    }
-                    GameUpdate::Live(game, new_count) => {
-                        // Add this game back into the map of game states, with an increased count
-                        live_games.increment(game, count * new_count);
-                    }
-                }
-            }
-        }
-    }
+
+    // Many different sequences of rolls lead to the same game state, so we only count each once
+    let mut memo = Memo::new();
+    let (w1, w2) = memo.solve(&GameState(p1, p2, true), &count_wins);
+    memo.stats().trace();
 
     w1.max(w2)
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
@@ -1,12 +1,24 @@
 use std::{collections::HashMap, convert::Infallible, ops::Neg, str::FromStr};
 
-use serde_scan::scan;
+use aoc_common::export::Options;
+use aoc_common::mesh::Mesh;
+use aoc_common::parallel;
+use aoc_common::parse::{integer, lines, parse_all};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+use nom::{
+    bytes::complete::tag,
+    character::complete::{alpha1, char},
+    combinator::{map, map_res},
+    sequence::{pair, preceded, separated_pair, tuple},
+    IResult,
+};
 
 use crate::helpers::increment::Increment;
 
 const INPUT: &str = include_str!("../../inputs/day22.txt");
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Input {
     pub actions: Vec<Action>,
 }
@@ -38,10 +50,14 @@ impl Region {
     // This is the region created by overlapping this region with an action
     // If the region and action do not overlap, then no region is returned
     fn overlapping_region(&self, action: &Action) -> Option<Region> {
+        self.intersection(&Region::new(action))
+    }
+
+    fn intersection(&self, other: &Region) -> Option<Region> {
         let region = Self {
-            x: (self.x.0.max(action.x.0), self.x.1.min(action.x.1)),
-            y: (self.y.0.max(action.y.0), self.y.1.min(action.y.1)),
-            z: (self.z.0.max(action.z.0), self.z.1.min(action.z.1)),
+            x: (self.x.0.max(other.x.0), self.x.1.min(other.x.1)),
+            y: (self.y.0.max(other.y.0), self.y.1.min(other.y.1)),
+            z: (self.z.0.max(other.z.0), self.z.1.min(other.z.1)),
         };
 
         if region.is_valid() {
@@ -51,6 +67,45 @@ impl Region {
         }
     }
 
+    // The parts of this region outside the other one, as up to six smaller regions
+    fn subtract(&self, other: &Region) -> Vec<Region> {
+        let Some(overlap) = self.intersection(other) else {
+            return vec![self.clone()];
+        };
+        let overlap = overlap.bounds();
+        let mut rest = self.bounds();
+        let mut pieces = Vec::new();
+
+        // Slice off whatever lies either side of the overlap along each axis in turn
+        for axis in 0..3 {
+            if rest[axis].0 < overlap[axis].0 {
+                let mut piece = rest;
+                piece[axis].1 = overlap[axis].0 - 1;
+                pieces.push(Region::from_bounds(piece));
+                rest[axis].0 = overlap[axis].0;
+            }
+            if rest[axis].1 > overlap[axis].1 {
+                let mut piece = rest;
+                piece[axis].0 = overlap[axis].1 + 1;
+                pieces.push(Region::from_bounds(piece));
+                rest[axis].1 = overlap[axis].1;
+            }
+        }
+        pieces
+    }
+
+    fn bounds(&self) -> [(isize, isize); 3] {
+        [self.x, self.y, self.z]
+    }
+
+    fn from_bounds([x, y, z]: [(isize, isize); 3]) -> Self {
+        Self { x, y, z }
+    }
+
+    fn volume(&self) -> isize {
+        (self.x.1 - self.x.0 + 1) * (self.y.1 - self.y.0 + 1) * (self.z.1 - self.z.0 + 1)
+    }
+
     fn is_valid(&self) -> bool {
         self.x.0 <= self.x.1 && self.y.0 <= self.y.1 && self.z.0 <= self.z.1
     }
@@ -72,7 +127,7 @@ impl Cube {
     }
 
     pub fn apply(&mut self, action: &Action) {
-        if !self.is_within(action) {
+        if !action.is_within(self.max) {
             return;
         }
 
@@ -84,12 +139,15 @@ impl Cube {
         }
 
         // Check all current regions
-        for (region, &sign) in &self.regions {
-            // If they overlap with this action, then they are being toggled on or off
-            if let Some(sub_region) = region.overlapping_region(action) {
-                // This toggles the region, by offsetting it by the current sign
-                region_changes.increment(sub_region, sign.neg())
-            }
+        // If they overlap with this action, then they are being toggled on or off
+        let overlaps = parallel::filter_map(&self.regions, |(region, &sign)| {
+            region
+                .overlapping_region(action)
+                .map(|sub_region| (sub_region, sign))
+        });
+        for (sub_region, sign) in overlaps {
+            // This toggles the region, by offsetting it by the current sign
+            region_changes.increment(sub_region, sign.neg())
         }
 
         // Update the main state with these changes
@@ -98,32 +156,43 @@ impl Cube {
         }
     }
 
-    fn is_within(&self, action: &Action) -> bool {
-        match self.max {
+    pub fn len(self) -> isize {
+        self.regions
+            .into_iter()
+            // For every region, count the number of cells and then multiply by the sign (which is +/- 1)
+            .map(|(region, sign)| region.volume() * sign)
+            .sum()
+    }
+}
+
+impl Action {
+    fn is_within(&self, max: Option<isize>) -> bool {
+        match max {
             None => true,
             Some(max) => {
-                action.x.0.abs() <= max
-                    && action.x.1.abs() <= max
-                    && action.y.0.abs() <= max
-                    && action.y.1.abs() <= max
-                    && action.z.0.abs() <= max
-                    && action.z.1.abs() <= max
+                self.x.0.abs() <= max
+                    && self.x.1.abs() <= max
+                    && self.y.0.abs() <= max
+                    && self.y.1.abs() <= max
+                    && self.z.0.abs() <= max
+                    && self.z.1.abs() <= max
             }
         }
     }
+}
 
-    pub fn len(self) -> isize {
-        self.regions
-            .into_iter()
-            .map(|(region, sign)| {
-                // For every region, count the number of cells and then multiply by the sign (which is +/- 1)
-                (region.x.1 - region.x.0 + 1)
-                    * (region.y.1 - region.y.0 + 1)
-                    * (region.z.1 - region.z.0 + 1)
-                    * sign
-            })
-            .sum()
+// The cubes that are on, as regions which don't overlap
+// Unlike the signed regions of a `Cube`, these can be drawn
+fn lit_regions(actions: &[Action], max: Option<isize>) -> Vec<Region> {
+    let mut lit: Vec<Region> = Vec::new();
+    for action in actions.iter().filter(|action| action.is_within(max)) {
+        let region = Region::new(action);
+        lit = lit.iter().flat_map(|r| r.subtract(&region)).collect();
+        if action.on_off == OnOff::On {
+            lit.push(region);
+        }
     }
+    lit
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -144,22 +213,32 @@ impl FromStr for OnOff {
     }
 }
 
+fn parse_range<'a>(axis: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, (isize, isize)> {
+    preceded(
+        pair(tag(axis), char('=')),
+        separated_pair(integer, tag(" to "), integer),
+    )
+}
+
+fn parse_action(i: &str) -> IResult<&str, Action> {
+    let cuboid = tuple((
+        parse_range("x"),
+        tag(" , "),
+        parse_range("y"),
+        tag(" , "),
+        parse_range("z"),
+    ));
+    map(
+        separated_pair(map_res(alpha1, OnOff::from_str), tag(": "), cuboid),
+        |(on_off, (x, _, y, _, z))| Action { on_off, x, y, z },
+    )(i)
+}
+
 impl Input {
     pub fn new(input: &str) -> Input {
-        let actions = input
-            .lines()
-            .map(|line| {
-                let (on_off_str, x1, x2, y1, y2, z1, z2) =
-                    scan!("{}: x={} to {} , y={} to {} , z={} to {}" <- line).unwrap();
-
-                let on_off = OnOff::from_str(on_off_str).unwrap();
-                let x = (x1, x2);
-                let y = (y1, y2);
-                let z = (z1, z2);
-                Action { on_off, x, y, z }
-            })
-            .collect();
-        Input { actions }
+        Input {
+            actions: parse_all(input, lines(parse_action)).unwrap(),
+        }
     }
 }
 
@@ -179,12 +258,28 @@ fn part2(input: &Input) -> isize {
     cube.len()
 }
 
-pub fn main() {
+pub fn export(options: &Options) {
     let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+    let mut mesh = Mesh::new();
+    for region in lit_regions(&input.actions, None) {
+        let [x, y, z] = region.bounds();
+        mesh.add_cuboid([x.0, y.0, z.0], [x.1, y.1, z.1]);
+    }
+    mesh.save(options);
+}
+
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
@@ -202,6 +297,17 @@ mod tests {
         assert_eq!(part2(&input2), 2758514936282235);
     }
 
+    #[test]
+    pub fn disjoint_regions() {
+        let input = Input::new(TEST_INPUT);
+        let regions = lit_regions(&input.actions, Some(50));
+        assert_eq!(regions.iter().map(Region::volume).sum::<isize>(), 590784);
+        assert!(regions
+            .iter()
+            .enumerate()
+            .all(|(i, a)| regions[i + 1..].iter().all(|b| a.intersection(b).is_none())));
+    }
+
     #[test]
     pub fn answers() {
         let input = Input::new(INPUT);
@@ -2,8 +2,15 @@ use std::{
     cmp::Ordering,
     collections::{BinaryHeap, HashMap},
     fmt,
+    hash::{Hash, Hasher},
 };
 
+use aoc_common::animate::{colour_chars, play, Animation, Colour, Options};
+use aoc_common::export::{self, record, Palette};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+use aoc_common::trace;
+
 const INPUT: &str = include_str!("../../inputs/day23.txt");
 const INPUT2: &str = include_str!("../../inputs/day23_2.txt");
 
@@ -52,10 +59,23 @@ impl fmt::Display for Amphipod {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
-struct Positions<const N: usize> {
-    // 11 cells of hallway, followed by the 8 cells of the hallways, ordered ABCDABCD in the target
-    cells: [Option<Amphipod>; N],
+// Positions are copied and hashed a lot, so the cells are kept inline rather than in a Vec.
+// This allows rooms up to 13 deep, which is far more than any input needs.
+const MAX_CELLS: usize = 64;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Positions {
+    // The number of spaces in each room, which is taken from the input
+    depth: usize,
+    // 11 cells of hallway, followed by the rooms one row at a time, ordered ABCD in the target
+    // Any cells past the rooms are always empty
+    cells: [Option<Amphipod>; MAX_CELLS],
+}
+
+impl Hash for Positions {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.cells[..11 + 4 * self.depth].hash(state);
+    }
 }
 
 const HALLWAY_INDICES: [usize; 7] = [0, 1, 3, 5, 7, 9, 10];
@@ -65,39 +85,18 @@ fn exit_point(room_index: usize) -> usize {
     2 + 2 * room_index
 }
 
-fn indices_for_room<const N: usize>(room_index: usize) -> Vec<usize> {
-    if N == 19 {
-        [11 + room_index, 15 + room_index].into()
-    } else if N == 27 {
-        [
-            11 + room_index,
-            15 + room_index,
-            19 + room_index,
-            23 + room_index,
-        ]
-        .into()
-    } else {
-        unreachable!()
-    }
+fn index_to_room(index: usize) -> usize {
+    (index - 11) % 4
 }
 
-fn index_to_room(index: usize) -> usize {
-    if index < 15 {
-        index - 11
-    } else if index < 19 {
-        index - 15
-    } else if index < 23 {
-        index - 19
-    } else if index < 27 {
-        index - 23
-    } else {
-        panic!("Impossible index {}", index);
-    }
+// How far into its room a cell is, counting from 0 at the top
+fn index_to_row(index: usize) -> usize {
+    (index - 11) / 4
 }
 
-fn distance<const N: usize>(x: usize, y: usize) -> usize {
+fn distance(x: usize, y: usize) -> usize {
     if x > y {
-        distance::<N>(y, x)
+        distance(y, x)
     } else if y < 11 {
         // Both are in the hallway
         y - x
@@ -105,19 +104,9 @@ fn distance<const N: usize>(x: usize, y: usize) -> usize {
         // Movement between hallway and room
         // x is hallway, y is room
         if x < 11 {
-            let room_index = index_to_room(y);
-            let exit = exit_point(room_index);
-            let distance_to_exit = if y >= 23 {
-                // y is at the end of a room
-                4
-            } else if y >= 19 {
-                3
-            } else if y >= 15 {
-                2
-            } else {
-                1
-            };
-            distance_to_exit + distance::<N>(x, exit)
+            let exit = exit_point(index_to_room(y));
+            let distance_to_exit = index_to_row(y) + 1;
+            distance_to_exit + distance(x, exit)
         } else {
             // Both in the rooms?
             todo!()
@@ -125,13 +114,24 @@ fn distance<const N: usize>(x: usize, y: usize) -> usize {
     }
 }
 
-impl<const N: usize> Positions<N> {
+impl Positions {
     fn new(amphipods: &[Amphipod]) -> Self {
-        let mut cells = [None; N];
+        assert!(
+            amphipods.len().is_multiple_of(ROOMS.len()),
+            "Every room should start full"
+        );
+        assert!(11 + amphipods.len() <= MAX_CELLS, "The rooms are too deep");
+        let depth = amphipods.len() / ROOMS.len();
+        let mut cells = [None; MAX_CELLS];
         for (i, &amphipod) in amphipods.iter().enumerate() {
             cells[11 + i] = Some(amphipod);
         }
-        Self { cells }
+        Self { depth, cells }
+    }
+
+    // From the top of the room to the bottom
+    fn indices_for_room(&self, room_index: usize) -> impl Iterator<Item = usize> {
+        (0..self.depth).map(move |row| 11 + 4 * row + room_index)
     }
 
     fn possible_moves(&self) -> Vec<(usize, usize, usize)> {
@@ -142,19 +142,16 @@ impl<const N: usize> Positions<N> {
             if let Some(amphipod) = self.at_index(from) {
                 if let Some(to) = self.can_enter_room_from(from, amphipod) {
                     // A room can be entered from here
-                    moves.push((from, to, distance::<N>(from, to)));
+                    moves.push((from, to, distance(from, to)));
                 }
             }
         }
 
         for room_index in ROOMS {
             if let Some(from) = self.can_leave_room(room_index) {
-                // println!("Can leave room {} and move from {}", room_index, from);
                 // Something can leave this room
-
                 for to in self.get_possible_hallway_positions_from(room_index) {
-                    // println!(" Can move to {}", to);
-                    moves.push((from, to, distance::<N>(from, to)))
+                    moves.push((from, to, distance(from, to)))
                 }
             }
         }
@@ -179,74 +176,21 @@ impl<const N: usize> Positions<N> {
             return None;
         }
 
-        // Check what is in the room right now
-        let room_cells = indices_for_room::<N>(target_room);
-        let room_occupants: Vec<Option<Amphipod>> = room_cells
-            .iter()
-            .map(|&index| self.at_index(index))
-            .collect();
-        if N == 19 {
-            if room_occupants[0].is_none() && room_occupants[1].is_none() {
-                // Both are empty, we can move to the end
-                return Some(room_cells[1]);
-            }
-            if room_occupants[0].is_none() && room_occupants[1].unwrap().room_index() == target_room
-            {
-                // Furthest spot is filled, we can move to the near one and complete this room
-                return Some(room_cells[0]);
-            }
-        } else if N == 27 {
-            if room_occupants[0].is_none()
-                && room_occupants[1].is_none()
-                && room_occupants[2].is_none()
-                && room_occupants[3].is_none()
-            {
-                // All are empty, we can move to the end
-                return Some(room_cells[3]);
-            }
-
-            if room_occupants[0].is_none()
-                && room_occupants[1].is_none()
-                && room_occupants[2].is_none()
-            {
-                // First three are empty
-                if room_occupants[3].unwrap().room_index() == target_room {
-                    // Three are empty and final is complete, we can move to the third
-                    return Some(room_cells[2]);
-                } else {
-                    return None;
-                }
-            }
-
-            if room_occupants[0].is_none() && room_occupants[1].is_none() {
-                if room_occupants[2].unwrap().room_index() == target_room
-                    && room_occupants[3].unwrap().room_index() == target_room
-                {
-                    // Two are empty and next two are complete, we can move to the third
-                    return Some(room_cells[1]);
-                } else {
-                    return None;
-                }
-            }
-
-            if room_occupants[0].is_none()
-                && room_occupants[1].unwrap().room_index() == target_room
-                && room_occupants[2].unwrap().room_index() == target_room
-                && room_occupants[3].unwrap().room_index() == target_room
-            {
-                // Furthest spot is filled, we can move to the near one and complete this room
-                return Some(room_cells[0]);
+        // The room can only be entered once everything left in it belongs there,
+        // and then we move as far in as possible
+        let mut deepest_empty = None;
+        for index in self.indices_for_room(target_room) {
+            match self.at_index(index) {
+                None => deepest_empty = Some(index),
+                Some(occupant) if occupant.room_index() == target_room => {}
+                Some(_) => return None,
             }
-        } else {
-            unreachable!();
         }
-
-        None
+        deepest_empty
     }
 
     fn can_leave_room(&self, room_index: usize) -> Option<usize> {
-        indices_for_room::<N>(room_index)
-            .into_iter()
+        self.indices_for_room(room_index)
             .find(|&i| self.at_index(i).is_some())
     }
 
@@ -286,7 +230,7 @@ impl<const N: usize> Positions<N> {
     }
 }
 
-impl<const N: usize> fmt::Display for Positions<N> {
+impl fmt::Display for Positions {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "#############")?;
         write!(f, "#")?;
@@ -298,40 +242,10 @@ impl<const N: usize> fmt::Display for Positions<N> {
             }
         }
         writeln!(f, "#")?;
-        write!(f, "###")?;
-        for index in 11..15 {
-            if let Some(amphipod) = self.at_index(index) {
-                write!(f, "{}", amphipod)?;
-            } else {
-                write!(f, " ")?;
-            }
-            write!(f, "#")?;
-        }
-        writeln!(f, "##")?;
-        write!(f, "###")?;
-        for index in 15..19 {
-            if let Some(amphipod) = self.at_index(index) {
-                write!(f, "{}", amphipod)?;
-            } else {
-                write!(f, " ")?;
-            }
-            write!(f, "#")?;
-        }
-        writeln!(f, "##")?;
-        if N == 27 {
+        for row in 0..self.depth {
             write!(f, "###")?;
-            for index in 19..23 {
-                if let Some(amphipod) = self.at_index(index) {
-                    write!(f, "{}", amphipod)?;
-                } else {
-                    write!(f, " ")?;
-                }
-                write!(f, "#")?;
-            }
-            writeln!(f, "##")?;
-            write!(f, "###")?;
-            for index in 23..27 {
-                if let Some(amphipod) = self.at_index(index) {
+            for room_index in ROOMS {
+                if let Some(amphipod) = self.at_index(11 + 4 * row + room_index) {
                     write!(f, "{}", amphipod)?;
                 } else {
                     write!(f, " ")?;
@@ -346,30 +260,30 @@ impl<const N: usize> fmt::Display for Positions<N> {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
-struct State<const N: usize> {
-    positions: Positions<N>,
+struct State {
+    positions: Positions,
     energy: usize,
 }
 
-impl<const N: usize> State<N> {
-    pub fn new(positions: Positions<N>, energy: usize) -> State<N> {
+impl State {
+    pub fn new(positions: Positions, energy: usize) -> State {
         State { positions, energy }
     }
 }
 
-impl<const N: usize> Ord for State<N> {
+impl Ord for State {
     fn cmp(&self, other: &Self) -> Ordering {
         other.energy.cmp(&self.energy)
     }
 }
 
-impl<const N: usize> PartialOrd for State<N> {
+impl PartialOrd for State {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
-impl<const N: usize> fmt::Display for State<N> {
+impl fmt::Display for State {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.positions)?;
         writeln!(f, "Energy {}", self.energy)?;
@@ -378,13 +292,13 @@ impl<const N: usize> fmt::Display for State<N> {
     }
 }
 
-#[derive(Clone)]
-struct Input<const N: usize> {
-    pub positions: Positions<N>,
+#[derive(Clone, Debug)]
+struct Input {
+    pub positions: Positions,
 }
 
-impl<const N: usize> Input<N> {
-    pub fn new(input: &str) -> Input<N> {
+impl Input {
+    pub fn new(input: &str) -> Input {
         let amphipods = {
             let mut iter = input.lines();
             // Skip the first two rows of input
@@ -419,16 +333,26 @@ fn get_amphipods(row: &str) -> Vec<Amphipod> {
         .collect()
 }
 
-fn dijkstra<const N: usize>(start: Positions<N>, target: Positions<N>) -> usize {
-    let mut best_distance_to: HashMap<Positions<N>, usize> = HashMap::new();
-    let mut queue: BinaryHeap<State<N>> = BinaryHeap::new();
+/// Finds the cheapest way to organise the amphipods, returning every state along the way
+fn dijkstra(start: Positions, target: Positions) -> Vec<State> {
+    let mut best_distance_to: HashMap<Positions, usize> = HashMap::new();
+    let mut previous: HashMap<Positions, Positions> = HashMap::new();
+    let mut queue: BinaryHeap<State> = BinaryHeap::new();
 
-    best_distance_to.insert(start.clone(), 0);
+    best_distance_to.insert(start, 0);
     queue.push(State::new(start, 0));
 
     while let Some(state) = queue.pop() {
+        trace::count("dijkstra pops");
         if state.positions == target {
-            return state.energy;
+            // Follow the moves back to the start
+            let mut path = vec![state];
+            while let Some(positions) = previous.get(&path.last().unwrap().positions) {
+                let energy = best_distance_to[positions];
+                path.push(State::new(*positions, energy));
+            }
+            path.reverse();
+            return path;
         }
 
         for (from, to, distance) in state.positions.possible_moves() {
@@ -436,17 +360,19 @@ fn dijkstra<const N: usize>(start: Positions<N>, target: Positions<N>) -> usize
             let new_cost = state.energy + extra_cost;
 
             // Work out the new position and
-            let mut new_positions = state.positions.clone();
+            let mut new_positions = state.positions;
             new_positions.update_move(from, to);
 
             // Previous best cost to get to this position
-            let best_cost_so_far = *best_distance_to
-                .entry(new_positions.clone())
-                .or_insert(usize::MAX);
+            let best_cost_so_far = best_distance_to
+                .get(&new_positions)
+                .copied()
+                .unwrap_or(usize::MAX);
 
             if new_cost < best_cost_so_far {
                 // This is better than we'd done so far - update the record, and add this to the queue
-                best_distance_to.insert(new_positions.clone(), new_cost);
+                best_distance_to.insert(new_positions, new_cost);
+                previous.insert(new_positions, state.positions);
                 queue.push(State::new(new_positions, new_cost));
             }
         }
@@ -455,51 +381,100 @@ fn dijkstra<const N: usize>(start: Positions<N>, target: Positions<N>) -> usize
     panic!("Failed to find a solution")
 }
 
-fn part1(input: &Input<19>) -> usize {
-    let target = Positions::new(&[
-        Amphipod::A,
-        Amphipod::B,
-        Amphipod::C,
-        Amphipod::D,
-        Amphipod::A,
-        Amphipod::B,
-        Amphipod::C,
-        Amphipod::D,
-    ]);
-
-    dijkstra(input.positions.clone(), target)
-}
-
-fn part2(input: &Input<27>) -> usize {
-    let target = Positions::new(&[
-        Amphipod::A,
-        Amphipod::B,
-        Amphipod::C,
-        Amphipod::D,
-        Amphipod::A,
-        Amphipod::B,
-        Amphipod::C,
-        Amphipod::D,
-        Amphipod::A,
-        Amphipod::B,
-        Amphipod::C,
-        Amphipod::D,
-        Amphipod::A,
-        Amphipod::B,
-        Amphipod::C,
-        Amphipod::D,
-    ]);
-
-    dijkstra(input.positions.clone(), target)
-}
-
-pub fn main() {
-    let input = Input::new(INPUT);
-    let input2 = Input::new(INPUT2);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input2);
-    println!("Part 2: {}", answer2);
+fn min_energy(start: Positions, target: Positions) -> usize {
+    dijkstra(start, target).last().unwrap().energy
+}
+
+/// Every amphipod in its own room
+fn organised(depth: usize) -> Positions {
+    let amphipods = [Amphipod::A, Amphipod::B, Amphipod::C, Amphipod::D].repeat(depth);
+    Positions::new(&amphipods)
+}
+
+fn solve(input: &Input) -> usize {
+    let positions = &input.positions;
+    min_energy(*positions, organised(positions.depth))
+}
+
+fn part1(input: &Input) -> usize {
+    solve(input)
+}
+
+// The second input has the extra rows unfolded
+fn part2(input: &Input) -> usize {
+    solve(input)
+}
+
+// Replays the cheapest solution one move at a time
+struct Solution {
+    path: Vec<State>,
+    index: usize,
+}
+
+impl Solution {
+    fn new(input: Input) -> Self {
+        let depth = input.positions.depth;
+        Self {
+            path: dijkstra(input.positions, organised(depth)),
+            index: 0,
+        }
+    }
+}
+
+impl Animation for Solution {
+    fn step(&mut self) -> bool {
+        if self.index + 1 == self.path.len() {
+            return false;
+        }
+        self.index += 1;
+        true
+    }
+
+    fn frame(&self) -> String {
+        colour_chars(&self.path[self.index].to_string(), |c| match c {
+            'A' => Some(Colour::Red),
+            'B' => Some(Colour::Green),
+            'C' => Some(Colour::Yellow),
+            'D' => Some(Colour::Blue),
+            '#' => Some(Colour::Grey),
+            _ => None,
+        })
+    }
+}
+
+const PALETTE: &Palette = &[
+    ('A', Colour::Red),
+    ('B', Colour::Green),
+    ('C', Colour::Yellow),
+    ('D', Colour::Blue),
+    ('#', Colour::Grey),
+];
+
+pub fn animate(options: &Options) {
+    let mut solution = Solution::new(Input::new(INPUT2));
+    play(&mut solution, options);
+}
+
+pub fn export(options: &export::Options) {
+    let mut solution = Solution::new(Input::new(INPUT2));
+    let picture = |solution: &Solution| solution.path[solution.index].to_string();
+    record(&mut solution, picture, PALETTE, options);
+}
+
+pub fn main(run: &mut Run) {
+    let (input, input2) = run.parse(|| (Input::new(INPUT), Input::new(INPUT2)));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input2));
+}
+
+// Part 2 unfolds the burrow, which the puzzle gives as an input of its own
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    let input2 = Input::new(options.input(INPUT2));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input2))
+        .run();
 }
 
 #[cfg(test)]
@@ -1,5 +1,15 @@
+use aoc_common::parse::{integer, lines, parse_all};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
 use itertools::Itertools;
-use serde_scan::scan;
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{one_of, space1},
+    combinator::map,
+    sequence::{preceded, separated_pair, terminated},
+    IResult,
+};
 
 use crate::helpers::digits::from_digits;
 
@@ -36,18 +46,6 @@ enum Entry {
     Number(isize),
 }
 
-impl Entry {
-    fn new(s: &str) -> Self {
-        let c = s.chars().next().unwrap();
-        if let Some(register) = Register::maybe(c) {
-            Entry::Register(register)
-        } else {
-            let n = s.parse::<isize>().unwrap();
-            Entry::Number(n)
-        }
-    }
-}
-
 #[derive(Debug, Clone, Copy)]
 enum Instruction {
     Inp(Register),
@@ -58,28 +56,36 @@ enum Instruction {
     Eql(Register, Entry),
 }
 
-impl From<&str> for Instruction {
-    fn from(line: &str) -> Self {
-        if line.starts_with("inp") {
-            let reg = scan!("inp {}" <- line).unwrap();
-            Instruction::Inp(Register::new(reg))
-        } else if let Ok((a, b)) = scan!("add {} {}" <- line) {
-            Instruction::Add(Register::new(a), Entry::new(b))
-        } else if let Ok((a, b)) = scan!("mul {} {}" <- line) {
-            Instruction::Mul(Register::new(a), Entry::new(b))
-        } else if let Ok((a, b)) = scan!("div {} {}" <- line) {
-            Instruction::Div(Register::new(a), Entry::new(b))
-        } else if let Ok((a, b)) = scan!("mod {} {}" <- line) {
-            Instruction::Mod(Register::new(a), Entry::new(b))
-        } else if let Ok((a, b)) = scan!("eql {} {}" <- line) {
-            Instruction::Eql(Register::new(a), Entry::new(b))
-        } else {
-            unreachable!()
-        }
-    }
+fn parse_register(i: &str) -> IResult<&str, Register> {
+    map(one_of("wxyz"), Register::new)(i)
 }
 
-#[derive(Clone)]
+fn parse_entry(i: &str) -> IResult<&str, Entry> {
+    alt((
+        map(parse_register, Entry::Register),
+        map(integer, Entry::Number),
+    ))(i)
+}
+
+fn parse_instruction(i: &str) -> IResult<&str, Instruction> {
+    let binary = |name, instruction: fn(Register, Entry) -> Instruction| {
+        let operands = separated_pair(parse_register, space1, parse_entry);
+        map(
+            preceded(terminated(tag(name), space1), operands),
+            move |(a, b)| instruction(a, b),
+        )
+    };
+    alt((
+        map(preceded(tag("inp "), parse_register), Instruction::Inp),
+        binary("add", Instruction::Add),
+        binary("mul", Instruction::Mul),
+        binary("div", Instruction::Div),
+        binary("mod", Instruction::Mod),
+        binary("eql", Instruction::Eql),
+    ))(i)
+}
+
+#[derive(Clone, Debug)]
 struct Input {
     pub instructions: Vec<Instruction>,
 }
@@ -87,7 +93,7 @@ struct Input {
 impl Input {
     pub fn new(input: &str) -> Input {
         Input {
-            instructions: input.lines().map(|line| line.into()).collect(),
+            instructions: parse_all(input, lines(parse_instruction)).unwrap(),
         }
     }
 }
@@ -179,12 +185,18 @@ fn part2(input: &Input) -> i64 {
     from_digits(digits)
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
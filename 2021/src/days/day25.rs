@@ -1,116 +1,115 @@
+use aoc_common::animate::{colour_chars, play, Animation, Colour, Generations, Options};
+use aoc_common::automaton::{Automaton, CellRule, Synchronous};
+use aoc_common::direction::Direction;
+use aoc_common::export::{self, record, Palette};
+use aoc_common::grid::{offset, Grid, Point};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+
 const INPUT: &str = include_str!("../../inputs/day25.txt");
 
-type Grid = Vec<Vec<Option<Cucumber>>>;
+// Each cell holds the direction of the sea cucumber in it, if there is one
+type SeaFloor = Grid<Option<Direction>>;
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Input {
-    pub grid: Grid,
-}
-
-#[derive(Clone, PartialEq, Eq)]
-enum Cucumber {
-    Right,
-    Down,
+    pub grid: SeaFloor,
 }
 
 impl Input {
     pub fn new(input: &str) -> Input {
         Input {
-            grid: input
-                .lines()
-                .map(|line| {
-                    line.chars()
-                        .map(|c| match c {
-                            '.' => None,
-                            '>' => Some(Cucumber::Right),
-                            'v' => Some(Cucumber::Down),
-                            _ => {
-                                println!("{}", c);
-                                unreachable!();
-                            }
-                        })
-                        .collect()
-                })
-                .collect(),
+            grid: Grid::parse(input.trim(), None, |c| match c {
+                '.' => None,
+                '>' | 'v' => Some(Direction::try_from(c).unwrap()),
+                _ => {
+                    println!("{}", c);
+                    unreachable!();
+                }
+            }),
         }
     }
 }
 
-fn change_positions(grid: &mut Grid, width: usize, height: usize) -> bool {
-    let mut something_moved = false;
-
-    // Move right
-    for row in grid.iter_mut() {
-        // Check the right hand column first
-        let crossed_boundary = if row[width - 1] == Some(Cucumber::Right) && row[0].is_none() {
-            row[width - 1] = None;
-            row[0] = Some(Cucumber::Right);
-            something_moved = true;
-            1
+/// Moves every sea cucumber in one herd forward, if the space in front of it is empty.
+/// The sea floor wraps around at the edges.
+#[derive(Clone, Debug)]
+struct MoveHerd(Direction);
+
+impl CellRule<Option<Direction>> for MoveHerd {
+    fn next(&self, grid: &SeaFloor, p: Point) -> Option<Direction> {
+        let herd = Some(self.0);
+        let cucumber = *grid.get(p);
+        let (dx, dy) = self.0.delta();
+        if cucumber == herd && grid.get_wrapped(offset(p, (dx, dy))).is_none() {
+            // This one moves out
+            None
+        } else if cucumber.is_none() && *grid.get_wrapped(offset(p, (-dx, -dy))) == herd {
+            // The one behind moves in
+            herd
         } else {
-            0
-        };
-
-        // If anything crossed over to the LHS, we do not need to check the first column
-        let mut col = crossed_boundary;
-        while col < width - crossed_boundary - 1usize {
-            if row[col] == Some(Cucumber::Right) && row[col + 1].is_none() {
-                row[col + 1] = Some(Cucumber::Right);
-                row[col] = None;
-                col += 1;
-                something_moved = true;
-            }
-            col += 1;
+            cucumber
         }
     }
 
-    // Move south.
-    for col in 0..width {
-        // Check the bottom row first
-        let crossed_boundary =
-            if grid[height - 1][col] == Some(Cucumber::Down) && grid[0][col].is_none() {
-                grid[height - 1][col] = None;
-                grid[0][col] = Some(Cucumber::Down);
-                something_moved = true;
-                1
-            } else {
-                0
-            };
-
-        // If anything crossed over to the top row, we do not need to check the first row
-        let mut row = crossed_boundary;
-        while row < height - crossed_boundary - 1 {
-            if grid[row][col] == Some(Cucumber::Down) && grid[row + 1][col].is_none() {
-                grid[row + 1][col] = Some(Cucumber::Down);
-                grid[row][col] = None;
-                row += 1;
-                something_moved = true;
-            }
-            row += 1;
-        }
+    fn next_background(&self, background: &Option<Direction>) -> Option<Direction> {
+        *background
     }
+}
+
+type Herds = (Synchronous<MoveHerd>, Synchronous<MoveHerd>);
 
-    something_moved
+fn sea_floor(grid: SeaFloor) -> Automaton<SeaFloor, Herds> {
+    // The east-facing herd moves first, then the south-facing herd
+    let herds = (
+        Synchronous(MoveHerd(Direction::Right)),
+        Synchronous(MoveHerd(Direction::Down)),
+    );
+    Automaton::new(grid, herds)
 }
 
-fn run_until_fixed(mut grid: Grid) -> usize {
-    let mut tick = 1;
-    let height = grid.len();
-    let width = grid[0].len();
-    while change_positions(&mut grid, width, height) {
-        tick += 1;
-    }
-    tick
+fn run_until_fixed(grid: SeaFloor) -> usize {
+    sea_floor(grid).run_until_stable()
+}
+
+const PALETTE: &Palette = &[('>', Colour::Green), ('v', Colour::Cyan)];
+
+fn migrating(input: Input) -> impl Animation {
+    Generations::new(sea_floor(input.grid), |grid: &SeaFloor| {
+        let cucumbers = grid.render(|cell| match cell {
+            Some(Direction::Right) => '>',
+            Some(_) => 'v',
+            None => '.',
+        });
+        colour_chars(&cucumbers, |c| match c {
+            '>' => Some(Colour::Green),
+            'v' => Some(Colour::Cyan),
+            _ => None,
+        })
+    })
+}
+
+pub fn animate(options: &Options) {
+    play(&mut migrating(Input::new(INPUT)), options);
+}
+
+pub fn export(options: &export::Options) {
+    let mut herds = migrating(Input::new(INPUT));
+    record(&mut herds, Animation::frame, PALETTE, options);
 }
 
 fn part1(input: Input) -> usize {
     run_until_fixed(input.grid)
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(input);
-    println!("Part 1: {}", answer1);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input).part(1, || part1(input.clone())).run();
 }
 
 #[cfg(test)]
@@ -1,41 +1,151 @@
 mod days;
 mod helpers;
 
+use std::time::Duration;
+
+use aoc_common::budget::{Budget, Budgets, MIB};
+use aoc_common::runner::{self, Format, Run};
+use aoc_common::{animate, export, memory, parallel, repl, trace};
+use itertools::Itertools;
+
+#[global_allocator]
+static ALLOCATOR: memory::Counting = memory::Counting;
+
+// Most days finish well within the default, so only the heavy ones are listed. Day 19 matches
+// scanners in about 130ms and under 1MiB, so the default is plenty for it too
+const BUDGETS: Budgets = Budgets {
+    default: Budget::new(Duration::from_secs(1), 64 * MIB),
+    days: &[
+        (18, Budget::new(Duration::from_secs(3), 64 * MIB)),
+        (23, Budget::new(Duration::from_secs(5), 512 * MIB)),
+        (25, Budget::new(Duration::from_secs(3), 64 * MIB)),
+    ],
+};
+
 fn main() {
-    let day: usize = std::env::args()
-        .nth(1)
-        .expect("Please specify a day")
-        .parse()
-        .expect("Day must be an integer");
+    let args: Vec<String> = std::env::args().collect();
+    let day = args.get(1).expect("Please specify a day");
+    let options = runner::Options::from_args(&args);
+    if day == "all" {
+        print!("{}", options.header());
+        let failed =
+            parallel::run_days(1..=25, &options.child_args(), |day| run_day(day, &options));
+        if !failed.is_empty() {
+            eprintln!("Days that failed: {}", failed.iter().join(", "));
+            std::process::exit(1);
+        }
+        return;
+    }
+    let day: usize = day.parse().expect("Day must be an integer or \"all\"");
 
-    println!("Day {}", day);
+    if let Some(options) = export::Options::from_args(&args) {
+        match day {
+            5 => days::day05::export(&options),
+            9 => days::day09::export(&options),
+            11 => days::day11::export(&options),
+            19 => days::day19::export(&options),
+            20 => days::day20::export(&options),
+            22 => days::day22::export(&options),
+            23 => days::day23::export(&options),
+            25 => days::day25::export(&options),
+            _ => panic!("Nothing to export for day {}", day),
+        }
+        return;
+    }
+
+    if let Some(options) = animate::Options::from_args(&args) {
+        match day {
+            11 => days::day11::animate(&options),
+            20 => days::day20::animate(&options),
+            23 => days::day23::animate(&options),
+            25 => days::day25::animate(&options),
+            _ => panic!("No animation for day {}", day),
+        }
+        return;
+    }
 
+    if let Some(options) = repl::Options::from_args(&args) {
+        match day {
+            1 => days::day01::explore(&options),
+            2 => days::day02::explore(&options),
+            3 => days::day03::explore(&options),
+            4 => days::day04::explore(&options),
+            5 => days::day05::explore(&options),
+            6 => days::day06::explore(&options),
+            7 => days::day07::explore(&options),
+            8 => days::day08::explore(&options),
+            9 => days::day09::explore(&options),
+            10 => days::day10::explore(&options),
+            11 => days::day11::explore(&options),
+            12 => days::day12::explore(&options),
+            13 => days::day13::explore(&options),
+            14 => days::day14::explore(&options),
+            15 => days::day15::explore(&options),
+            16 => days::day16::explore(&options),
+            17 => days::day17::explore(&options),
+            18 => days::day18::explore(&options),
+            19 => days::day19::explore(&options),
+            20 => days::day20::explore(&options),
+            21 => days::day21::explore(&options),
+            22 => days::day22::explore(&options),
+            23 => days::day23::explore(&options),
+            24 => days::day24::explore(&options),
+            25 => days::day25::explore(&options),
+            _ => panic!("Solution missing for day {}", day),
+        }
+        return;
+    }
+
+    print!("{}", options.header());
+    if !run_day(day, &options) {
+        std::process::exit(1);
+    }
+}
+
+/// Returns whether the day stayed within its budget.
+fn run_day(day: usize, options: &runner::Options) -> bool {
+    let mut run = Run::new(2021, day, options, BUDGETS.get(day));
     match day {
-        1 => days::day01::main(),
-        2 => days::day02::main(),
-        3 => days::day03::main(),
-        4 => days::day04::main(),
-        5 => days::day05::main(),
-        6 => days::day06::main(),
-        7 => days::day07::main(),
-        8 => days::day08::main(),
-        9 => days::day09::main(),
-        10 => days::day10::main(),
-        11 => days::day11::main(),
-        12 => days::day12::main(),
-        13 => days::day13::main(),
-        14 => days::day14::main(),
-        15 => days::day15::main(),
-        16 => days::day16::main(),
-        17 => days::day17::main(),
-        18 => days::day18::main(),
-        19 => days::day19::main(),
-        20 => days::day20::main(),
-        21 => days::day21::main(),
-        22 => days::day22::main(),
-        23 => days::day23::main(),
-        24 => days::day24::main(),
-        25 => days::day25::main(),
+        1 => days::day01::main(&mut run),
+        2 => days::day02::main(&mut run),
+        3 => days::day03::main(&mut run),
+        4 => days::day04::main(&mut run),
+        5 => days::day05::main(&mut run),
+        6 => days::day06::main(&mut run),
+        7 => days::day07::main(&mut run),
+        8 => days::day08::main(&mut run),
+        9 => days::day09::main(&mut run),
+        10 => days::day10::main(&mut run),
+        11 => days::day11::main(&mut run),
+        12 => days::day12::main(&mut run),
+        13 => days::day13::main(&mut run),
+        14 => days::day14::main(&mut run),
+        15 => days::day15::main(&mut run),
+        16 => days::day16::main(&mut run),
+        17 => days::day17::main(&mut run),
+        18 => days::day18::main(&mut run),
+        19 => days::day19::main(&mut run),
+        20 => days::day20::main(&mut run),
+        21 => days::day21::main(&mut run),
+        22 => days::day22::main(&mut run),
+        23 => days::day23::main(&mut run),
+        24 => days::day24::main(&mut run),
+        25 => days::day25::main(&mut run),
         _ => panic!("Solution missing for day {}", day),
     }
+
+    if trace::ENABLED {
+        // Records are meant to be read by scripts, so the report goes elsewhere
+        if options.format == Format::Text {
+            print!("{}", trace::report());
+        } else {
+            eprint!("{}", trace::report());
+        }
+        trace::reset();
+    }
+
+    for overrun in run.overruns() {
+        eprintln!("{}", overrun);
+    }
+    run.overruns().is_empty()
 }
@@ -0,0 +1,40 @@
+use std::process::{Command, Output};
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_aoc2021"))
+        .args(args)
+        .output()
+        .expect("Failed to run the program")
+}
+
+// Without the parallel feature the days run one after another in one process. That doesn't
+// change what each part is charged with, as a part only counts what it allocates itself. With it,
+// a day that goes over while the others are running is run again alone before it counts.
+#[test]
+fn within_budget() {
+    let output = run(&["all", "--format", "csv"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // Two parts for every day but day 25, which only has one
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let records = stdout.lines().filter(|line| line.starts_with("2021,"));
+    assert_eq!(records.count(), 49);
+}
+
+// Day 21 remembers a few MiB of game states in the second part, and next to nothing in the first
+#[test]
+fn over_memory_budget() {
+    let output = run(&["21", "--memory-budget", "1MiB"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stderr.contains("part 1 is over budget"), "{}", stderr);
+    assert!(
+        stderr.contains("2021 day 21 part 2 is over budget: allocated"),
+        "{}",
+        stderr
+    );
+}
@@ -1,28 +1,20 @@
 use std::cmp::Reverse;
 
+use aoc_common::parse::{integer, lines, parse_all, sections};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
 use itertools::Itertools;
 
 const INPUT: &str = include_str!("../../inputs/day01.txt");
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Input {
     elves: Vec<Vec<usize>>,
 }
 
 impl Input {
     fn new(input: &str) -> Input {
-        let mut elves = Vec::new();
-        let mut current_elf: Vec<usize> = Vec::new();
-        for line in input.lines() {
-            if line.is_empty() {
-                elves.push(current_elf);
-                current_elf = Vec::new();
-            } else {
-                let number = line.parse::<usize>().unwrap();
-                current_elf.push(number);
-            }
-        }
-        elves.push(current_elf);
+        let elves = parse_all(input, sections(lines(integer))).unwrap();
 
         Input { elves }
     }
@@ -47,12 +39,18 @@ fn part2(input: &Input) -> usize {
         .sum()
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
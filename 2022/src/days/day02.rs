@@ -1,15 +1,21 @@
 use std::{cmp::Ordering, convert::Infallible, str::FromStr};
 
-use serde_scan::scan;
+use aoc_common::parse::parse_all;
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+use nom::{
+    character::complete::{alpha1, char},
+    sequence::separated_pair,
+};
 
 const INPUT: &str = include_str!("../../inputs/day02.txt");
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Input {
     rounds: Vec<RoundInput>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct RoundInput {
     opponent: Throw,
     response: Response,
@@ -34,7 +40,7 @@ impl RoundInput {
     }
 }
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 enum Response {
     X,
     Y,
@@ -166,8 +172,8 @@ impl Input {
             rounds: input
                 .lines()
                 .map(|line| {
-                    let (opponent_str, response_str): (&str, &str) =
-                        scan!("{} {}" <- line).unwrap();
+                    let (opponent_str, response_str) =
+                        parse_all(line, separated_pair(alpha1, char(' '), alpha1)).unwrap();
                     let opponent = opponent_str.parse().unwrap();
                     let response = response_str.parse().unwrap();
                     RoundInput { opponent, response }
@@ -199,12 +205,18 @@ fn part2(input: &Input) -> usize {
         .sum()
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
@@ -1,5 +1,7 @@
 use std::{convert::Infallible, str::FromStr};
 
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
 use im::HashSet;
 use itertools::Itertools;
 
@@ -101,12 +103,18 @@ fn part2(input: &Input) -> u32 {
         .sum()
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
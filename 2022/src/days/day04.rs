@@ -1,6 +1,9 @@
 use std::{convert::Infallible, str::FromStr};
 
-use serde_scan::scan;
+use aoc_common::parse::{integer, parse_all};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+use nom::{character::complete::char, combinator::map, sequence::separated_pair, IResult};
 
 const INPUT: &str = include_str!("../../inputs/day04.txt");
 
@@ -29,16 +32,20 @@ impl FromStr for Pair {
     type Err = Infallible;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (start, end, start2, end2): (usize, usize, usize, usize) =
-            scan!("{}-{},{}-{}" <- s).unwrap();
-
-        let left = Range(start, end);
-        let right = Range(start2, end2);
+        let (left, right) =
+            parse_all(s, separated_pair(parse_range, char(','), parse_range)).unwrap();
 
         Ok(Self { left, right })
     }
 }
 
+fn parse_range(i: &str) -> IResult<&str, Range> {
+    map(
+        separated_pair(integer, char('-'), integer),
+        |(start, end)| Range(start, end),
+    )(i)
+}
+
 #[derive(Clone, Debug)]
 struct Range(usize, usize);
 
@@ -75,12 +82,18 @@ fn part2(input: &Input) -> usize {
         .count()
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
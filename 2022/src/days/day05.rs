@@ -1,5 +1,11 @@
+use aoc_common::parse::{integer, parse_all};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
 use itertools::Itertools;
-use serde_scan::scan;
+use nom::{
+    bytes::complete::tag,
+    sequence::{preceded, tuple},
+};
 
 const INPUT: &str = include_str!("../../inputs/day05.txt");
 
@@ -128,8 +134,15 @@ fn parse_stacks(str: &str) -> Vec<Stack> {
 fn parse_instructions(str: &str) -> Vec<Instruction> {
     str.lines()
         .map(|line| {
-            let (count, source, target): (_, usize, usize) =
-                scan!("move {} from {} to {}" <- line).unwrap();
+            let (count, source, target): (_, usize, usize) = parse_all(
+                line,
+                tuple((
+                    preceded(tag("move "), integer),
+                    preceded(tag(" from "), integer),
+                    preceded(tag(" to "), integer),
+                )),
+            )
+            .unwrap();
             Instruction {
                 // Ensure stacks are zero indexed
                 source: source - 1,
@@ -158,12 +171,18 @@ fn part2(input: &Input) -> String {
     stacks.iter().map(|stack| stack.peek().unwrap()).collect()
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
@@ -1,7 +1,15 @@
 use std::collections::HashMap;
 
+use aoc_common::parse::{integer, parse_all};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+use aoc_common::tree::{NodeId, Tree};
 use itertools::Itertools;
-use serde_scan::scan;
+use nom::{
+    bytes::complete::tag,
+    character::complete::{char, not_line_ending},
+    sequence::{preceded, separated_pair},
+};
 
 const INPUT: &str = include_str!("../../inputs/day07.txt");
 
@@ -9,123 +17,55 @@ const TOTAL_SPACE: usize = 70_000_000;
 const REQUIRED_SPACE: usize = 30_000_000;
 
 type DirName = String;
-type DirId = usize;
+type DirId = NodeId<Directory>;
 
 #[derive(Clone, Debug)]
 struct Input {
-    tree: Tree,
+    tree: Tree<Directory>,
+    root: DirId,
 }
 
-#[derive(Clone, Debug)]
-struct Tree {
-    arena: Arena,
-    node: DirId,
-}
-
-struct DirectorySizes<'a> {
-    sizes: HashMap<DirId, usize>,
-    tree: &'a Tree,
-}
-
-impl<'a> DirectorySizes<'a> {
-    fn new(tree: &'a Tree) -> Self {
-        Self {
-            sizes: HashMap::new(),
-            tree,
-        }
-    }
-
-    fn calculate(&mut self) -> HashMap<DirId, usize> {
-        self.calculate_directory_size(self.tree.node);
-        self.sizes.clone()
-    }
-
-    fn calculate_directory_size(&mut self, id: DirId) -> usize {
-        if let Some(size) = self.sizes.get(&id) {
-            return *size;
-        }
-
-        let mut size = 0;
-        let dir = self.tree.arena.get(id);
-        for file_size in dir.files.values() {
-            size += file_size;
-        }
-        for &child_id in dir.children.values() {
-            size += self.calculate_directory_size(child_id)
-        }
-
-        self.sizes.insert(id, size);
-
-        size
-    }
-}
-
-impl Tree {
+impl Input {
     fn directory_sizes(&self) -> HashMap<DirId, usize> {
-        let mut sizes = DirectorySizes::new(self);
-        sizes.calculate()
-    }
-}
-
-#[derive(Clone, Debug, Default)]
-/// Using an arena to own the data in our tree allows us to use integers as references
-/// This avoids needing to worry about Rust's ownership rules for references
-struct Arena {
-    data: Vec<Directory>,
-}
-
-impl Arena {
-    /// Add a new directory to the Arena, returning the new id
-    fn add(&mut self, dir: Directory) -> DirId {
-        let new_id = self.data.len();
-        self.data.push(dir);
-        new_id
-    }
-
-    /// Get a shared reference to a directory
-    fn get(&self, id: DirId) -> &Directory {
-        &self.data[id]
-    }
-
-    /// Get an exclusive reference to a directory
-    fn get_mut(&mut self, id: DirId) -> &mut Directory {
-        &mut self.data[id]
+        self.tree.aggregate(self.root, |dir, children| {
+            dir.files.values().sum::<usize>() + children.iter().sum::<usize>()
+        })
     }
 }
 
 #[derive(Clone, Debug, Default)]
 struct Directory {
-    // We need to keep a reference to the parent directory, so we can move up
-    parent: Option<DirId>,
-
-    // We need to keep a reference to all child directories, keyed by name
-    children: HashMap<DirName, DirId>,
+    name: DirName,
 
     // The data owned by this directory is the files it contains
     files: HashMap<String, usize>,
 }
 
 impl Directory {
-    fn new(parent: Option<DirId>) -> Self {
+    fn new(name: DirName) -> Self {
         Self {
-            parent,
-            children: HashMap::default(),
+            name,
             files: HashMap::default(),
         }
     }
+}
 
-    fn get_child_id(&self, name: &DirName) -> DirId {
-        *self.children.get(name).expect("failed to get child dir id")
-    }
+fn get_child_id(tree: &Tree<Directory>, id: DirId, name: &str) -> DirId {
+    *tree
+        .children(id)
+        .iter()
+        .find(|&&child| tree[child].name == name)
+        .expect("failed to get child dir id")
 }
 
 impl Input {
     fn new(input: &str) -> Input {
-        let mut arena = Arena::default();
+        // The tree keeps a reference to the parent directory, so we can move up
+        let mut tree = Tree::new();
 
         // Set up the top level directory
-        let directory = Directory::new(None);
-        let mut current_dir_id = arena.add(directory);
+        let root = tree.add(Directory::new("/".to_owned()));
+        let mut current_dir_id = root;
 
         // Skip the first line as it is just the top-level directory
         for line in input.lines().dropping(1) {
@@ -134,9 +74,8 @@ impl Input {
                 match line {
                     "$ cd .." => {
                         // Step up to the parent directory
-                        current_dir_id = arena
-                            .get(current_dir_id)
-                            .parent
+                        current_dir_id = tree
+                            .parent(current_dir_id)
                             .expect("directory has no parent")
                     }
                     "$ ls" => {
@@ -144,32 +83,29 @@ impl Input {
                     }
                     _ => {
                         // Step down to this directory
-                        let name: String = scan!("$ cd {}" <- line).unwrap();
-                        current_dir_id = arena.get(current_dir_id).get_child_id(&name);
+                        let name =
+                            parse_all(line, preceded(tag("$ cd "), not_line_ending)).unwrap();
+                        current_dir_id = get_child_id(&tree, current_dir_id, name);
                     }
                 }
             } else if line.starts_with('d') {
                 // It is a directory
-                let name: String = scan!("dir {}" <- line).unwrap();
-                let new_directory = Directory::new(Some(current_dir_id));
-                let dir_id = arena.add(new_directory);
-                arena.get_mut(current_dir_id).children.insert(name, dir_id);
+                let name = parse_all(line, preceded(tag("dir "), not_line_ending)).unwrap();
+                tree.add_child(current_dir_id, Directory::new(name.to_owned()));
             } else {
                 // It is a file, add it to the current directory
-                let (size, name): (usize, String) = scan!("{} {}" <- line).unwrap();
-                arena.get_mut(current_dir_id).files.insert(name, size);
+                let (size, name) =
+                    parse_all(line, separated_pair(integer, char(' '), not_line_ending)).unwrap();
+                tree[current_dir_id].files.insert(name.to_owned(), size);
             }
         }
 
-        let tree = Tree { arena, node: 0 };
-
-        Input { tree }
+        Input { tree, root }
     }
 }
 
 fn part1(input: &Input) -> usize {
     input
-        .tree
         .directory_sizes()
         .values()
         .filter(|&&s| s <= 100_000)
@@ -177,8 +113,8 @@ fn part1(input: &Input) -> usize {
 }
 
 fn part2(input: &Input) -> usize {
-    let sizes = input.tree.directory_sizes();
-    let current_space = sizes.get(&0).unwrap();
+    let sizes = input.directory_sizes();
+    let current_space = sizes.get(&input.root).unwrap();
     let remaining_space = TOTAL_SPACE - current_space;
     let required_to_delete = REQUIRED_SPACE - remaining_space;
     *sizes
@@ -188,12 +124,18 @@ fn part2(input: &Input) -> usize {
         .unwrap()
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
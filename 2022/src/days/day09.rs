@@ -1,6 +1,13 @@
 use std::collections::HashSet;
 
-use serde_scan::scan;
+use aoc_common::direction::Direction;
+use aoc_common::parse::{integer, parse_all};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+use nom::{
+    character::complete::{anychar, char},
+    sequence::separated_pair,
+};
 
 const INPUT: &str = include_str!("../../inputs/day09.txt");
 
@@ -15,8 +22,9 @@ impl Input {
             actions: input
                 .lines()
                 .flat_map(|line| {
-                    let (dir, distance): (char, usize) = scan!("{} {}" <- line).unwrap();
-                    let direction = dir.into();
+                    let (dir, distance): (char, usize) =
+                        parse_all(line, separated_pair(anychar, char(' '), integer)).unwrap();
+                    let direction = Direction::try_from(dir).unwrap();
                     vec![direction; distance]
                 })
                 .collect(),
@@ -24,26 +32,6 @@ impl Input {
     }
 }
 
-#[derive(Clone, Debug)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-
-impl From<char> for Direction {
-    fn from(c: char) -> Self {
-        match c {
-            'R' => Self::Right,
-            'L' => Self::Left,
-            'U' => Self::Up,
-            'D' => Self::Down,
-            _ => unreachable!(),
-        }
-    }
-}
-
 #[derive(Debug, Default, Hash, PartialEq, Eq, Clone, Copy)]
 struct Position {
     x: isize,
@@ -52,12 +40,9 @@ struct Position {
 
 impl Position {
     fn apply(&mut self, action: &Direction) {
-        match action {
-            Direction::Up => self.y += 1,
-            Direction::Down => self.y -= 1,
-            Direction::Left => self.x -= 1,
-            Direction::Right => self.x += 1,
-        }
+        let (dx, dy) = action.delta();
+        self.x += dx;
+        self.y += dy;
     }
 
     fn is_touching(&self, other: &Self) -> bool {
@@ -74,8 +59,8 @@ impl Position {
             _ => {}
         }
         match y_diff {
-            -1 | -2 => self.apply(&Direction::Up),
-            1 | 2 => self.apply(&Direction::Down),
+            -1 | -2 => self.apply(&Direction::Down),
+            1 | 2 => self.apply(&Direction::Up),
             _ => {}
         }
     }
@@ -134,12 +119,18 @@ fn part2(input: &Input) -> usize {
     rope.visited()
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
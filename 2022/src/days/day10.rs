@@ -1,4 +1,9 @@
-use serde_scan::scan;
+use aoc_common::animate::{colour_chars, play, Animation, Colour, Options};
+use aoc_common::export::{self, record, Palette};
+use aoc_common::parse::{integer, parse_all};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+use nom::{bytes::complete::tag, sequence::preceded};
 
 const INPUT: &str = include_str!("../../inputs/day10.txt");
 
@@ -16,7 +21,7 @@ impl Input {
                     if line == "noop" {
                         Instruction::NoOp
                     } else {
-                        let value = scan!("addx {}" <- line).unwrap();
+                        let value = parse_all(line, preceded(tag("addx "), integer)).unwrap();
                         Instruction::Add(value)
                     }
                 })
@@ -117,7 +122,7 @@ impl Crt {
     }
 }
 
-fn part2(input: &Input) {
+fn part2(input: &Input) -> String {
     let mut micro = Microcontroller::default();
     let mut crt = Crt::default();
 
@@ -127,14 +132,72 @@ fn part2(input: &Input) {
         });
     }
 
-    println!("{}", crt);
+    crt.to_string()
 }
 
-pub fn main() {
+// Draws the screen one instruction at a time
+struct Screen<'a> {
+    instructions: std::slice::Iter<'a, Instruction>,
+    micro: Microcontroller,
+    crt: Crt,
+}
+
+impl Screen<'_> {
+    fn new(input: &Input) -> Screen<'_> {
+        Screen {
+            instructions: input.instructions.iter(),
+            micro: Microcontroller::default(),
+            crt: Crt::default(),
+        }
+    }
+}
+
+impl Animation for Screen<'_> {
+    fn step(&mut self) -> bool {
+        let Some(instruction) = self.instructions.next() else {
+            return false;
+        };
+        let crt = &mut self.crt;
+        self.micro.apply(instruction, |cycle, x| crt.draw(x, cycle));
+        true
+    }
+
+    fn frame(&self) -> String {
+        let screen = colour_chars(&self.crt.to_string(), |c| {
+            (c == '#').then_some(Colour::Green)
+        });
+        format!(
+            "{}cycle {}, X = {}\n",
+            screen, self.micro.cycle, self.micro.x
+        )
+    }
+}
+
+const PALETTE: &Palette = &[('#', Colour::Green)];
+
+pub fn animate(options: &Options) {
+    let input = Input::new(INPUT);
+    play(&mut Screen::new(&input), options);
+}
+
+pub fn export(options: &export::Options) {
     let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    part2(&input);
+    let picture = |screen: &Screen| screen.crt.to_string();
+    record(&mut Screen::new(&input), picture, PALETTE, options);
+}
+
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
@@ -152,5 +215,22 @@ mod tests {
     fn answers() {
         let input = Input::new(INPUT);
         assert_eq!(part1(&input), 13480);
+        // The letters are drawn with trailing spaces, which don't matter
+        let drawing: Vec<String> = part2(&input)
+            .trim_end()
+            .lines()
+            .map(|line| line.trim_end().to_string())
+            .collect();
+        assert_eq!(
+            drawing,
+            [
+                "####  ##    ## ###   ##   ##  #### #  #",
+                "#    #  #    # #  # #  # #  # #    # #",
+                "###  #       # ###  #    #    ###  ##",
+                "#    # ##    # #  # # ## #    #    # #",
+                "#    #  # #  # #  # #  # #  # #    # #",
+                "####  ###  ##  ###   ###  ##  #    #  #",
+            ]
+        );
     }
 }
@@ -1,6 +1,7 @@
-use std::{cmp::Reverse, convert::Infallible, str::FromStr};
+use std::cmp::Reverse;
 
-use itertools::Itertools;
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
 
 const INPUT: &str = include_str!("../../inputs/day11.txt");
 
@@ -11,76 +12,69 @@ struct Input {
 
 impl Input {
     fn new(input: &str) -> Input {
-        let lines = input.lines().collect_vec();
-        let monkeys_str = lines.split(|line| line.is_empty());
-        let monkeys = monkeys_str
-            .map(|iter| {
-                // Drop the first line, which is just the monkey number
-                let mut iter = iter.iter().dropping(1);
-                let items_str = iter.next().unwrap();
-                let operation_str = iter.next().unwrap();
-                let test_str = iter.next().unwrap();
-                let true_str = iter.next().unwrap();
-                let false_str = iter.next().unwrap();
-
-                let items = items_str
-                    .split(": ")
-                    .dropping(1)
-                    .next()
-                    .unwrap()
-                    .split(", ")
-                    .map(|num| num.parse::<usize>().unwrap())
-                    .collect();
-
-                let operation = operation_str
-                    .split(" = ")
-                    .dropping(1)
-                    .next()
-                    .unwrap()
-                    .parse()
-                    .unwrap();
-
-                let test = test_str
-                    .split("by ")
-                    .dropping(1)
-                    .next()
-                    .unwrap()
-                    .parse()
-                    .unwrap();
-
-                let if_true = true_str
-                    .chars()
-                    .rev()
-                    .take(1)
-                    .next()
-                    .unwrap()
-                    .to_digit(10)
-                    .unwrap()
-                    .try_into()
-                    .unwrap();
-
-                let if_false = false_str
-                    .chars()
-                    .rev()
-                    .take(1)
-                    .next()
-                    .unwrap()
-                    .to_digit(10)
-                    .unwrap()
-                    .try_into()
-                    .unwrap();
-
-                Monkey {
-                    items,
-                    operation,
-                    divisor: test,
-                    if_true,
-                    if_false,
-                }
-            })
-            .collect_vec();
+        parser::parse_input(input)
+    }
+}
+
+// Separate module to avoid polluting the namespace with nom functions
+mod parser {
+    use super::*;
+    use aoc_common::parse::{comma_list, field, integer, parse_all, sections};
+    use nom::{
+        branch::alt,
+        bytes::complete::tag,
+        character::complete::line_ending,
+        combinator::{map, value},
+        sequence::{delimited, preceded, terminated, tuple},
+        IResult,
+    };
+
+    pub(super) fn parse_input(i: &str) -> Input {
+        Input {
+            monkeys: parse_all(i, sections(parse_monkey)).unwrap(),
+        }
+    }
 
-        Input { monkeys }
+    fn parse_monkey(i: &str) -> IResult<&str, Monkey> {
+        // The monkey number is just its position in the list, so can be skipped
+        let (i, _) = delimited(tag("Monkey "), integer::<usize>, tag(":"))(i)?;
+        map(
+            tuple((
+                preceded(line_ending, field("Starting items", comma_list(integer))),
+                preceded(line_ending, field("Operation", parse_operation)),
+                preceded(line_ending, field("Test", number_after("divisible by"))),
+                preceded(
+                    line_ending,
+                    field("If true", number_after("throw to monkey")),
+                ),
+                preceded(
+                    line_ending,
+                    field("If false", number_after("throw to monkey")),
+                ),
+            )),
+            |(items, operation, divisor, if_true, if_false)| Monkey {
+                items,
+                operation,
+                divisor,
+                if_true,
+                if_false,
+            },
+        )(i)
+    }
+
+    fn parse_operation(i: &str) -> IResult<&str, Operation> {
+        preceded(
+            tag("new = old "),
+            alt((
+                value(Operation::Square, tag("* old")),
+                map(preceded(tag("* "), integer), Operation::Multiply),
+                map(preceded(tag("+ "), integer), Operation::Add),
+            )),
+        )(i)
+    }
+
+    fn number_after<'a>(text: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, usize> {
+        preceded(terminated(tag(text), tag(" ")), integer)
     }
 }
 
@@ -100,31 +94,6 @@ enum Operation {
     Square,
 }
 
-impl FromStr for Operation {
-    type Err = Infallible;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s == "old * old" {
-            Ok(Operation::Square)
-        } else {
-            let num = s
-                .split(' ')
-                .rev()
-                .take(1)
-                .next()
-                .unwrap()
-                .parse::<usize>()
-                .unwrap();
-
-            if s.starts_with("old *") {
-                Ok(Operation::Multiply(num))
-            } else {
-                Ok(Operation::Add(num))
-            }
-        }
-    }
-}
-
 impl Operation {
     fn apply(&self, item: usize) -> usize {
         match self {
@@ -186,12 +155,22 @@ fn part2(input: &Input) -> usize {
     calculate_monkey_business(&mut input.monkeys.clone(), 10_000, false)
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part_with(1, &[("rounds", "20"), ("relief", "true")], |p| {
+            calculate_monkey_business(&mut input.monkeys.clone(), p.get("rounds"), p.get("relief"))
+        })
+        .part_with(2, &[("rounds", "10000"), ("relief", "false")], |p| {
+            calculate_monkey_business(&mut input.monkeys.clone(), p.get("rounds"), p.get("relief"))
+        })
+        .run();
 }
 
 #[cfg(test)]
@@ -1,5 +1,8 @@
 use std::cmp::Ordering;
 
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+use aoc_common::tree::{NodeId, Tree};
 use itertools::Itertools;
 
 const INPUT: &str = include_str!("../../inputs/day13.txt");
@@ -18,88 +21,107 @@ impl Input {
 // Separate module to avoid polluting the namespace with nom functions
 mod parser {
     use super::*;
+    use aoc_common::parse::{parse_all, sections};
     use nom::{
         bytes::complete::tag,
         character::complete::{line_ending, u8},
-        combinator::{all_consuming, map},
-        multi::{many0, separated_list0},
-        sequence::{delimited, separated_pair, terminated},
+        combinator::map,
+        multi::separated_list0,
+        sequence::{delimited, separated_pair},
         IResult, Parser,
     };
 
     pub(super) fn parse_input(i: &str) -> Input {
         Input {
-            pairs: parse_pairs(i).unwrap().1,
+            pairs: parse_all(i, sections(parse_packet_pair)).unwrap(),
         }
     }
 
-    fn parse_pairs(i: &str) -> IResult<&str, Vec<(Packet, Packet)>> {
-        all_consuming(many0(
-            terminated(parse_packet_pair, line_ending).or(parse_packet_pair),
-        ))(i)
-    }
-
     fn parse_packet_pair(i: &str) -> IResult<&str, (Packet, Packet)> {
-        terminated(
-            separated_pair(parse_packet, line_ending, parse_packet),
-            line_ending,
-        )(i)
+        separated_pair(parse_packet, line_ending, parse_packet)(i)
     }
 
     fn parse_packet(i: &str) -> IResult<&str, Packet> {
-        map(parse_packet_items, Packet::new)(i)
-    }
-
-    fn parse_packet_number(i: &str) -> IResult<&str, PacketItem> {
-        map(u8, PacketItem::Number)(i)
-    }
-
-    fn parse_packet_list(i: &str) -> IResult<&str, PacketItem> {
-        map(parse_packet_items, PacketItem::List)(i)
-    }
-
-    fn parse_packet_items(i: &str) -> IResult<&str, Vec<PacketItem>> {
-        delimited(
-            tag("["),
-            separated_list0(tag(","), parse_packet_number.or(parse_packet_list)),
-            tag("]"),
+        map(
+            // Unlike most lists, packets can be empty
+            delimited(
+                tag("["),
+                separated_list0(tag(","), map(u8, Packet::number).or(parse_packet)),
+                tag("]"),
+            ),
+            Packet::list,
         )(i)
     }
 }
 
-// This pair of structs defines a n-ary tree
+// Each packet owns an n-ary tree, where only lists have children
 #[derive(Clone, Debug)]
 struct Packet {
-    data: Vec<PacketItem>,
-}
-
-impl Packet {
-    fn new(data: Vec<PacketItem>) -> Self {
-        Self { data }
-    }
+    tree: Tree<PacketItem>,
+    root: NodeId<PacketItem>,
 }
 
 #[derive(Clone, Debug)]
 enum PacketItem {
     Number(u8),
-    List(Vec<PacketItem>),
+    List,
 }
 
-impl PartialEq for Packet {
-    fn eq(&self, other: &Self) -> bool {
-        self.data.cmp(&other.data) == Ordering::Equal
+impl Packet {
+    fn number(value: u8) -> Self {
+        let mut tree = Tree::new();
+        let root = tree.add(PacketItem::Number(value));
+        Self { tree, root }
+    }
+
+    fn list(items: Vec<Packet>) -> Self {
+        let mut tree = Tree::new();
+        let root = tree.add(PacketItem::List);
+        for item in items {
+            let child = tree.graft(&item.tree, item.root);
+            tree.append(root, child);
+        }
+        Self { tree, root }
+    }
+
+    fn cmp_items(&self, x: NodeId<PacketItem>, other: &Packet, y: NodeId<PacketItem>) -> Ordering {
+        match (&self.tree[x], &other.tree[y]) {
+            (PacketItem::Number(a), PacketItem::Number(b)) => a.cmp(b),
+            // A number compared with a list is treated as a list of just that number
+            (PacketItem::Number(_), PacketItem::List) => {
+                self.cmp_lists(&[x], other, other.tree.children(y))
+            }
+            (PacketItem::List, PacketItem::Number(_)) => {
+                self.cmp_lists(self.tree.children(x), other, &[y])
+            }
+            (PacketItem::List, PacketItem::List) => {
+                self.cmp_lists(self.tree.children(x), other, other.tree.children(y))
+            }
+        }
     }
-}
 
-impl Eq for Packet {}
+    fn cmp_lists(
+        &self,
+        xs: &[NodeId<PacketItem>],
+        other: &Packet,
+        ys: &[NodeId<PacketItem>],
+    ) -> Ordering {
+        // Compare item by item, and if one list runs out first it is the smaller
+        xs.iter()
+            .zip(ys)
+            .map(|(&x, &y)| self.cmp_items(x, other, y))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| xs.len().cmp(&ys.len()))
+    }
+}
 
-impl PartialEq for PacketItem {
+impl PartialEq for Packet {
     fn eq(&self, other: &Self) -> bool {
         self.cmp(other) == Ordering::Equal
     }
 }
 
-impl Eq for PacketItem {}
+impl Eq for Packet {}
 
 impl PartialOrd for Packet {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
@@ -109,25 +131,7 @@ impl PartialOrd for Packet {
 
 impl Ord for Packet {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.data.cmp(&other.data)
-    }
-}
-
-impl PartialOrd for PacketItem {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl Ord for PacketItem {
-    fn cmp(&self, other: &Self) -> Ordering {
-        match (self, other) {
-            (PacketItem::Number(x), PacketItem::Number(y)) => x.cmp(y),
-            (PacketItem::Number(_), PacketItem::List(y)) => vec![self.clone()].cmp(y),
-            (PacketItem::List(x), PacketItem::Number(_)) => x.cmp(&vec![other.clone()]),
-            // The complex sorting rules for lists are actually the default
-            (PacketItem::List(x), PacketItem::List(y)) => x.cmp(y),
-        }
+        self.cmp_items(self.root, other, other.root)
     }
 }
 
@@ -143,12 +147,8 @@ fn part1(input: &Input) -> usize {
 
 fn part2(input: &Input) -> usize {
     let mut packets = input.pairs.iter().flat_map(|(l, r)| [l, r]).collect_vec();
-    let divider_one = Packet {
-        data: vec![PacketItem::List(vec![PacketItem::Number(2)])],
-    };
-    let divider_two = Packet {
-        data: vec![PacketItem::List(vec![PacketItem::Number(6)])],
-    };
+    let divider_one = Packet::list(vec![Packet::list(vec![Packet::number(2)])]);
+    let divider_two = Packet::list(vec![Packet::list(vec![Packet::number(6)])]);
     packets.push(&divider_one);
     packets.push(&divider_two);
     packets.sort();
@@ -162,12 +162,18 @@ fn part2(input: &Input) -> usize {
         .product()
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
@@ -1,8 +1,13 @@
 use std::{
     cmp::{max, min},
     collections::HashSet,
+    fmt,
 };
 
+use aoc_common::animate::{colour_chars, play, Animation, Colour, Options};
+use aoc_common::export::{self, record, Palette};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
 use itertools::Itertools;
 
 const INPUT: &str = include_str!("../../inputs/day14.txt");
@@ -14,29 +19,27 @@ struct Input {
 
 impl Input {
     fn new(input: &str) -> Input {
-        let lines = input
-            .lines()
-            .map(|line| parser::parse_line(line).unwrap().1)
-            .collect();
-
-        Input { lines }
+        parser::parse_input(input)
     }
 }
 
 // Separate module to avoid polluting the namespace with nom functions
 mod parser {
     use super::*;
+    use aoc_common::parse::{lines, parse_all};
     use nom::{
-        bytes::complete::tag,
-        character::complete::u16,
-        combinator::{all_consuming, map},
-        multi::separated_list0,
-        sequence::separated_pair,
-        IResult,
+        bytes::complete::tag, character::complete::u16, combinator::map, multi::separated_list1,
+        sequence::separated_pair, IResult,
     };
 
-    pub(super) fn parse_line(i: &str) -> IResult<&str, Line> {
-        all_consuming(map(separated_list0(tag(" -> "), parse_coords), Line::new))(i)
+    pub(super) fn parse_input(i: &str) -> Input {
+        Input {
+            lines: parse_all(i, lines(parse_line)).unwrap(),
+        }
+    }
+
+    fn parse_line(i: &str) -> IResult<&str, Line> {
+        map(separated_list1(tag(" -> "), parse_coords), Line::new)(i)
     }
 
     fn parse_coords(i: &str) -> IResult<&str, Point> {
@@ -117,6 +120,8 @@ impl Point {
 struct Cave {
     // We track which points in the cave are filled with an object
     data: HashSet<Point>,
+    // The rock from the scan, which is only needed to tell rock and sand apart when drawing
+    rock: HashSet<Point>,
     // Max depth of the cave - anything below this is the abyss
     depth: usize,
     // Does the cave end in an abyss, or floor?
@@ -128,7 +133,12 @@ impl Cave {
         let data: HashSet<Point> = lines.iter().flat_map(|line| line.to_points()).collect();
         let depth = data.iter().map(|p| p.y).max().unwrap();
 
-        Self { data, depth, mode }
+        Self {
+            rock: data.clone(),
+            data,
+            depth,
+            mode,
+        }
     }
 
     fn is_filled(&self, p: &Point) -> bool {
@@ -191,6 +201,49 @@ impl Cave {
     }
 }
 
+impl fmt::Display for Cave {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let min_x = self.data.iter().map(|p| p.x).min().unwrap() - 1;
+        let max_x = self.data.iter().map(|p| p.x).max().unwrap() + 1;
+        let max_y = match self.mode {
+            Mode::Abyss => self.depth,
+            Mode::Floor => self.depth + 2,
+        };
+        for y in 0..=max_y {
+            for x in min_x..=max_x {
+                let p = Point::new((x, y));
+                let c = if (x, y) == (500, 0) {
+                    '+'
+                } else if self.rock.contains(&p) || y == self.depth + 2 {
+                    '#'
+                } else if self.data.contains(&p) {
+                    'o'
+                } else {
+                    '.'
+                };
+                write!(f, "{}", c)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Animation for Cave {
+    fn step(&mut self) -> bool {
+        self.drop_sand()
+    }
+
+    fn frame(&self) -> String {
+        colour_chars(&self.to_string(), |c| match c {
+            '#' => Some(Colour::Grey),
+            'o' => Some(Colour::Yellow),
+            '+' => Some(Colour::Red),
+            _ => None,
+        })
+    }
+}
+
 #[derive(PartialEq, Eq, Debug, Clone)]
 enum Mode {
     Abyss,
@@ -225,12 +278,35 @@ fn part2(input: &Input) -> usize {
     sand_dropped + 1
 }
 
-pub fn main() {
+const PALETTE: &Palette = &[
+    ('#', Colour::Grey),
+    ('o', Colour::Yellow),
+    ('+', Colour::Red),
+];
+
+pub fn animate(options: &Options) {
+    let input = Input::new(INPUT);
+    play(&mut Cave::new(&input.lines, Mode::Abyss), options);
+}
+
+pub fn export(options: &export::Options) {
     let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+    let mut cave = Cave::new(&input.lines, Mode::Abyss);
+    record(&mut cave, Cave::to_string, PALETTE, options);
+}
+
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
@@ -1,7 +1,14 @@
 use std::{convert::Infallible, str::FromStr};
 
+use aoc_common::parallel;
+use aoc_common::parse::{integer, parse_all};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
 use itertools::Itertools;
-use serde_scan::scan;
+use nom::{
+    bytes::complete::tag,
+    sequence::{preceded, tuple},
+};
 
 const INPUT: &str = include_str!("../../inputs/day15.txt");
 
@@ -32,8 +39,16 @@ impl FromStr for Observation {
     type Err = Infallible;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (sx, sy, bx, by) =
-            scan!("Sensor at x={}, y={}: closest beacon is at x={}, y={}" <- s).unwrap();
+        let (sx, sy, bx, by) = parse_all(
+            s,
+            tuple((
+                preceded(tag("Sensor at x="), integer),
+                preceded(tag(", y="), integer),
+                preceded(tag(": closest beacon is at x="), integer),
+                preceded(tag(", y="), integer),
+            )),
+        )
+        .unwrap();
         let sensor = Point::new(sx, sy);
         let beacon = Point::new(bx, by);
         Ok(Observation { sensor, beacon })
@@ -213,7 +228,7 @@ fn part2(input: &Input, max_coord: isize) -> isize {
     // The missing point must be between 4 diamonds
     // Or, more precisely, it must be the central point between 2 pairs of parallel overlapping lines
     // If we compare all pairs of diamonds, and find the points where their lines intersect, then we should be able to find the missing spot
-    let point = input
+    let candidates: Vec<Point> = input
         .diamonds
         .iter()
         // Look at every pair of diamonds
@@ -224,19 +239,28 @@ fn part2(input: &Input, max_coord: isize) -> isize {
         .unique()
         // Ensure the points are inside the boundary
         .filter(|p| p.x >= 0 && p.x <= max_coord && p.y >= 0 && p.y <= max_coord)
-        // Find the unique point that is not inside any of the other diamonds
-        .find(|p| input.diamonds.iter().all(|d| !d.contains(p)))
-        .unwrap();
+        .collect();
+    // Find the unique point that is not inside any of the other diamonds
+    let point = parallel::find_any(&candidates, |p| {
+        input.diamonds.iter().all(|d| !d.contains(p))
+    })
+    .unwrap();
 
     point.x * 4_000_000 + point.y
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input, 2_000_000);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input, 4_000_000);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input, 2_000_000));
+    run.part(2, || part2(&input, 4_000_000));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part_with(1, &[("row", "2000000")], |p| part1(&input, p.get("row")))
+        .part_with(2, &[("max", "4000000")], |p| part2(&input, p.get("max")))
+        .run();
 }
 
 #[cfg(test)]
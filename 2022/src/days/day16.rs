@@ -1,7 +1,16 @@
 use std::{collections::HashMap, convert::Infallible, str::FromStr};
 
+use aoc_common::parse::{integer, parse_all};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+use aoc_common::trace;
 use itertools::Itertools;
-use serde_scan::scan;
+use nom::{
+    bytes::complete::tag,
+    character::complete::alpha1,
+    multi::separated_list1,
+    sequence::{preceded, tuple},
+};
 
 const INPUT: &str = include_str!("../../inputs/day16.txt");
 
@@ -33,11 +42,21 @@ struct InputCave {
 impl FromStr for InputCave {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         // Modified input slightly to avoid the plural/singular difference (tunnels lead vs tunnel leads)
-        let (name, flow_rate, tunnels): (String, usize, String) =
-            scan!("Valve {} has flow rate={}; tunnels lead to valves {}" <- s).unwrap();
-        let tunnels = tunnels.split(", ").map(|s| s.to_owned()).collect();
+        let (name, flow_rate, tunnels) = parse_all(
+            s,
+            tuple((
+                preceded(tag("Valve "), alpha1),
+                preceded(tag(" has flow rate="), integer),
+                preceded(
+                    tag("; tunnels lead to valves "),
+                    separated_list1(tag(", "), alpha1),
+                ),
+            )),
+        )
+        .unwrap();
+        let tunnels = tunnels.into_iter().map(|s: &str| s.to_owned()).collect();
         Ok(Self {
-            name,
+            name: name.to_owned(),
             flow_rate,
             tunnels,
         })
@@ -49,32 +68,33 @@ impl FromStr for InputCave {
 type ValveId = usize;
 
 #[derive(Clone, Debug)]
-struct CaveSystem<const N: usize> {
+struct CaveSystem {
     start: usize,
     // time_to_travel[a][b] is the time to travel from a to b
-    time_to_travel: [[usize; N]; N],
+    time_to_travel: Vec<Vec<usize>>,
     // flow_rates[a] is the flow rate per minute from valve a
-    flow_rates: [usize; N],
+    flow_rates: Vec<usize>,
 }
 
-// Floyd-Warshall algorithm to find all pairwise shortest distances
-fn pairwise_shortest_distances<const N: usize>(
+// Floyd-Warshall algorithm to find all pairwise shortest distances between N caves
+fn pairwise_shortest_distances(
+    n: usize,
     edges: impl Iterator<Item = (usize, usize)>,
-) -> [[usize; N]; N] {
-    let mut time_to_travel = [[usize::MAX; N]; N];
+) -> Vec<Vec<usize>> {
+    let mut time_to_travel = vec![vec![usize::MAX; n]; n];
 
     for (from, to) in edges {
         time_to_travel[from][to] = 1;
         time_to_travel[to][from] = 1;
     }
 
-    (0..N).for_each(|i| {
+    (0..n).for_each(|i| {
         time_to_travel[i][i] = 0;
     });
 
-    for k in 0..N {
-        for i in 0..N {
-            for j in 0..N {
+    for k in 0..n {
+        for i in 0..n {
+            for j in 0..n {
                 if time_to_travel[i][k] == usize::MAX || time_to_travel[k][j] == usize::MAX {
                     continue;
                 }
@@ -88,14 +108,9 @@ fn pairwise_shortest_distances<const N: usize>(
     time_to_travel
 }
 
-impl<const N: usize> CaveSystem<N> {
+impl CaveSystem {
     fn build(caves: Vec<InputCave>) -> Self {
-        let flow_rates = caves
-            .iter()
-            .map(|c| c.flow_rate)
-            .collect_vec()
-            .try_into()
-            .unwrap();
+        let flow_rates = caves.iter().map(|c| c.flow_rate).collect();
 
         // We need a pairwise collection of the time to get between each pair of caves
         // Get a map so we can convert from name to id
@@ -112,7 +127,7 @@ impl<const N: usize> CaveSystem<N> {
                 .map(|name| (index, *name_to_id.get(name).unwrap()))
                 .collect_vec()
         });
-        let time_to_travel = pairwise_shortest_distances(edges);
+        let time_to_travel = pairwise_shortest_distances(caves.len(), edges);
 
         Self {
             start: name_to_id["AA"],
@@ -121,7 +136,7 @@ impl<const N: usize> CaveSystem<N> {
         }
     }
 
-    fn optimise<const M: usize>(self) -> CaveSystem<M> {
+    fn optimise(self) -> CaveSystem {
         // Optimise a CaveSystem by ordering by flow rate and removing those with zero weight
 
         // Maps from new_id to old_id + flow_rate
@@ -141,17 +156,13 @@ impl<const N: usize> CaveSystem<N> {
             .collect();
 
         // Order the flow rates
-        let flow_rates = flow_rates_map
-            .iter()
-            .map(|(_, f)| *f)
-            .collect_vec()
-            .try_into()
-            .unwrap();
+        let flow_rates = flow_rates_map.iter().map(|(_, f)| *f).collect();
 
         // Build a new time to travel matrix
-        let mut time_to_travel = [[usize::MAX; M]; M];
-        for i in 0..M {
-            for j in 0..M {
+        let m = flow_rates_map.len();
+        let mut time_to_travel = vec![vec![usize::MAX; m]; m];
+        for i in 0..m {
+            for j in 0..m {
                 if i == j {
                     time_to_travel[i][j] = 0;
                 } else {
@@ -168,6 +179,10 @@ impl<const N: usize> CaveSystem<N> {
         }
     }
 
+    fn len(&self) -> usize {
+        self.flow_rates.len()
+    }
+
     fn flow(&self, location: usize) -> usize {
         if location < self.flow_rates.len() {
             self.flow_rates[location]
@@ -198,27 +213,29 @@ impl<const N: usize> CaveSystem<N> {
 }
 
 #[derive(Clone, Debug)]
-struct CaveExplorer<const N: usize> {
+struct CaveExplorer {
     max_pressure_released: usize,
     visited: Vec<bool>,
 }
 
-impl<const N: usize> CaveExplorer<N> {
-    fn new() -> Self {
+impl CaveExplorer {
+    fn new(system: &CaveSystem) -> Self {
         Self {
             max_pressure_released: 0,
-            visited: vec![false; N + 1],
+            // Including the hidden cave that the elephant can stop in
+            visited: vec![false; system.len() + 1],
         }
     }
 
-    fn search(&mut self, system: &CaveSystem<N>, total_time: usize) -> usize {
+    fn search(&mut self, system: &CaveSystem, total_time: usize) -> usize {
         let total_flow = system.flow_rates.iter().sum();
         let state = State::new(0, total_time, total_flow);
         self.search_from(system, state);
         self.max_pressure_released
     }
 
-    fn search_from(&mut self, system: &CaveSystem<N>, mut state: State<N>) {
+    fn search_from(&mut self, system: &CaveSystem, mut state: State) {
+        trace::count("explorer calls");
         let location = state.person.location;
         let flow = system.flow(location);
         self.visited[location] = true;
@@ -241,14 +258,15 @@ impl<const N: usize> CaveExplorer<N> {
         self.visited[location] = false;
     }
 
-    fn search_pair(&mut self, system: &CaveSystem<N>, total_time: usize) -> usize {
+    fn search_pair(&mut self, system: &CaveSystem, total_time: usize) -> usize {
         let total_flow = system.flow_rates.iter().sum();
         let state = StatePair::new(0, total_time, total_flow);
         self.search_pair_from(system, state);
         self.max_pressure_released
     }
 
-    fn search_pair_from(&mut self, system: &CaveSystem<N>, mut state: StatePair<N>) {
+    fn search_pair_from(&mut self, system: &CaveSystem, mut state: StatePair) {
+        trace::count("explorer pair calls");
         let location_person = state.person.location;
         let location_elephant = state.elephant.location;
         self.visited[location_person] = true;
@@ -271,7 +289,7 @@ impl<const N: usize> CaveExplorer<N> {
 
         // Allow the elephant to move to a hidden cave, effectively stopping it's movement
         let mut possible_destinations_elephant = possible_destinations_elephant.collect_vec();
-        possible_destinations_elephant.push((N, 0));
+        possible_destinations_elephant.push((system.len(), 0));
 
         for (next, cost) in possible_destinations_person {
             let mut state = state.clone();
@@ -313,13 +331,13 @@ impl Person {
 }
 
 #[derive(Clone, Debug)]
-struct State<const N: usize> {
+struct State {
     pressure_released: usize,
     remaining_flow: usize,
     person: Person,
 }
 
-impl<const N: usize> State<N> {
+impl State {
     fn new(location: usize, time_left: usize, remaining_flow: usize) -> Self {
         Self {
             remaining_flow,
@@ -342,14 +360,14 @@ impl<const N: usize> State<N> {
 }
 
 #[derive(Clone, Debug)]
-struct StatePair<const N: usize> {
+struct StatePair {
     pressure_released: usize,
     remaining_flow: usize,
     person: Person,
     elephant: Person,
 }
 
-impl<const N: usize> StatePair<N> {
+impl StatePair {
     fn new(location: usize, time_left: usize, remaining_flow: usize) -> Self {
         Self {
             remaining_flow,
@@ -377,24 +395,30 @@ impl<const N: usize> StatePair<N> {
     }
 }
 
-fn part1<const N: usize, const M: usize>(input: &Input) -> usize {
-    let system: CaveSystem<M> = CaveSystem::<N>::build(input.valves.clone()).optimise();
+fn part1(input: &Input) -> usize {
+    let system = CaveSystem::build(input.valves.clone()).optimise();
 
-    CaveExplorer::new().search(&system, 30)
+    CaveExplorer::new(&system).search(&system, 30)
 }
 
-fn part2<const N: usize, const M: usize>(input: &Input) -> usize {
-    let system: CaveSystem<M> = CaveSystem::<N>::build(input.valves.clone()).optimise();
+fn part2(input: &Input) -> usize {
+    let system = CaveSystem::build(input.valves.clone()).optimise();
+
+    CaveExplorer::new(&system).search_pair(&system, 26)
+}
 
-    CaveExplorer::new().search_pair(&system, 26)
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1::<57, 16>(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2::<57, 16>(&input);
-    println!("Part 2: {}", answer2);
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
@@ -405,14 +429,14 @@ mod tests {
     #[test]
     fn examples() {
         let input = Input::new(TEST_INPUT);
-        assert_eq!(part1::<10, 7>(&input), 1651);
-        assert_eq!(part2::<10, 7>(&input), 1707);
+        assert_eq!(part1(&input), 1651);
+        assert_eq!(part2(&input), 1707);
     }
 
     #[test]
     fn answers() {
         let input = Input::new(INPUT);
-        assert_eq!(part1::<57, 16>(&input), 2359);
-        assert_eq!(part2::<57, 16>(&input), 2999);
+        assert_eq!(part1(&input), 2359);
+        assert_eq!(part2(&input), 2999);
     }
 }
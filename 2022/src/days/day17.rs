@@ -1,8 +1,17 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
+use std::fmt;
+
+use aoc_common::animate::{colour_chars, play, Animation, Colour, Options};
+use aoc_common::cycle::{Method, Projection};
+use aoc_common::export::{self, record, Palette};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
 
 const INPUT: &str = include_str!("../../inputs/day17.txt");
 
 const WIDTH: usize = 7;
+// How many rows of the tower to draw when animating
+const VIEW_HEIGHT: usize = 40;
 
 #[derive(Clone, Debug)]
 struct Input {
@@ -41,7 +50,7 @@ impl Action {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy)]
 enum Block {
     Horizontal,
     Plus,
@@ -51,11 +60,13 @@ enum Block {
 }
 
 impl Block {
-    fn iter() -> impl Iterator<Item = Block> + Clone {
-        use Block::*;
-
-        [Horizontal, Plus, Corner, Vertical, Square].into_iter()
-    }
+    const ALL: [Block; 5] = [
+        Block::Horizontal,
+        Block::Plus,
+        Block::Corner,
+        Block::Vertical,
+        Block::Square,
+    ];
 
     fn pixels(&self) -> impl Iterator<Item = &Point> {
         match self {
@@ -135,36 +146,28 @@ impl FallingBlock {
     }
 }
 
-#[derive(Debug, Clone, Default)]
-struct Tetris<A: Iterator<Item = (usize, Action)>, B: Iterator<Item = (usize, Block)>> {
+#[derive(Debug, Clone)]
+struct Tetris<'a> {
     pixels: HashSet<Point>,
-    actions: A,
-    blocks: B,
-    height_base: usize,
+    actions: &'a [Action],
+    next_action: usize,
+    next_block: usize,
     highest_pixels: [usize; WIDTH],
     highest_point: usize,
 }
 
-impl<A, B> Tetris<A, B>
-where
-    A: Iterator<Item = (usize, Action)>,
-    B: Iterator<Item = (usize, Block)>,
-{
-    fn new(actions: A, blocks: B) -> Self {
+impl<'a> Tetris<'a> {
+    fn new(actions: &'a [Action]) -> Self {
         Self {
             pixels: HashSet::new(),
             actions,
-            blocks,
-            height_base: 0,
+            next_action: 0,
+            next_block: 0,
             highest_pixels: [0; WIDTH],
             highest_point: 0,
         }
     }
 
-    fn final_height(&self) -> usize {
-        self.height_base + self.height()
-    }
-
     fn height(&self) -> usize {
         if self.pixels.is_empty() {
             0
@@ -173,16 +176,12 @@ where
         }
     }
 
-    fn play(&mut self, number_of_blocks: usize) {
-        // Action state, block state, & highest blocks in each column -> block count, height
-        let mut seen: HashMap<(usize, usize, [usize; WIDTH]), (usize, usize)> = HashMap::new();
-
-        let mut block_count = 0;
-        let (mut block_index, mut block) = self.next_block();
+    fn drop_block(&mut self) {
+        let mut block = self.next_block();
 
-        while block_count < number_of_blocks {
+        loop {
             // Action applies first
-            let (action_index, action) = self.next_action();
+            let action = self.next_action();
             let moved = block.apply(&action);
             if moved && self.intersecting(&block) {
                 // Hit something, reverse the movement
@@ -196,33 +195,23 @@ where
                     // Hit something, reverse the movement
                     block.rise_one();
                 } else {
-                    // Moved without hitting anything, drop the next block
+                    // Moved without hitting anything, keep falling
                     continue;
                 }
             }
 
             // The block has landed
-            block_count += 1;
             self.land_block(&block);
-
-            let key = (action_index, block_index, self.depth_map());
-            if let Some((prev_block_count, prev_height)) =
-                seen.insert(key, (block_count, self.height()))
-            {
-                // We can fast-forward because we saw this state before
-                let blocks_diff = block_count - prev_block_count;
-                let height_diff = self.height() - prev_height;
-
-                // We can leap forward by this many cycles, and increase our block count and height appropriately
-                let cycles = (number_of_blocks - block_count) / blocks_diff;
-                block_count += cycles * blocks_diff;
-                self.height_base += cycles * height_diff;
-            }
-
-            (block_index, block) = self.next_block();
+            return;
         }
     }
 
+    /// Action state, block state, & highest blocks in each column
+    /// If these repeat, then the tower will grow in the same way as it did before
+    fn state_key(&self) -> (usize, usize, [usize; WIDTH]) {
+        (self.next_action, self.next_block, self.depth_map())
+    }
+
     fn intersecting(&self, block: &FallingBlock) -> bool {
         block.pixels().any(|p| self.pixels.contains(&p))
     }
@@ -244,42 +233,128 @@ where
         self.highest_pixels.map(|x| x - lowest_highest_pixel)
     }
 
-    fn next_block(&mut self) -> (usize, FallingBlock) {
-        let (index, next) = self.blocks.next().unwrap();
-        let mut block: FallingBlock = next.into();
+    fn next_block(&mut self) -> FallingBlock {
+        let mut block: FallingBlock = Block::ALL[self.next_block].into();
+        self.next_block = (self.next_block + 1) % Block::ALL.len();
         block.position.1 += self.height();
-        (index, block)
+        block
     }
 
-    fn next_action(&mut self) -> (usize, Action) {
-        self.actions.next().unwrap()
+    fn next_action(&mut self) -> Action {
+        let action = self.actions[self.next_action];
+        self.next_action = (self.next_action + 1) % self.actions.len();
+        action
     }
 }
 
-fn part1(input: &Input) -> usize {
-    let actions = input.actions.iter().copied().enumerate().cycle();
-    let block = Block::iter().enumerate().cycle();
-    let mut tetris = Tetris::new(actions, block);
-    tetris.play(2022);
+impl fmt::Display for Tetris<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Only the top of the tower fits on screen, with room above for the next block to appear
+        let top = self.height() + 3;
+        for y in (top.saturating_sub(VIEW_HEIGHT)..top).rev() {
+            write!(f, "|")?;
+            for x in 0..WIDTH {
+                let c = if self.pixels.contains(&(x, y)) {
+                    '#'
+                } else {
+                    '.'
+                };
+                write!(f, "{}", c)?;
+            }
+            writeln!(f, "|")?;
+        }
+        if top <= VIEW_HEIGHT {
+            writeln!(f, "+{}+", "-".repeat(WIDTH))?;
+        }
+        Ok(())
+    }
+}
 
-    tetris.final_height()
+struct Tower<'a> {
+    tetris: Tetris<'a>,
+    blocks: usize,
 }
 
-fn part2(input: &Input) -> usize {
-    let actions = input.actions.iter().copied().enumerate().cycle();
-    let block = Block::iter().enumerate().cycle();
-    let mut tetris = Tetris::new(actions, block);
-    tetris.play(1_000_000_000_000);
+impl Tower<'_> {
+    fn new(input: &Input) -> Tower<'_> {
+        Tower {
+            tetris: Tetris::new(&input.actions),
+            blocks: 0,
+        }
+    }
+}
+
+impl Animation for Tower<'_> {
+    fn step(&mut self) -> bool {
+        if self.blocks == 2022 {
+            return false;
+        }
+        self.tetris.drop_block();
+        self.blocks += 1;
+        true
+    }
 
-    tetris.final_height()
+    fn frame(&self) -> String {
+        let tower = colour_chars(&self.tetris.to_string(), |c| match c {
+            '#' => Some(Colour::Cyan),
+            '|' | '-' | '+' => Some(Colour::Grey),
+            _ => None,
+        });
+        format!("{}height {}\n", tower, self.tetris.height())
+    }
 }
 
-pub fn main() {
+const PALETTE: &Palette = &[
+    ('#', Colour::Cyan),
+    ('|', Colour::Grey),
+    ('-', Colour::Grey),
+    ('+', Colour::Grey),
+];
+
+pub fn animate(options: &Options) {
     let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+    play(&mut Tower::new(&input), options);
+}
+
+pub fn export(options: &export::Options) {
+    let input = Input::new(INPUT);
+    let picture = |tower: &Tower| tower.tetris.to_string();
+    record(&mut Tower::new(&input), picture, PALETTE, options);
+}
+
+fn tower_height(input: &Input, number_of_blocks: usize) -> usize {
+    let tetris = Tetris::new(&input.actions);
+    // The tower grows by the same height every time the state repeats, so we can fast-forward
+    let heights = Projection::new(
+        &tetris,
+        Tetris::drop_block,
+        Tetris::state_key,
+        Tetris::height,
+        Method::HashMap,
+    );
+    heights.at(number_of_blocks)
+}
+
+fn part1(input: &Input) -> usize {
+    tower_height(input, 2022)
+}
+
+fn part2(input: &Input) -> usize {
+    tower_height(input, 1_000_000_000_000)
+}
+
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
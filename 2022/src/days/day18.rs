@@ -1,6 +1,14 @@
 use std::collections::{HashMap, HashSet, VecDeque};
 
-use serde_scan::scan;
+use aoc_common::export::Options;
+use aoc_common::mesh::Mesh;
+use aoc_common::parse::{integer, parse_all};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+use nom::{
+    character::complete::char,
+    sequence::{preceded, tuple},
+};
 
 const INPUT: &str = include_str!("../../inputs/day18.txt");
 
@@ -14,8 +22,15 @@ impl Input {
         let cubes = input
             .lines()
             .map(|line| {
-                let (x, y, z) = scan!("{},{},{}" <- line).unwrap();
-                (x, y, z)
+                parse_all(
+                    line,
+                    tuple((
+                        integer,
+                        preceded(char(','), integer),
+                        preceded(char(','), integer),
+                    )),
+                )
+                .unwrap()
             })
             .collect();
 
@@ -94,6 +109,11 @@ impl Droplet {
 
         self.count_all_uncovered_faces() - inner_droplet.count_all_uncovered_faces()
     }
+
+    /// Every uncovered face, including those around air pockets.
+    fn surface(&self) -> Mesh {
+        Mesh::voxel_surface(self.cubes.iter().map(|&(x, y, z)| [x, y, z]))
+    }
 }
 
 type Coord = (isize, isize, isize);
@@ -120,12 +140,22 @@ fn part2(input: &Input) -> usize {
     input.droplet.count_external_faces()
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn export(options: &Options) {
+    Input::new(INPUT).droplet.surface().save(options);
+}
+
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
@@ -138,6 +168,7 @@ mod tests {
         let input = Input::new(TEST_INPUT);
         assert_eq!(part1(&input), 64);
         assert_eq!(part2(&input), 58);
+        assert_eq!(input.droplet.surface().face_count(), 64);
     }
 
     #[test]
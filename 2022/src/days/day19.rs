@@ -1,5 +1,8 @@
 use std::{convert::Infallible, str::FromStr};
 
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+use aoc_common::{parallel, trace};
 use itertools::Itertools;
 
 const INPUT: &str = include_str!("../../inputs/day19.txt");
@@ -162,6 +165,7 @@ impl Blueprint {
     }
 
     fn max_score(&self, time_left: usize) -> usize {
+        let _span = trace::span("blueprint");
         let state = State::new(time_left);
         let mut best_score = 0;
 
@@ -195,6 +199,7 @@ impl Blueprint {
     }
 
     fn find_best_score(&self, mut state: State, target: &Material, best_score: &mut usize) {
+        trace::count("states explored");
         while state.time_left > 0 {
             if state.sufficient_to_build(self.cost(target)) {
                 // We can build a robot and move forward
@@ -226,29 +231,33 @@ impl Blueprint {
 }
 
 fn part1(input: &Input) -> usize {
-    input
-        .blueprints
-        .iter()
+    // Each blueprint is scored on its own
+    parallel::map(&input.blueprints, |b| b.max_score(24))
+        .into_iter()
         .enumerate()
-        .map(|(i, b)| b.max_score(24) * (i + 1))
+        .map(|(i, score)| score * (i + 1))
         .sum()
 }
 
 fn part2(input: &Input) -> usize {
-    input
-        .blueprints
-        .iter()
-        .take(3)
-        .map(|b| b.max_score(32))
+    let blueprints = &input.blueprints[..input.blueprints.len().min(3)];
+    parallel::map(blueprints, |b| b.max_score(32))
+        .into_iter()
         .product()
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
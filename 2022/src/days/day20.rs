@@ -1,3 +1,5 @@
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
 use itertools::Itertools;
 
 const INPUT: &str = include_str!("../../inputs/day20.txt");
@@ -19,33 +21,26 @@ impl Input {
 }
 
 #[derive(Debug, Clone)]
-struct DoublyLinkedList<const N: usize> {
-    numbers: [isize; N],
+struct DoublyLinkedList {
+    numbers: Vec<isize>,
     // The index of the next element after the one at this index
-    next_ptr: [usize; N],
+    next_ptr: Vec<usize>,
     // The index of the previous element
-    prev_ptr: [usize; N],
+    prev_ptr: Vec<usize>,
 }
 
-impl<const N: usize> From<Vec<isize>> for DoublyLinkedList<N> {
+impl From<Vec<isize>> for DoublyLinkedList {
     fn from(numbers: Vec<isize>) -> Self {
+        let n = numbers.len();
         Self {
-            numbers: numbers.try_into().unwrap(),
-            next_ptr: (0..N)
-                .map(|i| (i + 1) % N)
-                .collect_vec()
-                .try_into()
-                .unwrap(),
-            prev_ptr: (0..N)
-                .map(|i| (i + N - 1) % N)
-                .collect_vec()
-                .try_into()
-                .unwrap(),
+            numbers,
+            next_ptr: (0..n).map(|i| (i + 1) % n).collect(),
+            prev_ptr: (0..n).map(|i| (i + n - 1) % n).collect(),
         }
     }
 }
 
-impl<const N: usize> DoublyLinkedList<N> {
+impl DoublyLinkedList {
     fn get(&self, index: usize) -> isize {
         self.numbers[index % self.numbers.len()]
     }
@@ -122,14 +117,14 @@ impl<const N: usize> DoublyLinkedList<N> {
     }
 }
 
-fn part1<const N: usize>(input: &Input) -> isize {
-    let mut cycle: DoublyLinkedList<N> = input.numbers.clone().into();
+fn part1(input: &Input) -> isize {
+    let mut cycle: DoublyLinkedList = input.numbers.clone().into();
     cycle.mix();
     cycle.grove()
 }
 
-fn part2<const N: usize>(input: &Input) -> isize {
-    let mut cycle: DoublyLinkedList<N> = input.numbers.clone().into();
+fn part2(input: &Input) -> isize {
+    let mut cycle: DoublyLinkedList = input.numbers.clone().into();
     cycle.decrypt(811589153);
     for _ in 0..10 {
         cycle.mix();
@@ -137,12 +132,18 @@ fn part2<const N: usize>(input: &Input) -> isize {
     cycle.grove()
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1::<5000>(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2::<5000>(&input);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
@@ -153,14 +154,14 @@ mod tests {
     #[test]
     fn examples() {
         let input = Input::new(TEST_INPUT);
-        assert_eq!(part1::<7>(&input), 3);
-        assert_eq!(part2::<7>(&input), 1623178306);
+        assert_eq!(part1(&input), 3);
+        assert_eq!(part2(&input), 1623178306);
     }
 
     #[test]
     fn answers() {
         let input = Input::new(INPUT);
-        assert_eq!(part1::<5000>(&input), 14526);
-        assert_eq!(part2::<5000>(&input), 9738258246847);
+        assert_eq!(part1(&input), 14526);
+        assert_eq!(part2(&input), 9738258246847);
     }
 }
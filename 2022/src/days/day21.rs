@@ -1,5 +1,11 @@
 use std::collections::HashMap;
 
+use aoc_common::memo::Memo;
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+use aoc_common::tree::{NodeId, Tree};
+use itertools::Itertools;
+
 const INPUT: &str = include_str!("../../inputs/day21.txt");
 
 #[derive(Clone, Debug)]
@@ -29,6 +35,17 @@ enum Action {
     Div,
 }
 
+impl Action {
+    fn apply(&self, left: usize, right: usize) -> usize {
+        match self {
+            Action::Add => left + right,
+            Action::Sub => left - right,
+            Action::Mul => left * right,
+            Action::Div => left / right,
+        }
+    }
+}
+
 impl Input {
     fn new(input: &str) -> Input {
         let monkeys = input
@@ -59,216 +76,136 @@ impl Input {
     }
 }
 
+// The monkeys form a binary tree, with the two monkeys each operation waits on as its children
 #[derive(Debug, Clone)]
-struct MonkeyForest {
-    monkeys: HashMap<Name, Job>,
-    cache: HashMap<Name, usize>,
+struct MonkeyTree {
+    tree: Tree<Monkey>,
+    root: NodeId<Monkey>,
+    ids: HashMap<Name, NodeId<Monkey>>,
+    // Each monkey's number is worked out once, however many times it's asked for
+    numbers: Memo<NodeId<Monkey>, usize>,
 }
 
-impl MonkeyForest {
-    fn new(monkeys: Vec<Monkey>) -> Self {
+impl MonkeyTree {
+    fn new(monkeys: &[Monkey]) -> Self {
+        let monkeys: HashMap<&str, &Monkey> =
+            monkeys.iter().map(|m| (m.name.as_str(), m)).collect();
+        let mut tree = Tree::new();
+        let mut ids = HashMap::new();
+        let root = Self::add(&mut tree, &mut ids, &monkeys, "root");
         Self {
-            monkeys: monkeys
-                .iter()
-                .map(|m| (m.name.clone(), m.job.clone()))
-                .collect(),
-            cache: HashMap::new(),
+            tree,
+            root,
+            ids,
+            numbers: Memo::new(),
         }
     }
 
-    fn set_action(&mut self, name: &str, action: Action) {
-        let job = &self.monkeys[name];
-        if let Job::Operation(_, left, right) = job {
-            self.monkeys.insert(
-                name.to_owned(),
-                Job::Operation(action, left.clone(), right.clone()),
-            );
-        } else {
-            panic!("Monkey does not have operation, cannot set action")
+    fn add(
+        tree: &mut Tree<Monkey>,
+        ids: &mut HashMap<Name, NodeId<Monkey>>,
+        monkeys: &HashMap<&str, &Monkey>,
+        name: &str,
+    ) -> NodeId<Monkey> {
+        let monkey = monkeys[name];
+        let id = tree.add(monkey.clone());
+        ids.insert(name.to_owned(), id);
+        if let Job::Operation(_, left, right) = &monkey.job {
+            for child in [left, right] {
+                let child = Self::add(tree, ids, monkeys, child);
+                tree.append(id, child);
+            }
         }
+        id
     }
 
-    fn find_number(&mut self, name: &str) -> usize {
-        let monkey = self.monkeys[name].clone();
-        match monkey {
-            Job::Number(val) => val,
-            Job::Operation(ref action, ref left, ref right) => {
-                let left = if self.cache.contains_key(left) {
-                    self.cache[left]
-                } else {
-                    self.find_number(left)
-                };
-                let right = if self.cache.contains_key(right) {
-                    self.cache[right]
-                } else {
-                    self.find_number(right)
-                };
-
-                match action {
-                    Action::Add => left + right,
-                    Action::Sub => left - right,
-                    Action::Mul => left * right,
-                    Action::Div => left / right,
+    fn number(&mut self, id: NodeId<Monkey>) -> usize {
+        let tree = &self.tree;
+        self.numbers
+            .solve(&id, &|&id, recurse| match &tree[id].job {
+                Job::Number(val) => *val,
+                Job::Operation(action, _, _) => {
+                    let children = tree.children(id);
+                    action.apply(recurse(&children[0]), recurse(&children[1]))
                 }
-            }
-        }
+            })
+    }
+
+    fn find_number(&mut self, name: &str) -> usize {
+        self.number(self.ids[name])
     }
 
     fn find_number_derived(&mut self, target: &str) -> usize {
         // humn is meant to be an input, and root = x EQ y
-        // But we can rebase, and instead search for humn given root = x - y = 0
-
-        // We need to figure out humn, so we remove it
-        self.monkeys.remove(target);
-
-        // We know root must = 0
-        self.cache.insert("root".to_owned(), 0);
-
-        // We also know that root = x - y
-        self.set_action("root", Action::Sub);
-
-        // This lets us derive:
-        //  x = y + root
-        //  y = x - root
-        // We can do the same derivation for all other formulas
-        let derived_formulas: Vec<(Name, Job)> = self
-            .monkeys
-            .iter()
-            .flat_map(|(name, job)| match job {
-                Job::Number(_) => vec![(name.clone(), job.clone())],
-                Job::Operation(action, left, right) => match action {
-                    // N = L + R
-                    // L = N - R
-                    // R = N - L
-                    Action::Add => vec![
-                        (
-                            name.clone(),
-                            Job::Operation(Action::Add, left.clone(), right.clone()),
-                        ),
-                        (
-                            left.clone(),
-                            Job::Operation(Action::Sub, name.clone(), right.clone()),
-                        ),
-                        (
-                            right.clone(),
-                            Job::Operation(Action::Sub, name.clone(), left.clone()),
-                        ),
-                    ],
-                    // N = L - R
-                    // L = N + R
-                    // R = L - N
-                    Action::Sub => vec![
-                        (
-                            name.clone(),
-                            Job::Operation(Action::Sub, left.clone(), right.clone()),
-                        ),
-                        (
-                            left.clone(),
-                            Job::Operation(Action::Add, name.clone(), right.clone()),
-                        ),
-                        (
-                            right.clone(),
-                            Job::Operation(Action::Sub, left.clone(), name.clone()),
-                        ),
-                    ],
-                    // N = L * R
-                    // L = N / R
-                    // R = N / L
-                    Action::Mul => vec![
-                        (
-                            name.clone(),
-                            Job::Operation(Action::Mul, left.clone(), right.clone()),
-                        ),
-                        (
-                            left.clone(),
-                            Job::Operation(Action::Div, name.clone(), right.clone()),
-                        ),
-                        (
-                            right.clone(),
-                            Job::Operation(Action::Div, name.clone(), left.clone()),
-                        ),
-                    ],
-                    // N = L / R
-                    // L = N * R
-                    // R = L / N
-                    Action::Div => vec![
-                        (
-                            name.clone(),
-                            Job::Operation(Action::Div, left.clone(), right.clone()),
-                        ),
-                        (
-                            left.clone(),
-                            Job::Operation(Action::Mul, name.clone(), right.clone()),
-                        ),
-                        (
-                            right.clone(),
-                            Job::Operation(Action::Div, left.clone(), name.clone()),
-                        ),
-                    ],
-                },
-            })
-            .collect();
-
-        // Loop through inefficiently
-        // We should find at least one result on each cycle, so this is O(n^2)
-        while !self.cache.contains_key(target) {
-            for (name, job) in derived_formulas.iter() {
-                if self.cache.contains_key(name) {
-                    continue;
-                }
-                if let Some(value) = self.find_number_once(job) {
-                    self.cache.insert(name.clone(), value);
-                }
-            }
+        // Every monkey between humn and root depends on humn, and every other monkey doesn't,
+        // so we can walk down from root and undo each operation in turn.
+        // Working out root's number leaves every monkey's number in the cache
+        self.number(self.root);
+        let target = self.ids[target];
+        let mut path = self.tree.ancestors(target).collect::<Vec<_>>();
+        path.reverse();
+        path.push(target);
+
+        // For root, the side that depends on humn has to equal the other side
+        let mut value = None;
+        for (&node, &next) in path.iter().tuple_windows() {
+            let children = self.tree.children(node);
+            let next_is_left = children[0] == next;
+            let other = *self
+                .numbers
+                .cached(&children[if next_is_left { 1 } else { 0 }])
+                .unwrap();
+            let Some(n) = value else {
+                value = Some(other);
+                continue;
+            };
+            let Job::Operation(action, _, _) = &self.tree[node].job else {
+                unreachable!("Monkeys on the path to humn all have operations")
+            };
+            value = Some(match (action, next_is_left) {
+                // N = L + R, so L = N - R and R = N - L
+                (Action::Add, _) => n - other,
+                // N = L - R, so L = N + R and R = L - N
+                (Action::Sub, true) => n + other,
+                (Action::Sub, false) => other - n,
+                // N = L * R, so L = N / R and R = N / L
+                (Action::Mul, _) => n / other,
+                // N = L / R, so L = N * R and R = L / N
+                (Action::Div, true) => n * other,
+                (Action::Div, false) => other / n,
+            });
         }
 
-        self.cache[target]
-    }
-
-    fn find_number_once(&self, job: &Job) -> Option<usize> {
-        match job {
-            Job::Number(val) => Some(*val),
-            Job::Operation(ref action, ref left, ref right) => {
-                let left = if self.cache.contains_key(left) {
-                    self.cache[left]
-                } else {
-                    return None;
-                };
-                let right = if self.cache.contains_key(right) {
-                    self.cache[right]
-                } else {
-                    return None;
-                };
-
-                let v = match action {
-                    Action::Add => left + right,
-                    Action::Sub => left - right,
-                    Action::Mul => left * right,
-                    Action::Div => left / right,
-                };
-
-                Some(v)
-            }
-        }
+        value.unwrap()
     }
 }
 
 fn part1(input: &Input) -> usize {
-    let mut forest = MonkeyForest::new(input.monkeys.clone());
-    forest.find_number("root")
+    let mut tree = MonkeyTree::new(&input.monkeys);
+    let number = tree.find_number("root");
+    tree.numbers.stats().trace();
+    number
 }
 
 fn part2(input: &Input) -> usize {
-    let mut forest = MonkeyForest::new(input.monkeys.clone());
-    forest.find_number_derived("humn")
+    let mut tree = MonkeyTree::new(&input.monkeys);
+    let number = tree.find_number_derived("humn");
+    tree.numbers.stats().trace();
+    number
+}
+
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
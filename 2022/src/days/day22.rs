@@ -1,5 +1,9 @@
 use std::collections::HashMap;
 
+use aoc_common::direction::{Direction, Turn};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+
 const INPUT: &str = include_str!("../../inputs/day22.txt");
 
 #[derive(Clone, Debug)]
@@ -25,15 +29,7 @@ mod parser {
     }
 
     fn parse_turn(i: &str) -> IResult<&str, Turn> {
-        map(one_of("RL"), char_to_turn)(i)
-    }
-
-    fn char_to_turn(i: char) -> Turn {
-        match i {
-            'R' => Turn::Right,
-            'L' => Turn::Left,
-            _ => unreachable!(),
-        }
+        map(one_of("RL"), |c| Turn::try_from(c).unwrap())(i)
     }
 }
 
@@ -75,27 +71,13 @@ enum Instruction {
     Turn(Turn),
 }
 
-#[derive(Clone, Debug)]
-enum Turn {
-    Right,
-    Left,
-}
-
-#[derive(Clone, Copy, Debug)]
-enum Facing {
-    Right,
-    Down,
-    Left,
-    Up,
-}
-
 trait Wrapper {
     fn next(
         &self,
         grid: &Grid,
         position: &(isize, isize),
-        facing: &Facing,
-    ) -> ((isize, isize), Facing);
+        facing: &Direction,
+    ) -> ((isize, isize), Direction);
 }
 
 #[derive(Clone, Debug)]
@@ -106,13 +88,13 @@ impl Wrapper for SimpleWrap {
         &self,
         grid: &Grid,
         position: &(isize, isize),
-        facing: &Facing,
-    ) -> ((isize, isize), Facing) {
+        facing: &Direction,
+    ) -> ((isize, isize), Direction) {
         let p = match facing {
-            Facing::Up => grid.keys().filter(|(x, _)| *x == position.0).copied().max(),
-            Facing::Down => grid.keys().filter(|(x, _)| *x == position.0).copied().min(),
-            Facing::Left => grid.keys().filter(|(_, y)| *y == position.1).copied().max(),
-            Facing::Right => grid.keys().filter(|(_, y)| *y == position.1).copied().min(),
+            Direction::Up => grid.keys().filter(|(x, _)| *x == position.0).copied().max(),
+            Direction::Down => grid.keys().filter(|(x, _)| *x == position.0).copied().min(),
+            Direction::Left => grid.keys().filter(|(_, y)| *y == position.1).copied().max(),
+            Direction::Right => grid.keys().filter(|(_, y)| *y == position.1).copied().min(),
         }
         .unwrap();
         (p, *facing)
@@ -127,8 +109,8 @@ impl Wrapper for CubeWrap {
         &self,
         _grid: &Grid,
         position: &(isize, isize),
-        facing: &Facing,
-    ) -> ((isize, isize), Facing) {
+        facing: &Direction,
+    ) -> ((isize, isize), Direction) {
         let x_rem = position.0 % 50;
         let y_rem = position.1 % 50;
 
@@ -136,23 +118,23 @@ impl Wrapper for CubeWrap {
         let y_div = position.1 / 50;
 
         match (facing, x_div, y_div) {
-            (Facing::Right, 0, 3) => ((50 + y_rem, 149), Facing::Up),
-            (Facing::Right, 1, 1) => ((100 + y_rem, 49), Facing::Up),
-            (Facing::Right, 1, 2) => ((149, 49 - y_rem), Facing::Left),
-            (Facing::Right, 2, 0) => ((99, 149 - y_rem), Facing::Left),
+            (Direction::Right, 0, 3) => ((50 + y_rem, 149), Direction::Up),
+            (Direction::Right, 1, 1) => ((100 + y_rem, 49), Direction::Up),
+            (Direction::Right, 1, 2) => ((149, 49 - y_rem), Direction::Left),
+            (Direction::Right, 2, 0) => ((99, 149 - y_rem), Direction::Left),
 
-            (Facing::Down, 0, 3) => ((100 + x_rem, 0), Facing::Down),
-            (Facing::Down, 1, 2) => ((49, 150 + x_rem), Facing::Left),
-            (Facing::Down, 2, 0) => ((99, 50 + x_rem), Facing::Left),
+            (Direction::Down, 0, 3) => ((100 + x_rem, 0), Direction::Down),
+            (Direction::Down, 1, 2) => ((49, 150 + x_rem), Direction::Left),
+            (Direction::Down, 2, 0) => ((99, 50 + x_rem), Direction::Left),
 
-            (Facing::Left, 0, 2) => ((50, 49 - y_rem), Facing::Right),
-            (Facing::Left, 0, 3) => ((50 + y_rem, 0), Facing::Down),
-            (Facing::Left, 1, 0) => ((0, 149 - y_rem), Facing::Right),
-            (Facing::Left, 1, 1) => ((y_rem, 100), Facing::Down),
+            (Direction::Left, 0, 2) => ((50, 49 - y_rem), Direction::Right),
+            (Direction::Left, 0, 3) => ((50 + y_rem, 0), Direction::Down),
+            (Direction::Left, 1, 0) => ((0, 149 - y_rem), Direction::Right),
+            (Direction::Left, 1, 1) => ((y_rem, 100), Direction::Down),
 
-            (Facing::Up, 0, 2) => ((50, 50 + x_rem), Facing::Right),
-            (Facing::Up, 1, 0) => ((0, 150 + x_rem), Facing::Right),
-            (Facing::Up, 2, 0) => ((x_rem, 199), Facing::Up),
+            (Direction::Up, 0, 2) => ((50, 50 + x_rem), Direction::Right),
+            (Direction::Up, 1, 0) => ((0, 150 + x_rem), Direction::Right),
+            (Direction::Up, 2, 0) => ((x_rem, 199), Direction::Up),
 
             _ => unreachable!(),
         }
@@ -163,7 +145,7 @@ impl Wrapper for CubeWrap {
 struct Walker<W: Wrapper> {
     grid: Grid,
     position: (isize, isize),
-    facing: Facing,
+    facing: Direction,
     wrapper: W,
 }
 
@@ -180,7 +162,7 @@ impl<W: Wrapper> Walker<W> {
         Self {
             grid: grid.clone(),
             position,
-            facing: Facing::Right,
+            facing: Direction::Right,
             wrapper,
         }
     }
@@ -223,37 +205,18 @@ impl<W: Wrapper> Walker<W> {
     }
 
     fn next_position(&mut self) -> (isize, isize) {
-        match self.facing {
-            Facing::Right => (self.position.0 + 1, self.position.1),
-            Facing::Down => (self.position.0, self.position.1 + 1),
-            Facing::Left => (self.position.0 - 1, self.position.1),
-            Facing::Up => (self.position.0, self.position.1 - 1),
-        }
+        let (dx, dy) = self.facing.delta();
+        (self.position.0 + dx, self.position.1 + dy)
     }
 
     fn turn(&mut self, turn: &Turn) {
-        let new_facing = match turn {
-            Turn::Right => match self.facing {
-                Facing::Right => Facing::Down,
-                Facing::Down => Facing::Left,
-                Facing::Left => Facing::Up,
-                Facing::Up => Facing::Right,
-            },
-            Turn::Left => match self.facing {
-                Facing::Right => Facing::Up,
-                Facing::Down => Facing::Right,
-                Facing::Left => Facing::Down,
-                Facing::Up => Facing::Left,
-            },
-        };
-
-        self.facing = new_facing;
+        self.facing = self.facing.turn(*turn);
     }
 
     fn password(&self) -> usize {
         1000 * (self.position.1 as usize + 1)
             + 4 * (self.position.0 as usize + 1)
-            + (self.facing as usize)
+            + self.facing.facing_score()
     }
 }
 
@@ -273,12 +236,18 @@ fn part2(input: &Input) -> usize {
     walker.password()
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
@@ -1,239 +1,144 @@
-use std::{
-    collections::{HashMap, HashSet, VecDeque},
-    ops::Add,
-};
+use std::collections::HashMap;
+
+use aoc_common::animate::{colour_chars, play, Animation, Colour, Generations, Options};
+use aoc_common::automaton::{Automaton, Rule};
+use aoc_common::direction::Direction;
+use aoc_common::export::{self, record, Palette};
+use aoc_common::grid::{moore, offset, Point, SparseGrid};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
 
 const INPUT: &str = include_str!("../../inputs/day23.txt");
 
 #[derive(Clone, Debug)]
 struct Input {
-    elves: Vec<Elf>,
+    elves: SparseGrid,
 }
 
 impl Input {
     fn new(input: &str) -> Input {
-        let elves = input
-            .lines()
-            .enumerate()
-            .flat_map(|(y, line)| line.chars().enumerate().map(move |(x, c)| (c, (x, y))))
-            .filter(|(c, _)| *c == '#')
-            .map(|(_, (x, y))| Position {
-                x: x as isize,
-                y: -(y as isize),
-            })
-            .collect();
-
-        Input { elves }
-    }
-}
-
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
-struct Position {
-    x: isize,
-    y: isize,
-}
-
-impl Position {
-    fn new(x: isize, y: isize) -> Self {
-        Self { x, y }
-    }
-
-    fn neighbours(&self) -> [Position; 8] {
-        [
-            *self + Position::new(-1, -1),
-            *self + Position::new(-1, 0),
-            *self + Position::new(-1, 1),
-            *self + Position::new(0, -1),
-            *self + Position::new(0, 1),
-            *self + Position::new(1, -1),
-            *self + Position::new(1, 0),
-            *self + Position::new(1, 1),
-        ]
-    }
-}
-
-impl Add for Position {
-    type Output = Position;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        Position {
-            x: self.x + rhs.x,
-            y: self.y + rhs.y,
+        Input {
+            elves: SparseGrid::parse(input, '#'),
         }
     }
 }
 
-type Elf = Position;
-
-#[derive(Copy, Clone, Debug)]
-enum Direction {
-    South,
-    North,
-    East,
-    West,
-}
-
-impl Direction {
-    fn positions(&self) -> [Position; 3] {
-        match self {
-            Direction::North => [
-                Position::new(-1, 1),
-                Position::new(0, 1),
-                Position::new(1, 1),
-            ],
-            Direction::South => [
-                Position::new(-1, -1),
-                Position::new(0, -1),
-                Position::new(1, -1),
-            ],
-            Direction::East => [
-                Position::new(1, -1),
-                Position::new(1, 0),
-                Position::new(1, 1),
-            ],
-            Direction::West => [
-                Position::new(-1, -1),
-                Position::new(-1, 0),
-                Position::new(-1, 1),
-            ],
-        }
-    }
-
-    fn position(&self) -> Position {
-        match self {
-            Direction::North => Position::new(0, 1),
-            Direction::South => Position::new(0, -1),
-            Direction::East => Position::new(1, 0),
-            Direction::West => Position::new(-1, 0),
-        }
-    }
-}
-
-impl Elf {
-    fn positions_in_direction(&self, direction: &Direction) -> [Position; 3] {
-        direction.positions().map(|x| x + *self)
-    }
-
-    fn new_position(&self, direction: &Direction) -> Position {
-        *self + direction.position()
-    }
-}
+type Elf = Point;
 
+/// Each round the elves first propose a move, then make it if no other elf proposed the same one.
 #[derive(Clone, Debug)]
-struct Forest {
-    elves: Vec<Elf>,
-    set: Option<HashSet<Elf>>,
-    directions: VecDeque<Direction>,
-}
-
-impl Forest {
-    fn new(elves: &[Elf]) -> Self {
-        Self {
-            elves: elves.to_vec(),
-            set: None,
-            directions: VecDeque::from([
-                Direction::North,
-                Direction::South,
-                Direction::West,
-                Direction::East,
-            ]),
-        }
-    }
-
-    fn occupied_at(&self, p: &Position) -> bool {
-        self.set.as_ref().unwrap().contains(p)
-    }
-
-    fn should_move(&self, elf: &Elf, direction: &Direction) -> bool {
-        elf.positions_in_direction(direction)
+struct Spread;
+
+impl Spread {
+    /// The order the elves consider directions in rotates each round
+    fn directions(round: usize) -> impl Iterator<Item = Direction> {
+        let order = [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ];
+        (0..4).map(move |i| order[(round + i) % 4])
+    }
+
+    fn should_move(elves: &SparseGrid, elf: Elf, direction: Direction) -> bool {
+        direction
+            .with_diagonals()
             .iter()
-            .all(|p| !self.occupied_at(p))
+            .all(|c| !elves.contains(offset(elf, c.delta())))
     }
 
-    fn directions_queue(&mut self) -> VecDeque<Direction> {
-        let directions = self.directions.clone();
-        let direction = self.directions.pop_front().unwrap();
-        self.directions.push_back(direction);
-        directions
-    }
-
-    fn decide_action(&self, elf: &Elf, directions: &VecDeque<Direction>) -> Position {
-        let no_neighbours = elf.neighbours().iter().all(|p| !self.occupied_at(p));
+    fn decide_action(elves: &SparseGrid, elf: Elf, round: usize) -> Elf {
+        let no_neighbours = moore(elf).iter().all(|p| !elves.contains(*p));
         if no_neighbours {
-            return *elf;
+            return elf;
         }
 
-        for direction in directions {
-            if self.should_move(elf, direction) {
-                return elf.new_position(direction);
-            }
-        }
-        *elf
+        Self::directions(round)
+            .find(|direction| Self::should_move(elves, elf, *direction))
+            .map_or(elf, |direction| offset(elf, direction.delta()))
     }
+}
 
-    fn tick(&mut self) -> bool {
-        let directions = self.directions_queue();
-
-        // Collect the elves into a set to speed up checks
-        self.set = Some(self.elves.clone().into_iter().collect());
-
+impl Rule<SparseGrid> for Spread {
+    fn step(&self, round: usize, elves: &mut SparseGrid, next: &mut SparseGrid) -> bool {
         // Each elf considers their next move
-        let new_positions: Vec<Elf> = self
-            .elves
+        let proposals: Vec<(Elf, Elf)> = elves
             .iter()
-            .map(|elf| self.decide_action(elf, &directions))
+            .map(|elf| (elf, Self::decide_action(elves, elf, round)))
             .collect();
 
         // Count to find duplicates
-        let mut count: HashMap<&Position, usize> = HashMap::new();
-        for p in &new_positions {
-            *count.entry(p).or_default() += 1;
+        let mut count: HashMap<Elf, usize> = HashMap::new();
+        for (_, p) in &proposals {
+            *count.entry(*p).or_default() += 1;
         }
 
         let mut moved = false;
-        for (position, new_position) in self.elves.iter_mut().zip(&new_positions) {
-            if position != new_position && count[new_position] == 1 {
+        next.clear();
+        for (position, new_position) in proposals {
+            if position != new_position && count[&new_position] == 1 {
                 moved = true;
-                *position = *new_position;
+                next.insert(new_position);
+            } else {
+                next.insert(position);
             }
         }
+        std::mem::swap(elves, next);
         moved
     }
+}
 
-    fn empty_ground(&self) -> usize {
-        let n = self.elves.iter().map(|p| p.y).max().unwrap();
-        let s = self.elves.iter().map(|p| p.y).min().unwrap();
-        let w = self.elves.iter().map(|p| p.x).min().unwrap();
-        let e = self.elves.iter().map(|p| p.x).max().unwrap();
+type Forest = Automaton<SparseGrid, Spread>;
 
-        let total_tiles = (n - s + 1) * (e - w + 1);
-        total_tiles as usize - self.elves.len()
-    }
+fn empty_ground(elves: &SparseGrid) -> usize {
+    let ((w, n), (e, s)) = elves.bounds().unwrap();
+    let total_tiles = (s - n + 1) * (e - w + 1);
+    total_tiles as usize - elves.len()
 }
 
 fn part1(input: &Input) -> usize {
-    let mut forest = Forest::new(&input.elves);
-    for _ in 0..10 {
-        forest.tick();
-    }
-    forest.empty_ground()
+    let mut forest: Forest = Automaton::new(input.elves.clone(), Spread);
+    empty_ground(forest.run(10))
 }
 
 fn part2(input: &Input) -> usize {
-    let mut round = 1;
-    let mut forest = Forest::new(&input.elves);
-    while forest.tick() {
-        round += 1;
-    }
-    round
+    let mut forest: Forest = Automaton::new(input.elves.clone(), Spread);
+    forest.run_until_stable()
+}
+
+const PALETTE: &Palette = &[('#', Colour::Green)];
+
+fn spreading(input: Input) -> impl Animation {
+    let forest: Forest = Automaton::new(input.elves, Spread);
+    Generations::new(forest, |elves: &SparseGrid| {
+        colour_chars(&elves.render('#', '.'), |c| {
+            (c == '#').then_some(Colour::Green)
+        })
+    })
+}
+
+pub fn animate(options: &Options) {
+    play(&mut spreading(Input::new(INPUT)), options);
+}
+
+pub fn export(options: &export::Options) {
+    let mut forest = spreading(Input::new(INPUT));
+    record(&mut forest, Animation::frame, PALETTE, options);
+}
+
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
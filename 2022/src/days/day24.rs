@@ -1,4 +1,12 @@
-use std::{collections::HashSet, ops::Add};
+use std::{collections::HashSet, fmt, ops::Add};
+
+use aoc_common::animate::{colour_chars, play, Animation, Colour, Options};
+use aoc_common::automaton::{Automaton, CellRule, Synchronous};
+use aoc_common::direction::Direction;
+use aoc_common::export::{self, record, Palette};
+use aoc_common::grid::{self, offset};
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
 
 const INPUT: &str = include_str!("../../inputs/day24.txt");
 
@@ -11,23 +19,20 @@ impl Input {
     fn new(input: &str) -> Input {
         let height = input.lines().count();
         let width = input.lines().next().unwrap().len();
-        let blizzards = input
+        // Only the inside of the valley can contain blizzards
+        let rows = input
             .lines()
-            .enumerate()
-            .flat_map(|(y, line)| line.chars().enumerate().map(move |(x, c)| (c, (x, y))))
-            .filter(|(c, _)| *c != '#' && *c != '.')
-            .map(|(c, (x, y))| {
-                let direction = match c {
-                    '>' => Direction::Right,
-                    '<' => Direction::Left,
-                    '^' => Direction::Up,
-                    'v' => Direction::Down,
-                    _ => unreachable!(),
-                };
-                Blizzard {
-                    position: Point::new(x, y),
-                    direction,
-                }
+            .skip(1)
+            .take(height - 2)
+            .map(|line| {
+                line.chars()
+                    .skip(1)
+                    .take(width - 2)
+                    .map(|c| match c {
+                        '.' => 0,
+                        _ => bit(Direction::try_from(c).unwrap()),
+                    })
+                    .collect()
             })
             .collect();
 
@@ -35,17 +40,44 @@ impl Input {
             grid: Grid {
                 width,
                 height,
-                blizzards,
+                blizzards: Automaton::new(grid::Grid::from_rows(rows, 0), Synchronous(Blow)),
             },
         }
     }
 }
 
+/// Each cell inside the valley holds one bit for every direction of blizzard in it
+type Blizzards = grid::Grid<u8>;
+
+fn bit(direction: Direction) -> u8 {
+    1 << direction as u8
+}
+
+#[derive(Clone, Debug)]
+struct Blow;
+
+impl CellRule<u8> for Blow {
+    fn next(&self, blizzards: &Blizzards, p: grid::Point) -> u8 {
+        // Blizzards wrap around to the other side of the valley
+        Direction::ALL
+            .into_iter()
+            .filter(|d| {
+                let (dx, dy) = d.delta();
+                blizzards.get_wrapped(offset(p, (-dx, -dy))) & bit(*d) != 0
+            })
+            .fold(0, |cell, d| cell | bit(d))
+    }
+
+    fn next_background(&self, background: &u8) -> u8 {
+        *background
+    }
+}
+
 #[derive(Clone, Debug)]
 struct Grid {
     width: usize,
     height: usize,
-    blizzards: Vec<Blizzard>,
+    blizzards: Automaton<Blizzards, Synchronous<Blow>>,
 }
 
 impl Grid {
@@ -56,14 +88,7 @@ impl Grid {
     }
 
     fn update_blizzards(&mut self) {
-        for blizzard in self.blizzards.iter_mut() {
-            move_point(
-                &mut blizzard.position,
-                &blizzard.direction,
-                self.width,
-                self.height,
-            );
-        }
+        self.blizzards.step();
     }
 
     fn is_in_grid(&self, p: &Point) -> bool {
@@ -74,22 +99,29 @@ impl Grid {
             || (p.x == 1 && p.y == 0)
     }
 
-    fn update_player(
-        &self,
-        positions: HashSet<Point>,
-        hurricanes: HashSet<Point>,
-    ) -> HashSet<Point> {
+    fn has_blizzard(&self, p: &Point) -> bool {
+        // The starting and ending points are outside the valley
+        let inside = (p.x > 0 && p.y > 0) && (p.x < self.width - 1 && p.y < self.height - 1);
+        inside
+            && *self
+                .blizzards
+                .world()
+                .get((p.x as isize - 1, p.y as isize - 1))
+                != 0
+    }
+
+    fn update_player(&self, positions: HashSet<Point>) -> HashSet<Point> {
         let mut new = HashSet::new();
 
         for p in positions {
             // We can wait here
-            if !hurricanes.contains(&p) {
+            if !self.has_blizzard(&p) {
                 new.insert(p);
             }
 
             // We can move to here
             for n in self.neighbours(&p) {
-                if !hurricanes.contains(&n) {
+                if !self.has_blizzard(&n) {
                     new.insert(n);
                 }
             }
@@ -99,39 +131,96 @@ impl Grid {
     }
 }
 
-fn move_point(p: &mut Point, d: &Direction, width: usize, height: usize) {
-    match d {
-        Direction::Up => {
-            if p.y == 1 {
-                p.y = height - 2;
-            } else {
-                p.y -= 1;
-            }
-        }
-        Direction::Down => {
-            if p.y == height - 2 {
-                p.y = 1;
-            } else {
-                p.y += 1;
-            }
-        }
-        Direction::Left => {
-            if p.x == 1 {
-                p.x = width - 2;
-            } else {
-                p.x -= 1;
+impl Grid {
+    /// Draws the valley, with `E` wherever the expedition could be.
+    fn render(&self, positions: &HashSet<Point>) -> String {
+        let mut output = String::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let p = Point::new(x, y);
+                let c = if positions.contains(&p) {
+                    'E'
+                } else if !self.is_in_grid(&p) {
+                    '#'
+                } else if !self.has_blizzard(&p) {
+                    '.'
+                } else {
+                    let cell = *self.blizzards.world().get((x as isize - 1, y as isize - 1));
+                    match cell.count_ones() {
+                        1 => ['^', '>', 'v', '<'][cell.trailing_zeros() as usize],
+                        n => char::from_digit(n, 10).unwrap(),
+                    }
+                };
+                output.push(c);
             }
+            output.push('\n');
         }
-        Direction::Right => {
-            if p.x == width - 2 {
-                p.x = 1;
-            } else {
-                p.x += 1;
-            }
+        output
+    }
+}
+
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render(&HashSet::new()))
+    }
+}
+
+struct Walk {
+    grid: Grid,
+    positions: HashSet<Point>,
+    end: Point,
+}
+
+impl Walk {
+    fn new(grid: Grid) -> Self {
+        let end = Point::new(grid.width - 2, grid.height - 1);
+        Self {
+            grid,
+            positions: HashSet::from([Point::new(1, 0)]),
+            end,
         }
     }
 }
 
+impl Animation for Walk {
+    fn step(&mut self) -> bool {
+        self.grid.update_blizzards();
+        self.positions = self.grid.update_player(std::mem::take(&mut self.positions));
+        !self.positions.contains(&self.end)
+    }
+
+    fn frame(&self) -> String {
+        colour_chars(&self.grid.render(&self.positions), |c| match c {
+            '#' => Some(Colour::Grey),
+            'E' => Some(Colour::Yellow),
+            '.' => None,
+            _ => Some(Colour::Cyan),
+        })
+    }
+}
+
+const PALETTE: &Palette = &[
+    ('#', Colour::Grey),
+    ('E', Colour::Yellow),
+    ('^', Colour::Cyan),
+    ('>', Colour::Cyan),
+    ('v', Colour::Cyan),
+    ('<', Colour::Cyan),
+    ('2', Colour::Cyan),
+    ('3', Colour::Cyan),
+    ('4', Colour::Cyan),
+];
+
+pub fn animate(options: &Options) {
+    play(&mut Walk::new(Input::new(INPUT).grid), options);
+}
+
+pub fn export(options: &export::Options) {
+    let mut walk = Walk::new(Input::new(INPUT).grid);
+    let picture = |walk: &Walk| walk.grid.render(&walk.positions);
+    record(&mut walk, picture, PALETTE, options);
+}
+
 fn neighbouring_points(x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
     // Need signed integers for the bounds checks
     let x = x as isize;
@@ -143,12 +232,6 @@ fn neighbouring_points(x: usize, y: usize) -> impl Iterator<Item = (usize, usize
         .map(|(a, b)| (a as usize, b as usize))
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
-struct Blizzard {
-    position: Point,
-    direction: Direction,
-}
-
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 struct Point {
     x: usize,
@@ -172,14 +255,6 @@ impl Add for Point {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-
 struct Explorer {
     grid: Grid,
 }
@@ -197,8 +272,7 @@ impl Explorer {
 
         while !positions.contains(&end) {
             self.grid.update_blizzards();
-            let blizzards = self.grid.blizzards.iter().map(|b| b.position).collect();
-            positions = self.grid.update_player(positions, blizzards);
+            positions = self.grid.update_player(positions);
             minutes += 1;
         }
 
@@ -223,12 +297,18 @@ fn part2(input: &Input) -> usize {
     first + second + third
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
-    let answer2 = part2(&input);
-    println!("Part 2: {}", answer2);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+    run.part(2, || part2(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input)
+        .part(1, || part1(&input))
+        .part(2, || part2(&input))
+        .run();
 }
 
 #[cfg(test)]
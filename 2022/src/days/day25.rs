@@ -1,5 +1,8 @@
 use std::collections::VecDeque;
 
+use aoc_common::repl::{self, Session};
+use aoc_common::runner::Run;
+
 const INPUT: &str = include_str!("../../inputs/day25.txt");
 
 #[derive(Clone, Debug)]
@@ -105,10 +108,14 @@ fn part1(input: &Input) -> String {
     snafu
 }
 
-pub fn main() {
-    let input = Input::new(INPUT);
-    let answer1 = part1(&input);
-    println!("Part 1: {}", answer1);
+pub fn main(run: &mut Run) {
+    let input = run.parse(|| Input::new(INPUT));
+    run.part(1, || part1(&input));
+}
+
+pub fn explore(options: &repl::Options) {
+    let input = Input::new(options.input(INPUT));
+    Session::new(&input).part(1, || part1(&input)).run();
 }
 
 #[cfg(test)]
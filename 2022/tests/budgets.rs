@@ -0,0 +1,40 @@
+use std::process::{Command, Output};
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_aoc2022"))
+        .args(args)
+        .output()
+        .expect("Failed to run the program")
+}
+
+// The days take turns in this one process unless the parallel feature is on, and a part's time
+// only starts when it does, so a slow day doesn't eat into the next one's budget. With the feature
+// on, a day that goes over while the others are running is run again alone before it counts.
+#[test]
+fn within_budget() {
+    let output = run(&["all", "--format", "csv"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // Day 25 has a single part, and every other day two
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let records = stdout.lines().filter(|line| line.starts_with("2022,"));
+    assert_eq!(records.count(), 49);
+}
+
+// Flooding the space around day 18's droplet takes a good deal longer than a millisecond, even
+// built with optimisations
+#[test]
+fn over_time_budget() {
+    let output = run(&["18", "--time-budget", "1ms"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("2022 day 18 part 2 is over budget: took"),
+        "{}",
+        stderr
+    );
+}
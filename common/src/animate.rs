@@ -0,0 +1,365 @@
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use crate::automaton::{Automaton, Rule};
+
+// Everything is drawn with plain ANSI escape codes, so no terminal library is needed.
+// Controls are read a line at a time, so each command is followed by Enter.
+
+const CLEAR: &str = "\x1b[H\x1b[2J";
+const HELP: &str = "Enter: step, p: play/pause, +/-: speed, q: quit";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Colour {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    Grey,
+}
+
+impl Colour {
+    fn code(self) -> u8 {
+        match self {
+            Colour::Red => 31,
+            Colour::Green => 32,
+            Colour::Yellow => 33,
+            Colour::Blue => 34,
+            Colour::Magenta => 35,
+            Colour::Cyan => 36,
+            Colour::White => 97,
+            Colour::Grey => 90,
+        }
+    }
+
+    /// Roughly what the colour looks like in a terminal, for drawing images.
+    pub fn rgb(self) -> [u8; 3] {
+        match self {
+            Colour::Red => [205, 49, 49],
+            Colour::Green => [13, 188, 121],
+            Colour::Yellow => [229, 229, 16],
+            Colour::Blue => [36, 114, 200],
+            Colour::Magenta => [188, 63, 188],
+            Colour::Cyan => [17, 168, 205],
+            Colour::White => [229, 229, 229],
+            Colour::Grey => [102, 102, 102],
+        }
+    }
+
+    pub fn paint(self, text: impl fmt::Display) -> String {
+        format!("\x1b[{}m{}\x1b[0m", self.code(), text)
+    }
+}
+
+/// Colours each character of some text, such as the output of a `Display` impl.
+/// Characters given no colour are left as they are.
+pub fn colour_chars(text: &str, palette: impl Fn(char) -> Option<Colour>) -> String {
+    text.chars()
+        .map(|c| match palette(c) {
+            Some(colour) => colour.paint(c),
+            None => c.to_string(),
+        })
+        .collect()
+}
+
+/// A simulation that can be drawn between steps.
+pub trait Animation {
+    /// Advances the simulation, returning false if it has already finished.
+    fn step(&mut self) -> bool;
+
+    /// Draws the current state, which may include colour codes.
+    fn frame(&self) -> String;
+}
+
+/// Animates an automaton one generation at a time, until it stops changing.
+pub struct Generations<W, R, F> {
+    automaton: Automaton<W, R>,
+    draw: F,
+    limit: Option<usize>,
+}
+
+impl<W, R, F> Generations<W, R, F>
+where
+    W: Clone,
+    R: Rule<W>,
+    F: Fn(&W) -> String,
+{
+    pub fn new(automaton: Automaton<W, R>, draw: F) -> Self {
+        Self {
+            automaton,
+            draw,
+            limit: None,
+        }
+    }
+
+    /// Stops after this many generations, for worlds that never settle down.
+    pub fn with_limit(self, limit: usize) -> Self {
+        Self {
+            limit: Some(limit),
+            ..self
+        }
+    }
+}
+
+impl<W, R, F> Animation for Generations<W, R, F>
+where
+    W: Clone,
+    R: Rule<W>,
+    F: Fn(&W) -> String,
+{
+    fn step(&mut self) -> bool {
+        if self.limit == Some(self.automaton.generation()) {
+            return false;
+        }
+        self.automaton.step()
+    }
+
+    fn frame(&self) -> String {
+        (self.draw)(self.automaton.world())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Options {
+    pub fps: f64,
+    /// Start paused, so the first frames can be stepped through one at a time.
+    pub paused: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            fps: 10.0,
+            paused: false,
+        }
+    }
+}
+
+impl Options {
+    /// Reads `--animate [--fps N] [--paused]` from the command line arguments.
+    /// Returns `None` if animation wasn't asked for.
+    pub fn from_args(args: &[String]) -> Option<Self> {
+        if !args.iter().any(|arg| arg == "--animate") {
+            return None;
+        }
+
+        let mut options = Options::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--fps" => {
+                    options.fps = args
+                        .next()
+                        .and_then(|fps| fps.parse().ok())
+                        .filter(|fps: &f64| *fps > 0.0)
+                        .expect("--fps must be followed by a positive number")
+                }
+                "--paused" => options.paused = true,
+                _ => {}
+            }
+        }
+        Some(options)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Command {
+    Step,
+    TogglePause,
+    Faster,
+    Slower,
+    Quit,
+}
+
+impl Command {
+    fn parse(line: &str) -> Option<Self> {
+        match line.trim() {
+            "" | "n" => Some(Command::Step),
+            "p" => Some(Command::TogglePause),
+            "+" => Some(Command::Faster),
+            "-" => Some(Command::Slower),
+            "q" => Some(Command::Quit),
+            _ => None,
+        }
+    }
+}
+
+fn read_commands() -> Receiver<Command> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if let Some(command) = Command::parse(&line) {
+                if sender.send(command).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+    receiver
+}
+
+/// Plays an animation in the terminal until it finishes or is quit.
+pub fn play(animation: &mut impl Animation, options: &Options) {
+    run(
+        animation,
+        options,
+        Some(read_commands()),
+        &mut io::stdout().lock(),
+    )
+    .expect("Failed to write to the terminal");
+}
+
+fn run(
+    animation: &mut impl Animation,
+    options: &Options,
+    mut commands: Option<Receiver<Command>>,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let mut fps = options.fps;
+    let mut paused = options.paused;
+    let mut steps = 0;
+
+    loop {
+        let status = if paused { "paused" } else { "playing" };
+        write!(out, "{}{}", CLEAR, animation.frame())?;
+        writeln!(out, "step {} | {} | {} fps | {}", steps, status, fps, HELP)?;
+        out.flush()?;
+
+        let delay = Duration::from_secs_f64(1.0 / fps);
+        let received = match &commands {
+            None => {
+                thread::sleep(delay);
+                Err(RecvTimeoutError::Timeout)
+            }
+            Some(receiver) if paused => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            Some(receiver) => receiver.recv_timeout(delay),
+        };
+        let command = match received {
+            Ok(command) => Some(command),
+            Err(RecvTimeoutError::Timeout) => None,
+            // Without any way to receive commands, there is no way to unpause
+            Err(RecvTimeoutError::Disconnected) => {
+                commands = None;
+                paused = false;
+                None
+            }
+        };
+
+        match command {
+            Some(Command::Quit) => return Ok(()),
+            Some(Command::TogglePause) => {
+                paused = !paused;
+                continue;
+            }
+            Some(Command::Faster) => {
+                fps *= 2.0;
+                continue;
+            }
+            Some(Command::Slower) => {
+                fps /= 2.0;
+                continue;
+            }
+            // Stepping while playing pauses first, so the next step can be looked at
+            Some(Command::Step) => paused = true,
+            None if paused => continue,
+            None => {}
+        }
+
+        if !animation.step() {
+            write!(out, "{}{}", CLEAR, animation.frame())?;
+            writeln!(out, "finished after {} steps", steps)?;
+            return Ok(());
+        }
+        steps += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Countdown(usize);
+
+    impl Animation for Countdown {
+        fn step(&mut self) -> bool {
+            if self.0 == 0 {
+                return false;
+            }
+            self.0 -= 1;
+            true
+        }
+
+        fn frame(&self) -> String {
+            format!("{}\n", Colour::Green.paint(self.0))
+        }
+    }
+
+    fn args(args: &str) -> Vec<String> {
+        args.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn options() {
+        assert_eq!(Options::from_args(&args("14")), None);
+        assert_eq!(
+            Options::from_args(&args("14 --animate")),
+            Some(Options::default())
+        );
+        assert_eq!(
+            Options::from_args(&args("14 --animate --fps 2.5 --paused")),
+            Some(Options {
+                fps: 2.5,
+                paused: true
+            })
+        );
+    }
+
+    #[test]
+    fn colours() {
+        assert_eq!(Colour::Red.paint('#'), "\x1b[31m#\x1b[0m");
+        assert_eq!(
+            colour_chars("#.", |c| (c == '#').then_some(Colour::Grey)),
+            "\x1b[90m#\x1b[0m."
+        );
+    }
+
+    #[test]
+    fn commands() {
+        assert_eq!(Command::parse(""), Some(Command::Step));
+        assert_eq!(Command::parse(" p "), Some(Command::TogglePause));
+        assert_eq!(Command::parse("x"), None);
+    }
+
+    #[test]
+    fn playing() {
+        let options = Options {
+            fps: 10_000.0,
+            paused: false,
+        };
+        let mut out = Vec::new();
+        run(&mut Countdown(3), &options, None, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.matches(CLEAR).count(), 5);
+        assert!(out.ends_with("finished after 3 steps\n"));
+
+        // Paused, stepping twice and then quitting
+        let (sender, receiver) = mpsc::channel();
+        for command in [Command::Step, Command::Step, Command::Quit] {
+            sender.send(command).unwrap();
+        }
+        let options = Options {
+            paused: true,
+            ..options
+        };
+        let mut countdown = Countdown(5);
+        run(&mut countdown, &options, Some(receiver), &mut Vec::new()).unwrap();
+        assert_eq!(countdown.0, 3);
+    }
+}
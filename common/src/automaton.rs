@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::cycle::Cycle;
+use crate::grid::{moore, Grid, Point, SparseGrid};
+use crate::parallel;
+
+/// Worlds with at least this many cells are updated in parallel, when the `parallel` feature is on.
+pub const PARALLEL_THRESHOLD: usize = 1 << 14;
+
+/// Advances a world by one generation.
+pub trait Rule<W> {
+    /// `scratch` is a second buffer that double-buffered rules write into before swapping it
+    /// with `world`; its previous contents are meaningless. Returns whether anything changed.
+    fn step(&self, generation: usize, world: &mut W, scratch: &mut W) -> bool;
+}
+
+/// A rule that computes each cell of a dense grid from the previous generation.
+pub trait CellRule<T>: Sync {
+    fn next(&self, grid: &Grid<T>, p: Point) -> T;
+
+    /// How far the grid grows in every direction each generation.
+    /// Worlds on an infinite background need this to be non-zero.
+    fn margin(&self) -> usize {
+        0
+    }
+
+    /// What the infinite background becomes after a generation.
+    fn next_background(&self, background: &T) -> T;
+}
+
+/// A rule that decides whether each point of a sparse grid is live in the next generation.
+pub trait SparseRule: Sync {
+    fn alive(&self, grid: &SparseGrid, p: Point) -> bool;
+
+    /// The points that might become live because of a live point.
+    fn neighbourhood(&self, p: Point) -> Vec<Point> {
+        moore(p).to_vec()
+    }
+}
+
+/// Applies a cell rule to every cell at once.
+#[derive(Clone, Copy, Debug)]
+pub struct Synchronous<R>(pub R);
+
+impl<T, R> Rule<Grid<T>> for Synchronous<R>
+where
+    T: Clone + PartialEq + Send + Sync,
+    R: CellRule<T>,
+{
+    fn step(&self, _generation: usize, world: &mut Grid<T>, scratch: &mut Grid<T>) -> bool {
+        let margin = self.0.margin() as isize;
+        let (min, max) = world.bounds();
+        let min = (min.0 - margin, min.1 - margin);
+        let max = (max.0 + margin, max.1 + margin);
+        let background = self.0.next_background(world.background());
+
+        let size = ((max.0 - min.0) * (max.1 - min.1)) as usize;
+        if parallel::ENABLED && size >= PARALLEL_THRESHOLD {
+            let width = max.0 - min.0;
+            let cells = parallel::map(0..size as isize, |i| {
+                self.0.next(world, (min.0 + i % width, min.1 + i / width))
+            });
+            let mut cells = cells.into_iter();
+            scratch.refill(min, max, background, |_| cells.next().unwrap());
+        } else {
+            scratch.refill(min, max, background, |p| self.0.next(world, p));
+        }
+
+        let changed = world != scratch;
+        std::mem::swap(world, scratch);
+        changed
+    }
+}
+
+impl<R: SparseRule> Rule<SparseGrid> for Synchronous<R> {
+    fn step(&self, _generation: usize, world: &mut SparseGrid, scratch: &mut SparseGrid) -> bool {
+        let mut candidates: Vec<Point> = world
+            .iter()
+            .flat_map(|p| self.0.neighbourhood(p).into_iter().chain([p]))
+            .collect();
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        scratch.clear();
+        if parallel::ENABLED && candidates.len() >= PARALLEL_THRESHOLD {
+            let live = parallel::filter_map(candidates, |p| self.0.alive(world, p).then_some(p));
+            scratch.extend(live);
+        } else {
+            scratch.extend(candidates.into_iter().filter(|p| self.0.alive(world, *p)));
+        }
+
+        let changed = world != scratch;
+        std::mem::swap(world, scratch);
+        changed
+    }
+}
+
+/// Repeats a rule within a single generation until it stops changing anything.
+#[derive(Clone, Copy, Debug)]
+pub struct UntilStable<R>(pub R);
+
+impl<W, R: Rule<W>> Rule<W> for UntilStable<R> {
+    fn step(&self, generation: usize, world: &mut W, scratch: &mut W) -> bool {
+        let mut changed = false;
+        while self.0.step(generation, world, scratch) {
+            changed = true;
+        }
+        changed
+    }
+}
+
+// Tuples of rules run one after another as phases of the same generation
+
+impl<W, A: Rule<W>, B: Rule<W>> Rule<W> for (A, B) {
+    fn step(&self, generation: usize, world: &mut W, scratch: &mut W) -> bool {
+        let a = self.0.step(generation, world, scratch);
+        let b = self.1.step(generation, world, scratch);
+        a || b
+    }
+}
+
+impl<W, A: Rule<W>, B: Rule<W>, C: Rule<W>> Rule<W> for (A, B, C) {
+    fn step(&self, generation: usize, world: &mut W, scratch: &mut W) -> bool {
+        let a = self.0.step(generation, world, scratch);
+        let b = self.1.step(generation, world, scratch);
+        let c = self.2.step(generation, world, scratch);
+        a || b || c
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Automaton<W, R> {
+    world: W,
+    scratch: W,
+    rule: R,
+    generation: usize,
+}
+
+impl<W: Clone, R: Rule<W>> Automaton<W, R> {
+    pub fn new(world: W, rule: R) -> Self {
+        Self {
+            scratch: world.clone(),
+            world,
+            rule,
+            generation: 0,
+        }
+    }
+
+    pub fn world(&self) -> &W {
+        &self.world
+    }
+
+    pub fn into_world(self) -> W {
+        self.world
+    }
+
+    /// The number of generations run so far.
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Runs a single generation, returning whether anything changed.
+    pub fn step(&mut self) -> bool {
+        let changed = self
+            .rule
+            .step(self.generation, &mut self.world, &mut self.scratch);
+        self.generation += 1;
+        changed
+    }
+
+    pub fn run(&mut self, generations: usize) -> &W {
+        for _ in 0..generations {
+            self.step();
+        }
+        &self.world
+    }
+
+    /// Runs until the world satisfies `done` after a step, returning the generation reached.
+    pub fn run_until(&mut self, mut done: impl FnMut(&W) -> bool) -> usize {
+        loop {
+            self.step();
+            if done(&self.world) {
+                return self.generation;
+            }
+        }
+    }
+
+    /// Runs until a generation changes nothing, returning that generation's number.
+    pub fn run_until_stable(&mut self) -> usize {
+        while self.step() {}
+        self.generation
+    }
+
+    /// Runs until a world repeats. A fixed point is reported as a cycle of length one.
+    pub fn find_cycle(&mut self) -> Cycle
+    where
+        W: Hash + Eq,
+    {
+        let mut seen = HashMap::new();
+        loop {
+            if let Some(start) = seen.insert(self.world.clone(), self.generation) {
+                return Cycle {
+                    start,
+                    length: self.generation - start,
+                };
+            }
+            self.step();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Life;
+
+    impl CellRule<bool> for Life {
+        fn next(&self, grid: &Grid<bool>, p: Point) -> bool {
+            let neighbours = moore(p).iter().filter(|n| *grid.get(**n)).count();
+            matches!((grid.get(p), neighbours), (true, 2) | (_, 3))
+        }
+
+        fn next_background(&self, _background: &bool) -> bool {
+            false
+        }
+    }
+
+    impl SparseRule for Life {
+        fn alive(&self, grid: &SparseGrid, p: Point) -> bool {
+            let neighbours = moore(p).iter().filter(|n| grid.contains(**n)).count();
+            matches!((grid.contains(p), neighbours), (true, 2) | (_, 3))
+        }
+    }
+
+    const BLINKER: &str = ".....\n..#..\n..#..\n..#..\n.....\n";
+
+    #[test]
+    fn dense() {
+        let grid = Grid::parse(BLINKER, false, |c| c == '#');
+        let mut automaton = Automaton::new(grid.clone(), Synchronous(Life));
+        automaton.step();
+        assert_eq!(
+            automaton.world().render(|c| if *c { '#' } else { '.' }),
+            ".....\n.....\n.###.\n.....\n.....\n"
+        );
+        assert_eq!(
+            automaton.find_cycle(),
+            Cycle {
+                start: 1,
+                length: 2
+            }
+        );
+
+        // A block is a still life
+        let block = Grid::parse("....\n.##.\n.##.\n....\n", false, |c| c == '#');
+        assert_eq!(
+            Automaton::new(block, Synchronous(Life)).run_until_stable(),
+            1
+        );
+    }
+
+    #[test]
+    fn sparse() {
+        let grid = SparseGrid::parse(BLINKER, '#');
+        let mut automaton = Automaton::new(grid, Synchronous(Life));
+        automaton.step();
+        assert!(automaton.world().contains((1, 2)));
+        assert!(!automaton.world().contains((2, 1)));
+        assert_eq!(
+            automaton.find_cycle(),
+            Cycle {
+                start: 1,
+                length: 2
+            }
+        );
+    }
+
+    #[test]
+    fn parallel() {
+        // Large enough to take the parallel path: a glider crossing an empty board
+        let mut grid = Grid::new(200, 200, false);
+        for p in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+            grid.set(p, true);
+        }
+        let mut automaton = Automaton::new(grid, Synchronous(Life));
+        automaton.run(4);
+        assert_eq!(automaton.world().count(|c| *c), 5);
+        assert!(automaton.world().get((2, 1)) & automaton.world().get((3, 2)));
+    }
+
+    #[test]
+    fn phases() {
+        struct Increment;
+
+        impl CellRule<u8> for Increment {
+            fn next(&self, grid: &Grid<u8>, p: Point) -> u8 {
+                (grid.get(p) + 1).min(3)
+            }
+
+            fn next_background(&self, background: &u8) -> u8 {
+                *background
+            }
+        }
+
+        let grid = Grid::new(2, 2, 0);
+        let mut automaton = Automaton::new(grid, (Synchronous(Increment), Synchronous(Increment)));
+        automaton.step();
+        assert_eq!(automaton.world().count(|c| *c == 2), 4);
+        assert_eq!(automaton.run_until_stable(), 3);
+
+        let grid = Grid::new(2, 2, 0);
+        let mut automaton = Automaton::new(grid, UntilStable(Synchronous(Increment)));
+        automaton.step();
+        assert_eq!(automaton.world().count(|c| *c == 3), 4);
+    }
+}
@@ -0,0 +1,182 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::runner::{bytes, Record};
+
+// Budgets are checked once a part has finished, so a slow part still runs to the end, but the
+// run as a whole fails and says which part went over.
+
+/// Unoptimised builds, such as the ones `cargo test` makes, are about ten times slower, so they
+/// get ten times as long.
+pub const SLOWDOWN: u32 = if cfg!(debug_assertions) { 10 } else { 1 };
+
+pub const KIB: usize = 1024;
+pub const MIB: usize = 1024 * KIB;
+pub const GIB: usize = 1024 * MIB;
+
+/// The most each part of a day may take.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Budget {
+    pub time: Duration,
+    /// The most allocated at once, on top of whatever was allocated before the part started.
+    pub memory: usize,
+}
+
+impl Budget {
+    pub const fn new(time: Duration, memory: usize) -> Self {
+        Self { time, memory }
+    }
+
+    /// Says what a part went over, if anything.
+    pub fn check(&self, record: &Record) -> Option<Overrun> {
+        let budget = Budget {
+            time: self.time * SLOWDOWN,
+            ..*self
+        };
+        let slow = record.solve_time > budget.time;
+        let greedy = record.peak_memory > budget.memory;
+        (slow || greedy).then(|| Overrun {
+            record: record.clone(),
+            budget,
+            slow,
+            greedy,
+        })
+    }
+}
+
+/// A budget for every day, and bigger ones for the days that need them.
+#[derive(Copy, Clone, Debug)]
+pub struct Budgets {
+    pub default: Budget,
+    pub days: &'static [(usize, Budget)],
+}
+
+impl Budgets {
+    pub fn get(&self, day: usize) -> Budget {
+        self.days
+            .iter()
+            .find(|(d, _)| *d == day)
+            .map_or(self.default, |(_, budget)| *budget)
+    }
+}
+
+/// A part that took longer or allocated more than its budget.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Overrun {
+    pub record: Record,
+    pub budget: Budget,
+    pub slow: bool,
+    pub greedy: bool,
+}
+
+impl fmt::Display for Overrun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let record = &self.record;
+        write!(
+            f,
+            "{} day {} part {} is over budget:",
+            record.year, record.day, record.part
+        )?;
+        if self.slow {
+            write!(
+                f,
+                " took {:.2?} of {:.2?}",
+                record.solve_time, self.budget.time
+            )?;
+        }
+        if self.slow && self.greedy {
+            write!(f, ",")?;
+        }
+        if self.greedy {
+            write!(
+                f,
+                " allocated {} of {}",
+                bytes(record.peak_memory),
+                bytes(self.budget.memory)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads a time such as `500ms`, `2s` or `1.5s`.
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let (number, scale) = if let Some(ms) = s.strip_suffix("ms") {
+        (ms, 0.001)
+    } else {
+        (s.strip_suffix('s')?, 1.0)
+    };
+    let seconds: f64 = number.parse().ok()?;
+    (seconds >= 0.0).then(|| Duration::from_secs_f64(seconds * scale))
+}
+
+/// Reads an amount of memory such as `512KiB`, `64MiB` or `1GiB`, or a plain number of bytes.
+pub fn parse_bytes(s: &str) -> Option<usize> {
+    let (number, unit) = [("GiB", GIB), ("MiB", MIB), ("KiB", KIB), ("B", 1)]
+        .into_iter()
+        .find_map(|(suffix, unit)| Some((s.strip_suffix(suffix)?, unit)))
+        .unwrap_or((s, 1));
+    let amount: f64 = number.parse().ok()?;
+    (amount >= 0.0).then_some((amount * unit as f64) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUDGETS: Budgets = Budgets {
+        default: Budget::new(Duration::from_secs(1), 64 * MIB),
+        days: &[(16, Budget::new(Duration::from_secs(10), 64 * MIB))],
+    };
+
+    fn record(solve_time: Duration, peak_memory: usize) -> Record {
+        Record {
+            year: 2022,
+            day: 16,
+            part: 2,
+            answer: "2999".to_string(),
+            parse_time: Duration::ZERO,
+            solve_time,
+            peak_memory,
+        }
+    }
+
+    #[test]
+    fn checking() {
+        let budget = BUDGETS.get(16);
+        assert_eq!(budget.time, Duration::from_secs(10));
+        assert_eq!(BUDGETS.get(1).time, Duration::from_secs(1));
+
+        assert_eq!(budget.check(&record(Duration::from_secs(2), MIB)), None);
+        let overrun = budget
+            .check(&record(Duration::from_secs(2), 100 * MIB))
+            .unwrap();
+        assert_eq!(
+            overrun.to_string(),
+            "2022 day 16 part 2 is over budget: allocated 100.0 MiB of 64.0 MiB"
+        );
+
+        let slow = Duration::from_secs(12) * SLOWDOWN;
+        let overrun = budget.check(&record(slow, 100 * MIB)).unwrap();
+        assert_eq!(
+            overrun.to_string(),
+            format!(
+                "2022 day 16 part 2 is over budget: took {:.2?} of {:.2?}, allocated 100.0 MiB of 64.0 MiB",
+                slow,
+                budget.time * SLOWDOWN
+            )
+        );
+        assert_eq!(budget.check(&record(budget.time * SLOWDOWN, MIB)), None);
+    }
+
+    #[test]
+    fn parsing() {
+        assert_eq!(parse_duration("500ms"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_duration("1.5s"), Some(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("5"), None);
+        assert_eq!(parse_bytes("64MiB"), Some(64 * MIB));
+        assert_eq!(parse_bytes("1.5KiB"), Some(1536));
+        assert_eq!(parse_bytes("100"), Some(100));
+        assert_eq!(parse_bytes("lots"), None);
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::{Add, Mul, Sub};
+
+// All of these assume the sequence of states does eventually repeat, and will loop forever if not.
+// States are compared by a key, which can ignore details that don't affect what happens next.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    /// The first step that is part of the cycle.
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    /// The step within the first pass through the cycle that matches step `n`.
+    pub fn equivalent_step(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.length
+        }
+    }
+
+    /// How many whole cycles lie between the equivalent step and step `n`.
+    pub fn cycles_skipped(&self, n: usize) -> usize {
+        n.saturating_sub(self.start) / self.length
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Method {
+    /// Remembers every key seen, so it needs the fewest steps but the most memory.
+    HashMap,
+    /// Floyd's tortoise and hare, which needs constant memory.
+    Floyd,
+    /// Brent's algorithm, which needs constant memory and fewer steps than Floyd's.
+    Brent,
+}
+
+pub fn find_cycle<S, K>(
+    initial: &S,
+    step: impl FnMut(&mut S),
+    key: impl Fn(&S) -> K,
+    method: Method,
+) -> Cycle
+where
+    S: Clone,
+    K: Hash + Eq,
+{
+    match method {
+        Method::HashMap => hash_map(initial, step, key),
+        Method::Floyd => floyd(initial, step, key),
+        Method::Brent => brent(initial, step, key),
+    }
+}
+
+pub fn hash_map<S, K>(initial: &S, mut step: impl FnMut(&mut S), key: impl Fn(&S) -> K) -> Cycle
+where
+    S: Clone,
+    K: Hash + Eq,
+{
+    let mut seen = HashMap::new();
+    let mut state = initial.clone();
+    let mut n = 0;
+    loop {
+        if let Some(start) = seen.insert(key(&state), n) {
+            return Cycle {
+                start,
+                length: n - start,
+            };
+        }
+        step(&mut state);
+        n += 1;
+    }
+}
+
+pub fn floyd<S, K>(initial: &S, mut step: impl FnMut(&mut S), key: impl Fn(&S) -> K) -> Cycle
+where
+    S: Clone,
+    K: PartialEq,
+{
+    // The hare moves twice as fast, so they meet somewhere inside the cycle
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+    loop {
+        step(&mut tortoise);
+        step(&mut hare);
+        step(&mut hare);
+        if key(&tortoise) == key(&hare) {
+            break;
+        }
+    }
+
+    let start = find_start(initial, hare, &mut step, &key);
+
+    // Walk once around the cycle to measure it
+    let mut tortoise = initial.clone();
+    for _ in 0..start {
+        step(&mut tortoise);
+    }
+    let target = key(&tortoise);
+    let mut length = 1;
+    step(&mut tortoise);
+    while key(&tortoise) != target {
+        step(&mut tortoise);
+        length += 1;
+    }
+
+    Cycle { start, length }
+}
+
+pub fn brent<S, K>(initial: &S, mut step: impl FnMut(&mut S), key: impl Fn(&S) -> K) -> Cycle
+where
+    S: Clone,
+    K: PartialEq,
+{
+    // The tortoise teleports to the hare at every power of two, so the cycle length is found directly
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = key(initial);
+    let mut hare = initial.clone();
+    step(&mut hare);
+    while key(&hare) != tortoise {
+        if power == length {
+            tortoise = key(&hare);
+            power *= 2;
+            length = 0;
+        }
+        step(&mut hare);
+        length += 1;
+    }
+
+    // Start the hare one cycle length ahead, then move both until they meet
+    let mut hare = initial.clone();
+    for _ in 0..length {
+        step(&mut hare);
+    }
+    let start = find_start(initial, hare, &mut step, &key);
+
+    Cycle { start, length }
+}
+
+/// Moves a tortoise from the start and the hare at the same speed, and they meet at the start of the cycle.
+fn find_start<S, K>(
+    initial: &S,
+    mut hare: S,
+    step: &mut impl FnMut(&mut S),
+    key: &impl Fn(&S) -> K,
+) -> usize
+where
+    S: Clone,
+    K: PartialEq,
+{
+    let mut tortoise = initial.clone();
+    let mut start = 0;
+    while key(&tortoise) != key(&hare) {
+        step(&mut tortoise);
+        step(&mut hare);
+        start += 1;
+    }
+    start
+}
+
+/// Extrapolates a metric that grows by the same amount each time around a cycle.
+#[derive(Clone, Debug)]
+pub struct Projection<M> {
+    cycle: Cycle,
+    // The metric at every step up to the end of the first pass through the cycle
+    metrics: Vec<M>,
+}
+
+impl<M> Projection<M>
+where
+    M: Copy + Add<Output = M> + Sub<Output = M> + Mul<Output = M> + TryFrom<usize>,
+{
+    pub fn new<S, K>(
+        initial: &S,
+        mut step: impl FnMut(&mut S),
+        key: impl Fn(&S) -> K,
+        metric: impl Fn(&S) -> M,
+        method: Method,
+    ) -> Self
+    where
+        S: Clone,
+        K: Hash + Eq,
+    {
+        let cycle = find_cycle(initial, &mut step, key, method);
+
+        let mut state = initial.clone();
+        let mut metrics = Vec::with_capacity(cycle.start + cycle.length + 1);
+        metrics.push(metric(&state));
+        for _ in 0..cycle.start + cycle.length {
+            step(&mut state);
+            metrics.push(metric(&state));
+        }
+
+        Self { cycle, metrics }
+    }
+
+    pub fn cycle(&self) -> Cycle {
+        self.cycle
+    }
+
+    /// The value of the metric after `n` steps.
+    pub fn at(&self, n: usize) -> M {
+        if n < self.metrics.len() {
+            return self.metrics[n];
+        }
+        let start = self.cycle.start;
+        let per_cycle = self.metrics[start + self.cycle.length] - self.metrics[start];
+        let cycles = M::try_from(self.cycle.cycles_skipped(n))
+            .unwrap_or_else(|_| panic!("Too many cycles to represent in the metric type"));
+        self.metrics[self.cycle.equivalent_step(n)] + per_cycle * cycles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0, 1, 2, 3, 4, 5, 3, 4, 5, ...
+    fn step(n: &mut usize) {
+        *n = if *n == 5 { 3 } else { *n + 1 };
+    }
+
+    #[test]
+    fn methods() {
+        let expected = Cycle {
+            start: 3,
+            length: 3,
+        };
+        for method in [Method::HashMap, Method::Floyd, Method::Brent] {
+            assert_eq!(find_cycle(&0, step, |n| *n, method), expected);
+        }
+
+        // A fixed point is a cycle of length one
+        for method in [Method::HashMap, Method::Floyd, Method::Brent] {
+            let cycle = find_cycle(&0_usize, |n| *n = (*n + 1).min(4), |n| *n, method);
+            assert_eq!(
+                cycle,
+                Cycle {
+                    start: 4,
+                    length: 1
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn projection() {
+        // Count the total of the values seen so far alongside the value itself
+        let projection = Projection::new(
+            &(0, 0),
+            |(n, total)| {
+                step(n);
+                *total += *n;
+            },
+            |(n, _)| *n,
+            |(_, total)| *total,
+            Method::Brent,
+        );
+        let mut state = (0, 0);
+        for n in 0..100 {
+            assert_eq!(projection.at(n), state.1);
+            step(&mut state.0);
+            state.1 += state.0;
+        }
+        // Every three steps past the start adds 3 + 4 + 5
+        assert_eq!(projection.at(3_000_003), projection.at(3) + 12_000_000);
+    }
+}
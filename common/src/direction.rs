@@ -0,0 +1,290 @@
+use std::fmt;
+use std::str::FromStr;
+
+// All deltas use screen coordinates: x increases to the right and y increases downwards,
+// which matches the row/column order that grids are read in.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDirectionError(String);
+
+impl fmt::Display for ParseDirectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid direction: {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseDirectionError {}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Turn {
+    Left,
+    Right,
+    Around,
+}
+
+impl TryFrom<char> for Turn {
+    type Error = ParseDirectionError;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            'L' => Ok(Turn::Left),
+            'R' => Ok(Turn::Right),
+            _ => Err(ParseDirectionError(c.to_string())),
+        }
+    }
+}
+
+/// The four cardinal directions, in clockwise order.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    fn from_index(index: usize) -> Self {
+        Self::ALL[index % 4]
+    }
+
+    pub fn turn_right(self) -> Self {
+        Self::from_index(self as usize + 1)
+    }
+
+    pub fn turn_left(self) -> Self {
+        Self::from_index(self as usize + 3)
+    }
+
+    pub fn reverse(self) -> Self {
+        Self::from_index(self as usize + 2)
+    }
+
+    pub fn turn(self, turn: Turn) -> Self {
+        match turn {
+            Turn::Left => self.turn_left(),
+            Turn::Right => self.turn_right(),
+            Turn::Around => self.reverse(),
+        }
+    }
+
+    pub fn delta(self) -> (isize, isize) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Right => (1, 0),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+        }
+    }
+
+    /// The facing score used by 2022 day 22: right is 0, then counting clockwise.
+    pub fn facing_score(self) -> usize {
+        (self as usize + 3) % 4
+    }
+
+    /// This direction and the two diagonals either side of it.
+    pub fn with_diagonals(self) -> [Compass; 3] {
+        let compass = Compass::from(self);
+        [compass.rotate_left(), compass, compass.rotate_right()]
+    }
+
+    /// Reads a direction written out as a command word. Courses are drawn with depth going down
+    /// the screen, so `forward` is to the right.
+    pub fn from_word(s: &str) -> Result<Self, ParseDirectionError> {
+        match s {
+            "up" => Ok(Direction::Up),
+            "down" => Ok(Direction::Down),
+            "forward" => Ok(Direction::Right),
+            _ => Err(ParseDirectionError(s.to_string())),
+        }
+    }
+}
+
+impl TryFrom<char> for Direction {
+    type Error = ParseDirectionError;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            'U' | '^' | 'N' => Ok(Direction::Up),
+            'R' | '>' | 'E' => Ok(Direction::Right),
+            'D' | 'v' | 'S' => Ok(Direction::Down),
+            'L' | '<' | 'W' => Ok(Direction::Left),
+            _ => Err(ParseDirectionError(c.to_string())),
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = ParseDirectionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Direction::try_from(c),
+            _ => Direction::from_word(s),
+        }
+    }
+}
+
+/// All eight points of the compass: the cardinal directions and the ordinal ones between them.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Compass {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Compass {
+    pub const ALL: [Compass; 8] = [
+        Compass::North,
+        Compass::NorthEast,
+        Compass::East,
+        Compass::SouthEast,
+        Compass::South,
+        Compass::SouthWest,
+        Compass::West,
+        Compass::NorthWest,
+    ];
+
+    fn from_index(index: usize) -> Self {
+        Self::ALL[index % 8]
+    }
+
+    /// Rotates 45 degrees clockwise.
+    pub fn rotate_right(self) -> Self {
+        Self::from_index(self as usize + 1)
+    }
+
+    /// Rotates 45 degrees anticlockwise.
+    pub fn rotate_left(self) -> Self {
+        Self::from_index(self as usize + 7)
+    }
+
+    pub fn turn_right(self) -> Self {
+        Self::from_index(self as usize + 2)
+    }
+
+    pub fn turn_left(self) -> Self {
+        Self::from_index(self as usize + 6)
+    }
+
+    pub fn reverse(self) -> Self {
+        Self::from_index(self as usize + 4)
+    }
+
+    pub fn turn(self, turn: Turn) -> Self {
+        match turn {
+            Turn::Left => self.turn_left(),
+            Turn::Right => self.turn_right(),
+            Turn::Around => self.reverse(),
+        }
+    }
+
+    pub fn is_cardinal(self) -> bool {
+        matches!(
+            self,
+            Compass::North | Compass::East | Compass::South | Compass::West
+        )
+    }
+
+    pub fn delta(self) -> (isize, isize) {
+        match self {
+            Compass::North => (0, -1),
+            Compass::NorthEast => (1, -1),
+            Compass::East => (1, 0),
+            Compass::SouthEast => (1, 1),
+            Compass::South => (0, 1),
+            Compass::SouthWest => (-1, 1),
+            Compass::West => (-1, 0),
+            Compass::NorthWest => (-1, -1),
+        }
+    }
+}
+
+impl From<Direction> for Compass {
+    fn from(direction: Direction) -> Self {
+        Self::from_index(direction as usize * 2)
+    }
+}
+
+impl TryFrom<Compass> for Direction {
+    type Error = Compass;
+
+    fn try_from(compass: Compass) -> Result<Self, Self::Error> {
+        if compass.is_cardinal() {
+            Ok(Direction::from_index(compass as usize / 2))
+        } else {
+            Err(compass)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing() {
+        for s in ["U", "^", "N", "up"] {
+            assert_eq!(s.parse::<Direction>(), Ok(Direction::Up));
+        }
+        for s in ["D", "v", "S", "down"] {
+            assert_eq!(s.parse::<Direction>(), Ok(Direction::Down));
+        }
+        for s in ["R", ">", "E", "forward"] {
+            assert_eq!(s.parse::<Direction>(), Ok(Direction::Right));
+        }
+        for s in ["L", "<", "W"] {
+            assert_eq!(s.parse::<Direction>(), Ok(Direction::Left));
+        }
+        assert_eq!(Direction::from_word("forward"), Ok(Direction::Right));
+        assert!(Direction::from_word("U").is_err());
+        assert!("backward".parse::<Direction>().is_err());
+        assert!("UU".parse::<Direction>().is_err());
+        assert_eq!(Turn::try_from('L'), Ok(Turn::Left));
+        assert!(Turn::try_from('U').is_err());
+    }
+
+    #[test]
+    fn rotation() {
+        for d in Direction::ALL {
+            assert_eq!(d.turn_left().turn_right(), d);
+            assert_eq!(d.turn_right().turn_right(), d.reverse());
+            assert_eq!(d.turn(Turn::Around).turn(Turn::Around), d);
+            let (dx, dy) = d.delta();
+            assert_eq!(d.reverse().delta(), (-dx, -dy));
+            // Turning right in screen coordinates maps (dx, dy) to (-dy, dx)
+            assert_eq!(d.turn_right().delta(), (-dy, dx));
+            assert_eq!(Compass::from(d).turn_right(), Compass::from(d.turn_right()));
+            assert_eq!(Direction::try_from(Compass::from(d)), Ok(d));
+        }
+        for c in Compass::ALL {
+            assert_eq!(c.rotate_left().rotate_right(), c);
+            assert_eq!(c.turn_left().reverse(), c.turn_right());
+        }
+        assert_eq!(
+            Direction::Up.with_diagonals(),
+            [Compass::NorthWest, Compass::North, Compass::NorthEast]
+        );
+    }
+
+    #[test]
+    fn facing_scores() {
+        assert_eq!(Direction::Right.facing_score(), 0);
+        assert_eq!(Direction::Down.facing_score(), 1);
+        assert_eq!(Direction::Left.facing_score(), 2);
+        assert_eq!(Direction::Up.facing_score(), 3);
+    }
+}
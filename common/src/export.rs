@@ -0,0 +1,374 @@
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::animate::{Animation, Colour};
+
+// Pictures are drawn from the same text as the terminal animations, one pixel per character
+// before scaling, so every day only needs to say which colour each character should be.
+
+const BACKGROUND: [u8; 3] = [15, 15, 35];
+
+/// The colour of each character that should stand out, with anything else left as background.
+pub type Palette = [(char, Colour)];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// A single animated image of the whole run.
+    Gif,
+    /// One numbered image per step, such as `frames_0000.png`.
+    Png,
+    /// A 3D surface, for the days that have one.
+    Obj,
+    Stl,
+    /// 3D points with colours.
+    Ply,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Options {
+    pub path: PathBuf,
+    pub format: Format,
+    /// How many pixels wide and high each character is drawn.
+    pub scale: usize,
+    pub fps: f64,
+}
+
+impl Options {
+    /// Reads `--export PATH [--scale N] [--fps N]` from the command line arguments,
+    /// with the format chosen by the extension of the path.
+    /// Returns `None` if exporting wasn't asked for.
+    pub fn from_args(args: &[String]) -> Option<Self> {
+        let position = args.iter().position(|arg| arg == "--export")?;
+        let path = PathBuf::from(
+            args.get(position + 1)
+                .expect("--export must be followed by a path"),
+        );
+        let format = match path.extension().and_then(|e| e.to_str()) {
+            Some("gif") => Format::Gif,
+            Some("png") => Format::Png,
+            Some("obj") => Format::Obj,
+            Some("stl") => Format::Stl,
+            Some("ply") => Format::Ply,
+            _ => panic!("--export needs a path ending in .gif, .png, .obj, .stl or .ply"),
+        };
+
+        let mut options = Options {
+            path,
+            format,
+            scale: 4,
+            fps: 10.0,
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--scale" => {
+                    options.scale = args
+                        .next()
+                        .and_then(|scale| scale.parse().ok())
+                        .filter(|scale| *scale > 0)
+                        .expect("--scale must be followed by a positive integer")
+                }
+                "--fps" => {
+                    options.fps = args
+                        .next()
+                        .and_then(|fps| fps.parse().ok())
+                        .filter(|fps: &f64| *fps > 0.0)
+                        .expect("--fps must be followed by a positive number")
+                }
+                _ => {}
+            }
+        }
+        Some(options)
+    }
+
+    /// Where the image for step `n` goes when writing one image per step.
+    pub fn numbered_path(&self, n: usize) -> PathBuf {
+        let stem = self.path.file_stem().unwrap_or_default().to_string_lossy();
+        self.path.with_file_name(format!("{}_{:04}.png", stem, n))
+    }
+}
+
+/// A picture where each pixel is an index into a palette, with 0 as the background.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Draws one pixel per character, ignoring any colour codes.
+    /// Short lines are padded with background.
+    pub fn from_text(text: &str, palette: &Palette) -> Self {
+        assert!(palette.len() < 256, "Too many colours for an image");
+        let plain = strip_colours(text);
+        let rows: Vec<Vec<u8>> = plain
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .map(|c| {
+                        palette
+                            .iter()
+                            .position(|&(p, _)| p == c)
+                            .map_or(0, |i| i as u8 + 1)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let mut image = Image::blank(width, rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            image.pixels[y * width..y * width + row.len()].copy_from_slice(row);
+        }
+        image
+    }
+
+    fn blank(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height],
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> u8 {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Blows each pixel up into a square, then pads the result out to at least the given size.
+    fn resized(&self, scale: usize, width: usize, height: usize) -> Self {
+        let mut image = Image::blank(
+            width.max(self.width * scale),
+            height.max(self.height * scale),
+        );
+        for y in 0..self.height * scale {
+            for x in 0..self.width * scale {
+                image.pixels[y * image.width + x] = self.get(x / scale, y / scale);
+            }
+        }
+        image
+    }
+
+    pub fn write_png(&self, palette: &Palette, out: impl Write) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(out, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Indexed);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_palette(colour_table(palette));
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        Ok(())
+    }
+}
+
+fn strip_colours(text: &str) -> Cow<'_, str> {
+    if !text.contains('\x1b') {
+        return Cow::Borrowed(text);
+    }
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip up to the letter that ends the escape sequence
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            plain.push(c);
+        }
+    }
+    Cow::Owned(plain)
+}
+
+fn colour_table(palette: &Palette) -> Vec<u8> {
+    std::iter::once(BACKGROUND)
+        .chain(palette.iter().map(|(_, colour)| colour.rgb()))
+        .flatten()
+        .collect()
+}
+
+/// Writes every frame to one animated image that loops forever.
+/// Frames of different sizes are padded out to the largest.
+pub fn write_gif(
+    images: &[Image],
+    palette: &Palette,
+    options: &Options,
+    out: impl Write,
+) -> io::Result<()> {
+    let scale = options.scale;
+    let width = images
+        .iter()
+        .map(|image| image.width * scale)
+        .max()
+        .unwrap_or(0);
+    let height = images
+        .iter()
+        .map(|image| image.height * scale)
+        .max()
+        .unwrap_or(0);
+    let (Ok(w), Ok(h)) = (u16::try_from(width), u16::try_from(height)) else {
+        return Err(io::Error::other("Frames are too big for a GIF"));
+    };
+    // Delays are in hundredths of a second
+    let delay = (100.0 / options.fps).round().max(1.0) as u16;
+
+    let mut encoder =
+        gif::Encoder::new(out, w, h, &colour_table(palette)).map_err(io::Error::other)?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(io::Error::other)?;
+    for image in images {
+        let image = image.resized(scale, width, height);
+        let frame = gif::Frame {
+            width: w,
+            height: h,
+            delay,
+            buffer: Cow::Owned(image.pixels),
+            ..Default::default()
+        };
+        encoder.write_frame(&frame).map_err(io::Error::other)?;
+    }
+    Ok(())
+}
+
+/// Runs an animation to the end, drawing each step with `picture` and saving the pictures as asked.
+/// `picture` should leave out anything that isn't part of the state, such as a status line.
+pub fn record<A: Animation>(
+    animation: &mut A,
+    picture: impl Fn(&A) -> String,
+    palette: &Palette,
+    options: &Options,
+) {
+    assert!(
+        matches!(options.format, Format::Gif | Format::Png),
+        "An animation can only be exported as .gif or .png"
+    );
+    let mut images = vec![Image::from_text(&picture(animation), palette)];
+    loop {
+        let finished = !animation.step();
+        // The last step can still change something, as with reaching the end of a walk
+        let image = Image::from_text(&picture(animation), palette);
+        if !finished || images.last() != Some(&image) {
+            images.push(image);
+        }
+        if finished {
+            break;
+        }
+    }
+    save(&images, palette, options).expect("Failed to write the export");
+    println!(
+        "Exported {} frames to {}",
+        images.len(),
+        options.path.display()
+    );
+}
+
+/// Saves a single picture drawn from text, such as a map, as one image at the path given.
+pub fn picture(text: &str, palette: &Palette, options: &Options) {
+    let image = Image::from_text(text, palette);
+    let saved = match options.format {
+        Format::Png => create(&options.path)
+            .and_then(|out| image.resized(options.scale, 0, 0).write_png(palette, out)),
+        Format::Gif => save(&[image], palette, options),
+        _ => panic!("A picture can only be exported as .gif or .png"),
+    };
+    saved.expect("Failed to write the export");
+    println!("Exported a picture to {}", options.path.display());
+}
+
+fn save(images: &[Image], palette: &Palette, options: &Options) -> io::Result<()> {
+    match options.format {
+        Format::Gif => write_gif(images, palette, options, create(&options.path)?),
+        Format::Png => {
+            for (n, image) in images.iter().enumerate() {
+                let image = image.resized(options.scale, 0, 0);
+                image.write_png(palette, create(&options.numbered_path(n))?)?;
+            }
+            Ok(())
+        }
+        format => unreachable!("{:?} isn't an image format", format),
+    }
+}
+
+pub(crate) fn create(path: &Path) -> io::Result<BufWriter<File>> {
+    File::create(path).map(BufWriter::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PALETTE: &Palette = &[('#', Colour::Grey), ('o', Colour::Yellow)];
+
+    fn args(args: &str) -> Vec<String> {
+        args.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn options() {
+        assert_eq!(Options::from_args(&args("14 --animate")), None);
+        let options = Options::from_args(&args("14 --export out/sand.png --scale 2")).unwrap();
+        assert_eq!(options.format, Format::Png);
+        assert_eq!(options.scale, 2);
+        assert_eq!(options.numbered_path(7), PathBuf::from("out/sand_0007.png"));
+        let options = Options::from_args(&args("14 --export sand.gif --fps 25")).unwrap();
+        assert_eq!(options.format, Format::Gif);
+        assert_eq!(options.fps, 25.0);
+        let options = Options::from_args(&args("18 --export droplet.stl")).unwrap();
+        assert_eq!(options.format, Format::Stl);
+    }
+
+    #[test]
+    fn images() {
+        let image = Image::from_text(&format!("#.{}\n#\n", Colour::Yellow.paint('o')), PALETTE);
+        assert_eq!((image.width, image.height), (3, 2));
+        assert_eq!(image.pixels, vec![1, 0, 2, 1, 0, 0]);
+
+        let big = image.resized(2, 8, 0);
+        assert_eq!((big.width, big.height), (8, 4));
+        assert_eq!(big.get(5, 1), 2);
+        assert_eq!(big.get(7, 1), 0);
+    }
+
+    #[test]
+    fn png() {
+        let image = Image::from_text("#o\n.#\n", PALETTE);
+        let mut out = Vec::new();
+        image.write_png(PALETTE, &mut out).unwrap();
+
+        let mut reader = png::Decoder::new(out.as_slice()).read_info().unwrap();
+        let info = reader.info();
+        assert_eq!((info.width, info.height), (2, 2));
+        assert_eq!(info.color_type, png::ColorType::Indexed);
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut pixels).unwrap();
+        assert_eq!(pixels, vec![1, 2, 0, 1]);
+    }
+
+    #[test]
+    fn gif() {
+        let images = [
+            Image::from_text("#\n", PALETTE),
+            Image::from_text("o#\n#\n", PALETTE),
+        ];
+        let options = Options {
+            path: PathBuf::from("test.gif"),
+            format: Format::Gif,
+            scale: 3,
+            fps: 20.0,
+        };
+        let mut out = Vec::new();
+        write_gif(&images, PALETTE, &options, &mut out).unwrap();
+
+        let mut decoder = gif::DecodeOptions::new();
+        decoder.set_color_output(gif::ColorOutput::Indexed);
+        let mut decoder = decoder.read_info(out.as_slice()).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (6, 6));
+        let mut frames = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.delay, 5);
+            frames += 1;
+        }
+        assert_eq!(frames, 2);
+    }
+}
@@ -0,0 +1,300 @@
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+use crate::direction::{Compass, Direction};
+
+pub type Point = (isize, isize);
+
+pub fn offset((x, y): Point, (dx, dy): (isize, isize)) -> Point {
+    (x + dx, y + dy)
+}
+
+/// The eight surrounding points.
+pub fn moore(p: Point) -> [Point; 8] {
+    Compass::ALL.map(|c| offset(p, c.delta()))
+}
+
+/// The four orthogonally adjacent points.
+pub fn von_neumann(p: Point) -> [Point; 4] {
+    Direction::ALL.map(|d| offset(p, d.delta()))
+}
+
+/// A dense rectangular grid, stored row by row.
+/// Anything outside the grid reads as the background value, so a grid can stand in for an
+/// infinite plane as long as everything beyond its bounds looks the same.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    origin: Point,
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+    background: T,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, background: T) -> Self {
+        Self {
+            origin: (0, 0),
+            width,
+            height,
+            cells: vec![background.clone(); width * height],
+            background,
+        }
+    }
+
+    pub fn from_rows(rows: Vec<Vec<T>>, background: T) -> Self {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |row| row.len());
+        assert!(
+            rows.iter().all(|row| row.len() == width),
+            "Grid rows must all be the same length"
+        );
+        Self {
+            origin: (0, 0),
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+            background,
+        }
+    }
+
+    pub fn parse(input: &str, background: T, mut f: impl FnMut(char) -> T) -> Self {
+        let rows = input
+            .lines()
+            .map(|line| line.chars().map(&mut f).collect())
+            .collect();
+        Self::from_rows(rows, background)
+    }
+
+    /// Resizes this grid to cover `min..max` and refills it from `f`, reusing the allocation.
+    pub fn refill(&mut self, min: Point, max: Point, background: T, f: impl FnMut(Point) -> T) {
+        self.origin = min;
+        self.width = (max.0 - min.0) as usize;
+        self.height = (max.1 - min.1) as usize;
+        self.background = background;
+        self.cells.clear();
+        self.cells.extend(Self::points_between(min, max).map(f));
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn background(&self) -> &T {
+        &self.background
+    }
+
+    /// The top-left corner (inclusive) and bottom-right corner (exclusive).
+    pub fn bounds(&self) -> (Point, Point) {
+        let (x, y) = self.origin;
+        (
+            self.origin,
+            (x + self.width as isize, y + self.height as isize),
+        )
+    }
+
+    fn index(&self, (x, y): Point) -> Option<usize> {
+        let x = x - self.origin.0;
+        let y = y - self.origin.1;
+        if x < 0 || y < 0 || x >= self.width as isize || y >= self.height as isize {
+            None
+        } else {
+            Some(y as usize * self.width + x as usize)
+        }
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        self.index(p).is_some()
+    }
+
+    pub fn get(&self, p: Point) -> &T {
+        match self.index(p) {
+            Some(i) => &self.cells[i],
+            None => &self.background,
+        }
+    }
+
+    /// Reads from the grid as if it were wrapped around a torus.
+    pub fn get_wrapped(&self, (x, y): Point) -> &T {
+        let x = (x - self.origin.0).rem_euclid(self.width as isize);
+        let y = (y - self.origin.1).rem_euclid(self.height as isize);
+        &self.cells[y as usize * self.width + x as usize]
+    }
+
+    pub fn set(&mut self, p: Point, value: T) {
+        let i = self.index(p).expect("Point outside the grid");
+        self.cells[i] = value;
+    }
+
+    fn points_between(min: Point, max: Point) -> impl Iterator<Item = Point> {
+        (min.1..max.1).flat_map(move |y| (min.0..max.0).map(move |x| (x, y)))
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let (min, max) = self.bounds();
+        Self::points_between(min, max)
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    pub fn count(&self, f: impl Fn(&T) -> bool) -> usize {
+        self.cells.iter().filter(|cell| f(cell)).count()
+    }
+
+    pub fn render(&self, f: impl Fn(&T) -> char) -> String {
+        let mut output = String::with_capacity((self.width + 1) * self.height);
+        for row in self.cells.chunks(self.width.max(1)) {
+            output.extend(row.iter().map(&f));
+            output.push('\n');
+        }
+        output
+    }
+}
+
+/// A set of live points on an unbounded plane, for worlds that are mostly empty.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SparseGrid {
+    points: HashSet<Point>,
+}
+
+impl SparseGrid {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn parse(input: &str, live: char) -> Self {
+        input
+            .lines()
+            .enumerate()
+            .flat_map(|(y, line)| {
+                line.chars()
+                    .enumerate()
+                    .filter(move |(_, c)| *c == live)
+                    .map(move |(x, _)| (x as isize, y as isize))
+            })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        self.points.contains(&p)
+    }
+
+    pub fn insert(&mut self, p: Point) -> bool {
+        self.points.insert(p)
+    }
+
+    pub fn remove(&mut self, p: Point) -> bool {
+        self.points.remove(&p)
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Point> + '_ {
+        self.points.iter().copied()
+    }
+
+    /// The smallest rectangle containing every live point, as inclusive corners.
+    pub fn bounds(&self) -> Option<(Point, Point)> {
+        let min_x = self.iter().map(|p| p.0).min()?;
+        let max_x = self.iter().map(|p| p.0).max()?;
+        let min_y = self.iter().map(|p| p.1).min()?;
+        let max_y = self.iter().map(|p| p.1).max()?;
+        Some(((min_x, min_y), (max_x, max_y)))
+    }
+
+    /// Draws the smallest rectangle containing every live point.
+    pub fn render(&self, live: char, dead: char) -> String {
+        let Some((min, max)) = self.bounds() else {
+            return String::new();
+        };
+        let mut output = String::new();
+        for y in min.1..=max.1 {
+            output.extend((min.0..=max.0).map(|x| if self.contains((x, y)) { live } else { dead }));
+            output.push('\n');
+        }
+        output
+    }
+}
+
+impl FromIterator<Point> for SparseGrid {
+    fn from_iter<I: IntoIterator<Item = Point>>(iter: I) -> Self {
+        Self {
+            points: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<Point> for SparseGrid {
+    fn extend<I: IntoIterator<Item = Point>>(&mut self, iter: I) {
+        self.points.extend(iter)
+    }
+}
+
+impl Hash for SparseGrid {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Sets have no fixed order, so hash the points in sorted order
+        let mut points: Vec<&Point> = self.points.iter().collect();
+        points.sort_unstable();
+        points.hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dense() {
+        let mut grid = Grid::parse("#.\n.#\n..\n", false, |c| c == '#');
+        assert_eq!((grid.width(), grid.height()), (2, 3));
+        assert!(*grid.get((0, 0)));
+        assert!(!*grid.get((5, -1)));
+        assert!(*grid.get_wrapped((-1, 1)));
+        grid.set((1, 2), true);
+        assert_eq!(grid.count(|c| *c), 3);
+        assert_eq!(grid.render(|c| if *c { '#' } else { '.' }), "#.\n.#\n.#\n");
+
+        grid.refill((-1, -1), (1, 1), true, |(x, y)| x == y);
+        assert_eq!(grid.bounds(), ((-1, -1), (1, 1)));
+        assert_eq!(grid.count(|c| *c), 2);
+        assert!(*grid.get((5, 5)));
+    }
+
+    #[test]
+    fn sparse() {
+        let grid = SparseGrid::parse("#.\n.#\n", '#');
+        assert_eq!(grid.len(), 2);
+        assert!(grid.contains((1, 1)));
+        assert_eq!(grid.bounds(), Some(((0, 0), (1, 1))));
+        assert_eq!(grid.render('#', '.'), "#.\n.#\n");
+        assert_eq!(
+            moore((0, 0)).iter().filter(|p| grid.contains(**p)).count(),
+            1
+        );
+        assert_eq!(
+            von_neumann((1, 0))
+                .iter()
+                .filter(|p| grid.contains(**p))
+                .count(),
+            2
+        );
+    }
+}
@@ -0,0 +1,16 @@
+pub mod animate;
+pub mod automaton;
+pub mod budget;
+pub mod cycle;
+pub mod direction;
+pub mod export;
+pub mod grid;
+pub mod memo;
+pub mod memory;
+pub mod mesh;
+pub mod parallel;
+pub mod parse;
+pub mod repl;
+pub mod runner;
+pub mod trace;
+pub mod tree;
@@ -0,0 +1,200 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::hash::Hash;
+
+use crate::trace;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+    pub evictions: usize,
+    /// The most entries the cache held at once.
+    pub peak_size: usize,
+}
+
+impl CacheStats {
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+
+    /// Adds the lookups and evictions to the trace counters, which are printed after each day when
+    /// the `trace` feature is on. The peak size is left out, as it doesn't add up across caches.
+    pub fn trace(&self) {
+        trace::add("cache hits", self.hits as u64);
+        trace::add("cache misses", self.misses as u64);
+        if self.evictions > 0 {
+            trace::add("cache evictions", self.evictions as u64);
+        }
+    }
+}
+
+impl fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} hits, {} misses ({:.1}% hit rate), {} evictions, {} peak entries",
+            self.hits,
+            self.misses,
+            self.hit_rate() * 100.0,
+            self.evictions,
+            self.peak_size
+        )
+    }
+}
+
+/// A cache for recursive computations over any hashable state.
+/// If a size limit is set, the oldest entries are evicted first once the cache is full.
+#[derive(Clone, Debug)]
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+    limit: Option<usize>,
+    // Insertion order, only tracked when there is a limit
+    order: VecDeque<K>,
+    stats: CacheStats,
+}
+
+impl<K, V> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self {
+            cache: HashMap::new(),
+            limit: None,
+            order: VecDeque::new(),
+            stats: CacheStats::default(),
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_limit(limit: usize) -> Self {
+        assert!(limit > 0, "Cache limit must be positive");
+        Self {
+            limit: Some(limit),
+            ..Default::default()
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.cache.contains_key(key)
+    }
+
+    /// Looks at a cached value without counting it as a hit or miss.
+    pub fn cached(&self, key: &K) -> Option<&V> {
+        self.cache.get(key)
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        if let Some(limit) = self.limit {
+            if !self.cache.contains_key(&key) {
+                while self.cache.len() >= limit {
+                    let oldest = self.order.pop_front().unwrap();
+                    self.cache.remove(&oldest);
+                    self.stats.evictions += 1;
+                }
+                self.order.push_back(key.clone());
+            }
+        }
+        self.cache.insert(key, value);
+        self.stats.peak_size = self.stats.peak_size.max(self.cache.len());
+    }
+
+    fn lookup(&mut self, key: &K) -> Option<V> {
+        let value = self.cache.get(key).cloned();
+        if value.is_some() {
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+        }
+        value
+    }
+
+    pub fn get_or_insert_with(&mut self, key: K, f: impl FnOnce(&K) -> V) -> V {
+        if let Some(value) = self.lookup(&key) {
+            return value;
+        }
+        let value = f(&key);
+        self.insert(key, value.clone());
+        value
+    }
+
+    /// Computes the value for `key` with `f`, reusing cached values where possible.
+    /// `f` is given a function to call for the value of any other key it depends on,
+    /// which goes back through the cache.
+    pub fn solve<F>(&mut self, key: &K, f: &F) -> V
+    where
+        F: Fn(&K, &mut dyn FnMut(&K) -> V) -> V,
+    {
+        if let Some(value) = self.lookup(key) {
+            return value;
+        }
+        let value = f(key, &mut |k| self.solve(k, f));
+        self.insert(key.clone(), value.clone());
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fibonacci(n: &u64, recurse: &mut dyn FnMut(&u64) -> u64) -> u64 {
+        if *n < 2 {
+            *n
+        } else {
+            recurse(&(n - 1)) + recurse(&(n - 2))
+        }
+    }
+
+    #[test]
+    fn unbounded() {
+        let mut memo = Memo::new();
+        assert_eq!(memo.solve(&90, &fibonacci), 2880067194370816120);
+        let stats = memo.stats();
+        assert_eq!(stats.misses, 91);
+        assert_eq!(stats.hits, 88);
+        assert_eq!(stats.evictions, 0);
+        assert_eq!(stats.peak_size, 91);
+
+        // A second call is a single hit
+        assert_eq!(memo.solve(&90, &fibonacci), 2880067194370816120);
+        assert_eq!(memo.stats().hits, 89);
+    }
+
+    #[test]
+    fn bounded() {
+        let mut memo = Memo::with_limit(3);
+        assert_eq!(memo.solve(&50, &fibonacci), 12586269025);
+        assert_eq!(memo.len(), 3);
+        assert_eq!(memo.stats().peak_size, 3);
+        assert!(memo.stats().evictions > 0);
+
+        let mut memo = Memo::with_limit(2);
+        assert_eq!(memo.get_or_insert_with(1, |k| k * 10), 10);
+        assert_eq!(memo.get_or_insert_with(2, |k| k * 10), 20);
+        assert_eq!(memo.get_or_insert_with(1, |_| unreachable!()), 10);
+        assert_eq!(memo.get_or_insert_with(3, |k| k * 10), 30);
+        assert!(!memo.contains(&1));
+        assert_eq!(memo.cached(&2), Some(&20));
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+
+use crate::animate::Colour;
+use crate::export::{create, Format, Options};
+
+// Everything here is measured in whole cells, where the cell at [x, y, z] is the unit cube
+// from that corner to [x + 1, y + 1, z + 1].
+
+pub type Point3 = [isize; 3];
+
+/// A surface made of square faces, with corners listed anticlockwise when seen from outside.
+#[derive(Clone, Debug, Default)]
+pub struct Mesh {
+    vertices: Vec<Point3>,
+    faces: Vec<[usize; 4]>,
+    // So that faces which touch share their corners
+    indices: HashMap<Point3, usize>,
+}
+
+impl Mesh {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// The faces of a set of cells that aren't covered by another cell.
+    pub fn voxel_surface(cells: impl IntoIterator<Item = Point3>) -> Self {
+        let cells: HashSet<Point3> = cells.into_iter().collect();
+        let mut mesh = Mesh::new();
+        for &cell in &cells {
+            for axis in 0..3 {
+                for positive in [false, true] {
+                    let mut neighbour = cell;
+                    neighbour[axis] += if positive { 1 } else { -1 };
+                    if cells.contains(&neighbour) {
+                        continue;
+                    }
+                    let mut origin = cell;
+                    if positive {
+                        origin[axis] += 1;
+                    }
+                    mesh.add_face(origin, axis, (1, 1), positive);
+                }
+            }
+        }
+        mesh
+    }
+
+    /// Adds a box covering the cells from `min` to `max` inclusive.
+    pub fn add_cuboid(&mut self, min: Point3, max: Point3) {
+        for axis in 0..3 {
+            let (b, c) = ((axis + 1) % 3, (axis + 2) % 3);
+            let spans = (max[b] - min[b] + 1, max[c] - min[c] + 1);
+            self.add_face(min, axis, spans, false);
+            let mut far = min;
+            far[axis] = max[axis] + 1;
+            self.add_face(far, axis, spans, true);
+        }
+    }
+
+    /// Adds a rectangle at right angles to `axis`, facing along it if `positive`.
+    fn add_face(&mut self, origin: Point3, axis: usize, spans: (isize, isize), positive: bool) {
+        // Going around the next two axes in order is anticlockwise when looking back along `axis`
+        let (b, c) = ((axis + 1) % 3, (axis + 2) % 3);
+        let mut corners = [origin; 4];
+        corners[1][b] += spans.0;
+        corners[2][b] += spans.0;
+        corners[2][c] += spans.1;
+        corners[3][c] += spans.1;
+        if !positive {
+            corners.reverse();
+        }
+        let face = corners.map(|corner| self.vertex(corner));
+        self.faces.push(face);
+    }
+
+    fn vertex(&mut self, point: Point3) -> usize {
+        *self.indices.entry(point).or_insert_with(|| {
+            self.vertices.push(point);
+            self.vertices.len() - 1
+        })
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    pub fn face_count(&self) -> usize {
+        self.faces.len()
+    }
+
+    /// Writes a Wavefront OBJ, which keeps the square faces.
+    pub fn write_obj(&self, out: &mut impl Write) -> io::Result<()> {
+        for [x, y, z] in &self.vertices {
+            writeln!(out, "v {} {} {}", x, y, z)?;
+        }
+        for [a, b, c, d] in &self.faces {
+            // Indices count from 1
+            writeln!(out, "f {} {} {} {}", a + 1, b + 1, c + 1, d + 1)?;
+        }
+        Ok(())
+    }
+
+    /// Writes an ASCII STL, where each face becomes two triangles.
+    pub fn write_stl(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "solid aoc")?;
+        for face in &self.faces {
+            let [a, b, c, d] = face.map(|i| self.vertices[i]);
+            let [nx, ny, nz] = normal(a, b, c);
+            for triangle in [[a, b, c], [a, c, d]] {
+                writeln!(out, "facet normal {} {} {}", nx, ny, nz)?;
+                writeln!(out, "  outer loop")?;
+                for [x, y, z] in triangle {
+                    writeln!(out, "    vertex {} {} {}", x, y, z)?;
+                }
+                writeln!(out, "  endloop")?;
+                writeln!(out, "endfacet")?;
+            }
+        }
+        writeln!(out, "endsolid aoc")
+    }
+
+    /// Writes the mesh to the path in the options, as OBJ or STL.
+    pub fn save(&self, options: &Options) {
+        let write = match options.format {
+            Format::Obj => Mesh::write_obj,
+            Format::Stl => Mesh::write_stl,
+            _ => panic!("A surface can only be exported as .obj or .stl"),
+        };
+        let mut out = create(&options.path).expect("Failed to create the export");
+        write(self, &mut out)
+            .and_then(|_| out.flush())
+            .expect("Failed to write the export");
+        println!(
+            "Exported {} faces to {}",
+            self.face_count(),
+            options.path.display()
+        );
+    }
+}
+
+/// The direction a face points, which is always along an axis here.
+fn normal(a: Point3, b: Point3, c: Point3) -> Point3 {
+    let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+    [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ]
+    .map(isize::signum)
+}
+
+/// Coloured points, such as beacons and the scanners that found them.
+#[derive(Clone, Debug, Default)]
+pub struct PointCloud {
+    points: Vec<(Point3, Colour)>,
+}
+
+impl PointCloud {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn add(&mut self, point: Point3, colour: Colour) {
+        self.points.push((point, colour));
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Writes an ASCII PLY with a colour for each point.
+    pub fn write_ply(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "ply")?;
+        writeln!(out, "format ascii 1.0")?;
+        writeln!(out, "element vertex {}", self.points.len())?;
+        for property in ["int x", "int y", "int z"] {
+            writeln!(out, "property {}", property)?;
+        }
+        for property in ["uchar red", "uchar green", "uchar blue"] {
+            writeln!(out, "property {}", property)?;
+        }
+        writeln!(out, "end_header")?;
+        for ([x, y, z], colour) in &self.points {
+            let [r, g, b] = colour.rgb();
+            writeln!(out, "{} {} {} {} {} {}", x, y, z, r, g, b)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the points to the path in the options, which must be a PLY file.
+    pub fn save(&self, options: &Options) {
+        assert!(
+            options.format == Format::Ply,
+            "Points can only be exported as .ply"
+        );
+        let mut out = create(&options.path).expect("Failed to create the export");
+        self.write_ply(&mut out)
+            .and_then(|_| out.flush())
+            .expect("Failed to write the export");
+        println!(
+            "Exported {} points to {}",
+            self.len(),
+            options.path.display()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn surfaces() {
+        let mesh = Mesh::voxel_surface([[0, 0, 0]]);
+        assert_eq!((mesh.vertex_count(), mesh.face_count()), (8, 6));
+
+        // Two cells side by side hide the faces where they touch
+        let mesh = Mesh::voxel_surface([[0, 0, 0], [1, 0, 0]]);
+        assert_eq!((mesh.vertex_count(), mesh.face_count()), (12, 10));
+
+        let mut boxes = Mesh::new();
+        boxes.add_cuboid([0, 0, 0], [1, 0, 0]);
+        assert_eq!((boxes.vertex_count(), boxes.face_count()), (8, 6));
+
+        // Every face points outwards
+        for face in &mesh.faces {
+            let [a, b, c, _] = face.map(|i| mesh.vertices[i]);
+            let n = normal(a, b, c);
+            let centre = [0, 1, 2].map(|axis| a[axis] + c[axis]);
+            let axis = n.iter().position(|&x| x != 0).unwrap();
+            // Doubled coordinates, so the centre of the pair of cells is [2, 1, 1]
+            let middle = [2, 1, 1];
+            assert_eq!(n[axis], (centre[axis] - middle[axis]).signum());
+        }
+    }
+
+    #[test]
+    fn files() {
+        let mut mesh = Mesh::new();
+        mesh.add_face([0, 0, 0], 2, (1, 1), true);
+        let mut obj = Vec::new();
+        mesh.write_obj(&mut obj).unwrap();
+        assert_eq!(
+            String::from_utf8(obj).unwrap(),
+            "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf 1 2 3 4\n"
+        );
+
+        let mut stl = Vec::new();
+        mesh.write_stl(&mut stl).unwrap();
+        let stl = String::from_utf8(stl).unwrap();
+        assert_eq!(stl.matches("facet normal 0 0 1").count(), 2);
+        assert!(stl.ends_with("endsolid aoc\n"));
+
+        let mut cloud = PointCloud::new();
+        cloud.add([1, -2, 3], Colour::Red);
+        let mut ply = Vec::new();
+        cloud.write_ply(&mut ply).unwrap();
+        let ply = String::from_utf8(ply).unwrap();
+        assert!(ply.contains("element vertex 1\n"));
+        assert!(ply.ends_with("end_header\n1 -2 3 205 49 49\n"));
+    }
+}
@@ -0,0 +1,160 @@
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+#[cfg(feature = "parallel")]
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+// Independent pieces of work go through here, so the solutions read the same either way and
+// only spread across threads when the `parallel` feature is on.
+
+/// Whether work is spread across threads.
+pub const ENABLED: bool = cfg!(feature = "parallel");
+
+/// Items that can be shared out: anything rayon can split up when running in parallel, and
+/// anything that can be iterated over when not.
+#[cfg(feature = "parallel")]
+pub trait Items<T>: IntoIterator<Item = T> + IntoParallelIterator<Item = T> {}
+
+#[cfg(feature = "parallel")]
+impl<I, T> Items<T> for I where I: IntoIterator<Item = T> + IntoParallelIterator<Item = T> {}
+
+#[cfg(not(feature = "parallel"))]
+pub trait Items<T>: IntoIterator<Item = T> {}
+
+#[cfg(not(feature = "parallel"))]
+impl<I, T> Items<T> for I where I: IntoIterator<Item = T> {}
+
+/// Maps every item, keeping them in order.
+pub fn map<I, T, U>(items: I, f: impl Fn(T) -> U + Sync + Send) -> Vec<U>
+where
+    I: Items<T>,
+    T: Send,
+    U: Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        items.into_par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        items.into_iter().map(f).collect()
+    }
+}
+
+/// Maps every item, keeping the results that aren't `None` in order.
+pub fn filter_map<I, T, U>(items: I, f: impl Fn(T) -> Option<U> + Sync + Send) -> Vec<U>
+where
+    I: Items<T>,
+    T: Send,
+    U: Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        items.into_par_iter().filter_map(f).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        items.into_iter().filter_map(f).collect()
+    }
+}
+
+/// Finds an item that matches, which is only sure to be the first one when running in order.
+pub fn find_any<I, T>(items: I, predicate: impl Fn(&T) -> bool + Sync + Send) -> Option<T>
+where
+    I: Items<T>,
+    T: Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        items.into_par_iter().find_any(predicate)
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        items.into_iter().find(predicate)
+    }
+}
+
+/// Runs each day with `run`, or when running in parallel, starts this program again for every
+/// day at once, passing on `args`. The output of each day is printed in order once it has finished.
+/// Days sharing the machine run slower, so any day that fails is run again on its own afterwards,
+/// and it's that run which counts, so time budgets measure the day rather than its neighbours.
+/// Returns the days that failed, which is when `run` returns false or the program fails.
+pub fn run_days(
+    days: impl IntoIterator<Item = usize>,
+    args: &[String],
+    run: impl Fn(usize) -> bool,
+) -> Vec<usize> {
+    if !ENABLED {
+        return days.into_iter().filter(|&day| !run(day)).collect();
+    }
+
+    let program = std::env::current_exe().expect("Failed to find the running program");
+    let start = |day: usize| {
+        Command::new(&program)
+            .arg(day.to_string())
+            .args(args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("Failed to start a day")
+    };
+    let children: Vec<_> = days.into_iter().map(|day| (day, start(day))).collect();
+    let outputs: Vec<_> = children
+        .into_iter()
+        .map(|(day, child)| (day, child.wait_with_output().expect("Failed to run a day")))
+        .collect();
+    // Every day has finished by now, so the ones that failed get the machine to themselves
+    let outputs = outputs.into_iter().map(|(day, output)| {
+        if output.status.success() {
+            (day, output)
+        } else {
+            (
+                day,
+                start(day).wait_with_output().expect("Failed to run a day"),
+            )
+        }
+    });
+
+    let mut stdout = io::stdout().lock();
+    let mut stderr = io::stderr().lock();
+    let mut failed = Vec::new();
+    for (day, output) in outputs {
+        stdout
+            .write_all(&output.stdout)
+            .expect("Failed to write the output");
+        stderr
+            .write_all(&output.stderr)
+            .expect("Failed to write the output");
+        if !output.status.success() {
+            failed.push(day);
+        }
+    }
+    failed
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn in_order() {
+        let numbers: Vec<u32> = (1..=100).collect();
+        assert_eq!(
+            map(&numbers, |n| n * 2),
+            (2..=200).step_by(2).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            filter_map(numbers.as_slice(), |&n| (n % 30 == 0).then_some(n)),
+            vec![30, 60, 90]
+        );
+        assert_eq!(find_any(&numbers, |&&n| n * n == 49), Some(&7));
+        assert_eq!(find_any(&numbers, |&&n| n > 100), None);
+
+        let table: HashMap<u32, u32> = numbers.iter().map(|&n| (n, n * n)).collect();
+        let mut squares = filter_map(&table, |(_, &square)| (square < 10).then_some(square));
+        squares.sort_unstable();
+        assert_eq!(squares, vec![1, 4, 9]);
+    }
+}
@@ -0,0 +1,200 @@
+use std::fmt;
+use std::str::FromStr;
+
+use nom::{
+    bytes::complete::tag,
+    character::complete::{
+        char, digit1, line_ending, multispace0, none_of, one_of, space0, space1,
+    },
+    combinator::{all_consuming, map, map_opt, map_res, opt, recognize, verify},
+    error::{Error, ErrorKind},
+    multi::{many1, separated_list1},
+    sequence::{pair, preceded, separated_pair, terminated, tuple},
+    Finish, IResult, Parser,
+};
+
+use crate::grid::Grid;
+
+/// Where and why an input failed to parse.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// Lines and columns count from 1.
+    pub line: usize,
+    pub column: usize,
+    pub kind: ErrorKind,
+}
+
+impl ParseError {
+    fn new(input: &str, remaining: &str, kind: ErrorKind) -> Self {
+        let before = &input[..input.len() - remaining.len()];
+        let line = before.matches('\n').count() + 1;
+        let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+        Self { line, column, kind }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // nom calls it the end of the file, but for `parse_all` it means the parser stopped
+        // before the input did
+        let reason = match self.kind {
+            ErrorKind::Eof => "unexpected trailing input",
+            _ => self.kind.description(),
+        };
+        write!(
+            f,
+            "parse error at line {}, column {}: {}",
+            self.line, self.column, reason
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Runs a parser over the whole input, allowing trailing whitespace.
+pub fn parse_all<'a, O>(
+    input: &'a str,
+    parser: impl Parser<&'a str, O, Error<&'a str>>,
+) -> Result<O, ParseError> {
+    all_consuming(terminated(parser, multispace0))(input)
+        .finish()
+        .map(|(_, output)| output)
+        .map_err(|e| ParseError::new(input, e.input, e.code))
+}
+
+/// An integer with an optional sign, as any type that can be parsed from a string.
+pub fn integer<T: FromStr>(i: &str) -> IResult<&str, T> {
+    map_res(recognize(pair(opt(one_of("+-")), digit1)), str::parse)(i)
+}
+
+/// Items separated by commas, with or without a space after each comma.
+pub fn comma_list<'a, O>(
+    item: impl Parser<&'a str, O, Error<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>> {
+    separated_list1(pair(char(','), space0), item)
+}
+
+/// Items separated by spaces, allowing the padding used to line up columns.
+pub fn space_list<'a, O>(
+    item: impl Parser<&'a str, O, Error<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>> {
+    preceded(space0, separated_list1(space1, item))
+}
+
+/// One item per line.
+pub fn lines<'a, O>(
+    item: impl Parser<&'a str, O, Error<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>> {
+    separated_list1(line_ending, item)
+}
+
+/// Groups separated by blank lines.
+pub fn sections<'a, O>(
+    section: impl Parser<&'a str, O, Error<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>> {
+    separated_list1(pair(line_ending, line_ending), section)
+}
+
+/// A rectangular block of characters, read into a grid by `f`.
+/// Any character `f` rejects ends the block.
+pub fn grid<'a, T: Clone>(
+    background: T,
+    f: impl Fn(char) -> Option<T>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Grid<T>> {
+    move |i| {
+        let row = many1(map_opt(none_of("\r\n"), &f));
+        let rows = verify(lines(row), |rows: &Vec<Vec<T>>| {
+            rows.iter().all(|row| row.len() == rows[0].len())
+        });
+        map(rows, |rows| Grid::from_rows(rows, background.clone()))(i)
+    }
+}
+
+/// A `name: value` line, possibly indented, where only the value is kept.
+pub fn field<'a, O>(
+    name: &'static str,
+    value: impl Parser<&'a str, O, Error<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, O> {
+    preceded(tuple((space0, tag(name), char(':'), space0)), value)
+}
+
+/// A `key: value` pair where both sides are kept.
+pub fn key_value<'a, K, V>(
+    key: impl Parser<&'a str, K, Error<&'a str>>,
+    value: impl Parser<&'a str, V, Error<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, (K, V)> {
+    separated_pair(key, pair(char(':'), space0), value)
+}
+
+/// Two nodes joined by an arrow such as `" -> "` or `"-"`.
+pub fn edge<'a, O>(
+    arrow: &'static str,
+    node: impl Fn(&'a str) -> IResult<&'a str, O>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, (O, O)> {
+    move |i| separated_pair(&node, tag(arrow), &node)(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use nom::character::complete::alpha1;
+
+    use super::*;
+
+    #[test]
+    fn combinators() {
+        assert_eq!(parse_all("-12", integer::<i32>), Ok(-12));
+        assert_eq!(parse_all("+7\n", integer::<u8>), Ok(7));
+        assert!(parse_all("-7", integer::<u8>).is_err());
+        assert_eq!(
+            parse_all("1,2, 3", comma_list(integer::<u8>)),
+            Ok(vec![1, 2, 3])
+        );
+        assert_eq!(
+            parse_all(" 4  5 6", space_list(integer::<u8>)),
+            Ok(vec![4, 5, 6])
+        );
+        assert_eq!(
+            parse_all("1\n2\n\n3\n", sections(lines(integer::<u8>))),
+            Ok(vec![vec![1, 2], vec![3]])
+        );
+        assert_eq!(
+            parse_all(
+                "  Starting items: 79, 98",
+                field("Starting items", comma_list(integer::<u8>))
+            ),
+            Ok(vec![79, 98])
+        );
+        assert_eq!(
+            parse_all("a: 1", key_value(alpha1, integer::<u8>)),
+            Ok(("a", 1))
+        );
+        assert_eq!(
+            parse_all("start-end", edge("-", alpha1)),
+            Ok(("start", "end"))
+        );
+    }
+
+    #[test]
+    fn grids() {
+        let (rest, image) = grid(false, |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })("#.\n.#\n\nafter")
+        .unwrap();
+        assert_eq!(rest, "\n\nafter");
+        assert_eq!(image.render(|c| if *c { '#' } else { '.' }), "#.\n.#\n");
+
+        assert!(parse_all("#.\n#\n", grid(false, |c| Some(c == '#'))).is_err());
+    }
+
+    #[test]
+    fn errors() {
+        let error = parse_all("1,2\n3,x\n", lines(comma_list(integer::<u8>))).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 2));
+        assert_eq!(
+            error.to_string(),
+            "parse error at line 2, column 2: unexpected trailing input"
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use std::fmt::{Debug, Display};
+use std::io::{self, BufRead, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::str::FromStr;
+use std::time::Instant;
+
+// A day hands over its parsed input and its parts, and the session takes commands a line at a
+// time. Parts are run inside `catch_unwind`, so a bad parameter doesn't end the session.
+
+const HELP: &str = "\
+Commands:
+  input                   show the parsed input
+  parts                   list the parts and their parameters
+  <part> [name=value...]  run a part, such as `1` or `2 rounds=20`
+  set name=value...       change parameters for every run after this
+  quit                    leave";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Options {
+    /// The text of an input to use instead of the puzzle input.
+    pub text: Option<String>,
+}
+
+impl Options {
+    /// Reads `--repl [PATH]` from the command line arguments.
+    /// Returns `None` if the REPL wasn't asked for.
+    pub fn from_args(args: &[String]) -> Option<Self> {
+        let position = args.iter().position(|arg| arg == "--repl")?;
+        let text = args
+            .get(position + 1)
+            .filter(|path| !path.starts_with("--"))
+            .map(|path| std::fs::read_to_string(path).expect("Failed to read the input"));
+        Some(Options { text })
+    }
+
+    /// The input given on the command line, or the puzzle input if there wasn't one.
+    pub fn input<'a>(&'a self, puzzle_input: &'a str) -> &'a str {
+        self.text.as_deref().unwrap_or(puzzle_input)
+    }
+}
+
+/// The values a part is run with, by name.
+pub struct Parameters<'p>(&'p [(&'static str, String)]);
+
+impl Parameters<'_> {
+    /// Reads a parameter as whatever type the part needs.
+    /// Panics if it doesn't parse, which the session reports.
+    pub fn get<T: FromStr>(&self, name: &str) -> T {
+        let (_, value) = self
+            .0
+            .iter()
+            .find(|(n, _)| *n == name)
+            .unwrap_or_else(|| panic!("No parameter called {}", name));
+        value
+            .parse()
+            .unwrap_or_else(|_| panic!("{} isn't a valid value for {}", value, name))
+    }
+}
+
+type Solve<'a> = Box<dyn Fn(&Parameters) -> String + 'a>;
+
+struct Part<'a> {
+    number: usize,
+    parameters: Vec<(&'static str, String)>,
+    solve: Solve<'a>,
+}
+
+/// An interactive look at one day.
+pub struct Session<'a> {
+    input: &'a dyn Debug,
+    parts: Vec<Part<'a>>,
+}
+
+impl<'a> Session<'a> {
+    pub fn new(input: &'a impl Debug) -> Self {
+        Self {
+            input,
+            parts: Vec::new(),
+        }
+    }
+
+    pub fn part<T: Display>(self, number: usize, solve: impl Fn() -> T + 'a) -> Self {
+        self.part_with(number, &[], move |_| solve())
+    }
+
+    /// Adds a part that takes parameters, each given with its default value.
+    pub fn part_with<T: Display>(
+        mut self,
+        number: usize,
+        parameters: &[(&'static str, &str)],
+        solve: impl Fn(&Parameters) -> T + 'a,
+    ) -> Self {
+        self.parts.push(Part {
+            number,
+            parameters: parameters
+                .iter()
+                .map(|&(name, value)| (name, value.to_string()))
+                .collect(),
+            solve: Box::new(move |parameters| solve(parameters).to_string()),
+        });
+        self
+    }
+
+    /// Takes commands from the terminal until told to quit.
+    pub fn run(mut self) {
+        self.run_with(io::stdin().lock(), &mut io::stdout().lock())
+            .expect("Failed to write to the terminal");
+    }
+
+    fn run_with(&mut self, commands: impl BufRead, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "{}", HELP)?;
+        write!(out, "> ")?;
+        out.flush()?;
+        for line in commands.lines() {
+            let line = line?;
+            let mut words = line.split_whitespace();
+            match words.next() {
+                None => {}
+                Some("quit" | "q") => return Ok(()),
+                Some("help") => writeln!(out, "{}", HELP)?,
+                Some("input") => writeln!(out, "{:#?}", self.input)?,
+                Some("parts") => self.list(out)?,
+                Some("set") => {
+                    for (name, value) in words.filter_map(|word| word.split_once('=')) {
+                        self.set(name, value, out)?;
+                    }
+                }
+                Some(word) => match word.parse() {
+                    Ok(number) => self.solve(number, words, out)?,
+                    Err(_) => writeln!(out, "Unknown command {}, try help", word)?,
+                },
+            }
+            write!(out, "> ")?;
+            out.flush()?;
+        }
+        Ok(())
+    }
+
+    fn list(&self, out: &mut impl Write) -> io::Result<()> {
+        for part in &self.parts {
+            let parameters: Vec<String> = part
+                .parameters
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect();
+            writeln!(out, "Part {} {}", part.number, parameters.join(" "))?;
+        }
+        Ok(())
+    }
+
+    fn set(&mut self, name: &str, value: &str, out: &mut impl Write) -> io::Result<()> {
+        let mut found = false;
+        for part in &mut self.parts {
+            for (n, v) in &mut part.parameters {
+                if *n == name {
+                    *v = value.to_string();
+                    found = true;
+                }
+            }
+        }
+        if !found {
+            writeln!(out, "No part has a parameter called {}", name)?;
+        }
+        Ok(())
+    }
+
+    fn solve<'w>(
+        &self,
+        number: usize,
+        overrides: impl Iterator<Item = &'w str>,
+        out: &mut impl Write,
+    ) -> io::Result<()> {
+        let Some(part) = self.parts.iter().find(|part| part.number == number) else {
+            return writeln!(out, "There is no part {}", number);
+        };
+        let mut parameters = part.parameters.clone();
+        for word in overrides {
+            match word.split_once('=').and_then(|(name, value)| {
+                Some((parameters.iter_mut().find(|(n, _)| *n == name)?, value))
+            }) {
+                Some(((_, v), value)) => *v = value.to_string(),
+                None => return writeln!(out, "Part {} has no parameter {}", number, word),
+            }
+        }
+
+        let start = Instant::now();
+        // The panic message has already been printed by the time this sees it
+        match panic::catch_unwind(AssertUnwindSafe(|| (part.solve)(&Parameters(&parameters)))) {
+            Ok(answer) if answer.contains('\n') => {
+                writeln!(
+                    out,
+                    "Part {} ({:.2?}):\n{}",
+                    number,
+                    start.elapsed(),
+                    answer
+                )
+            }
+            Ok(answer) => writeln!(out, "Part {}: {} ({:.2?})", number, answer, start.elapsed()),
+            Err(_) => writeln!(out, "Part {} failed", number),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct Input {
+        numbers: Vec<usize>,
+    }
+
+    fn session(input: &Input) -> Session<'_> {
+        Session::new(input)
+            .part(1, || input.numbers.iter().sum::<usize>())
+            .part_with(2, &[("take", "2"), ("scale", "1")], |p| {
+                input.numbers.iter().take(p.get("take")).sum::<usize>() * p.get::<usize>("scale")
+            })
+    }
+
+    fn run(commands: &str) -> String {
+        let input = Input {
+            numbers: vec![1, 2, 3],
+        };
+        let mut out = Vec::new();
+        session(&input)
+            .run_with(commands.as_bytes(), &mut out)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn commands() {
+        let out = run("parts\n1\n2\n2 take=3\nset scale=10\n2\nquit\n1\n");
+        assert!(out.contains("Part 2 take=2 scale=1\n"));
+        assert!(out.contains("Part 1: 6 ("));
+        assert!(out.contains("Part 2: 3 ("));
+        assert!(out.contains("Part 2: 6 ("));
+        assert!(out.contains("Part 2: 30 ("));
+        // Nothing runs after quitting
+        assert_eq!(out.matches("Part 1: ").count(), 1);
+
+        let out = run("input\n3\n1 take=1\nset size=2\nfly\n");
+        assert!(out.contains("numbers: [\n"));
+        assert!(out.contains("There is no part 3\n"));
+        assert!(out.contains("Part 1 has no parameter take=1\n"));
+        assert!(out.contains("No part has a parameter called size\n"));
+        assert!(out.contains("Unknown command fly, try help\n"));
+    }
+
+    #[test]
+    fn failures() {
+        let out = run("2 take=lots\n2\n");
+        assert!(out.contains("Part 2 failed\n"));
+        // The session carries on
+        assert!(out.contains("Part 2: 3 ("));
+    }
+}
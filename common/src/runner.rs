@@ -0,0 +1,384 @@
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+use crate::budget::{parse_bytes, parse_duration, Budget, Overrun};
+use crate::{memory, trace};
+
+// Every day reports its answers through a `Run`, so the same solution can be printed for people
+// or as records for scripts. Records are printed as soon as each part finishes.
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// `Part 1: ...` lines, as people read them.
+    Text,
+    /// One JSON object per line, so the output of several runs can be joined together.
+    Json,
+    Csv,
+    /// Lined up columns with a header.
+    Table,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Options {
+    pub format: Format,
+    /// Whether the column names are printed before the first record.
+    pub header: bool,
+    /// Budgets for every day, instead of the ones the days were given.
+    pub time_budget: Option<Duration>,
+    pub memory_budget: Option<usize>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            format: Format::Text,
+            header: true,
+            time_budget: None,
+            memory_budget: None,
+        }
+    }
+}
+
+impl Options {
+    /// Reads `[--format text|json|csv|table] [--no-header] [--time-budget T] [--memory-budget M]`
+    /// from the command line arguments.
+    pub fn from_args(args: &[String]) -> Self {
+        let mut options = Options::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--format" => {
+                    options.format = match args.next().map(String::as_str) {
+                        Some("text") => Format::Text,
+                        Some("json") => Format::Json,
+                        Some("csv") => Format::Csv,
+                        Some("table") => Format::Table,
+                        _ => panic!("--format must be followed by text, json, csv or table"),
+                    }
+                }
+                "--no-header" => options.header = false,
+                "--time-budget" => {
+                    options.time_budget = Some(
+                        args.next()
+                            .and_then(|time| parse_duration(time))
+                            .expect("--time-budget must be followed by a time such as 500ms or 2s"),
+                    )
+                }
+                "--memory-budget" => {
+                    options.memory_budget = Some(
+                        args.next()
+                            .and_then(|memory| parse_bytes(memory))
+                            .expect("--memory-budget must be followed by an amount such as 64MiB"),
+                    )
+                }
+                _ => {}
+            }
+        }
+        options
+    }
+
+    /// Arguments for running another day the same way, leaving the header to whoever started it.
+    pub fn child_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        let format = match self.format {
+            Format::Text => None,
+            Format::Json => Some("json"),
+            Format::Csv => Some("csv"),
+            Format::Table => Some("table"),
+        };
+        if let Some(format) = format {
+            args.extend(["--format", format, "--no-header"].map(String::from));
+        }
+        if let Some(time) = self.time_budget {
+            args.extend([
+                "--time-budget".to_string(),
+                format!("{}s", time.as_secs_f64()),
+            ]);
+        }
+        if let Some(memory) = self.memory_budget {
+            args.extend(["--memory-budget".to_string(), memory.to_string()]);
+        }
+        args
+    }
+
+    /// The budget for a day, with anything given on the command line taking its place.
+    pub fn budget(&self, budget: Budget) -> Budget {
+        Budget {
+            time: self.time_budget.unwrap_or(budget.time),
+            memory: self.memory_budget.unwrap_or(budget.memory),
+        }
+    }
+
+    /// What goes before the first record, which is nothing for some formats.
+    pub fn header(&self) -> &'static str {
+        if !self.header {
+            return "";
+        }
+        match self.format {
+            Format::Text | Format::Json => "",
+            Format::Csv => "year,day,part,answer,parse_ms,solve_ms,peak_bytes\n",
+            Format::Table => concat!(
+                "Year  Day  Part  Answer                    Parse      Solve  Peak memory\n",
+                "----  ---  ----  --------------------  ---------  ---------  -----------\n"
+            ),
+        }
+    }
+}
+
+/// The answer to one part, and what it took to get there.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    pub year: u16,
+    pub day: usize,
+    pub part: usize,
+    /// Some answers are drawings, which take up several lines.
+    pub answer: String,
+    /// The same for both parts of a day, as they share their input.
+    pub parse_time: Duration,
+    pub solve_time: Duration,
+    /// The most the part allocated at once, which is only measured by binaries that install
+    /// `memory::Counting`.
+    pub peak_memory: usize,
+}
+
+impl Record {
+    pub fn format(&self, format: Format) -> String {
+        match format {
+            Format::Text if self.answer.contains('\n') => {
+                format!("Part {}:\n{}", self.part, self.answer)
+            }
+            Format::Text => format!("Part {}: {}", self.part, self.answer),
+            Format::Json => format!(
+                r#"{{"year":{},"day":{},"part":{},"answer":{},"parse_ms":{:.3},"solve_ms":{:.3},"peak_bytes":{}}}"#,
+                self.year,
+                self.day,
+                self.part,
+                json_string(&self.answer),
+                milliseconds(self.parse_time),
+                milliseconds(self.solve_time),
+                self.peak_memory
+            ),
+            Format::Csv => format!(
+                "{},{},{},{},{:.3},{:.3},{}",
+                self.year,
+                self.day,
+                self.part,
+                csv_field(&self.answer),
+                milliseconds(self.parse_time),
+                milliseconds(self.solve_time),
+                self.peak_memory
+            ),
+            Format::Table => {
+                // Drawings don't fit in a column
+                let lines = self.answer.lines().count();
+                let answer = if lines > 1 {
+                    format!("({} lines)", lines)
+                } else {
+                    self.answer.clone()
+                };
+                format!(
+                    "{:<4}  {:>3}  {:>4}  {:<20}  {:>9}  {:>9}  {:>11}",
+                    self.year,
+                    self.day,
+                    self.part,
+                    answer,
+                    format!("{:.2?}", self.parse_time),
+                    format!("{:.2?}", self.solve_time),
+                    bytes(self.peak_memory)
+                )
+            }
+        }
+    }
+}
+
+fn milliseconds(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+pub(crate) fn bytes(n: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = n as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", n)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Times the parts of one day, printing each answer as it comes.
+pub struct Run {
+    year: u16,
+    day: usize,
+    format: Format,
+    parse_time: Duration,
+    budget: Budget,
+    overruns: Vec<Overrun>,
+}
+
+impl Run {
+    pub fn new(year: u16, day: usize, options: &Options, budget: Budget) -> Self {
+        if options.format == Format::Text {
+            println!("Day {}", day);
+        }
+        Self {
+            year,
+            day,
+            format: options.format,
+            parse_time: Duration::ZERO,
+            budget: options.budget(budget),
+            overruns: Vec::new(),
+        }
+    }
+
+    /// Reads the input, timing how long it takes.
+    pub fn parse<T>(&mut self, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let input = f();
+        self.parse_time += start.elapsed();
+        input
+    }
+
+    /// Solves a part, whose result is the answer.
+    pub fn part<T: Display>(&mut self, part: usize, f: impl FnOnce() -> T) -> T {
+        self.part_with(part, f, T::to_string)
+    }
+
+    /// Solves a part whose result holds more than the answer, such as something the next part
+    /// carries on from.
+    pub fn part_with<T>(
+        &mut self,
+        part: usize,
+        f: impl FnOnce() -> T,
+        answer: impl FnOnce(&T) -> String,
+    ) -> T {
+        let span = match part {
+            1 => "part 1",
+            2 => "part 2",
+            _ => "part",
+        };
+        let start = Instant::now();
+        let (result, peak_memory) = memory::measure(|| trace::time(span, f));
+        let record = Record {
+            year: self.year,
+            day: self.day,
+            part,
+            answer: answer(&result).trim_end().to_string(),
+            parse_time: self.parse_time,
+            solve_time: start.elapsed(),
+            peak_memory,
+        };
+        println!("{}", record.format(self.format));
+        self.overruns.extend(self.budget.check(&record));
+        result
+    }
+
+    /// The parts that went over their budget so far.
+    pub fn overruns(&self) -> &[Overrun] {
+        &self.overruns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &str) -> Vec<String> {
+        args.split_whitespace().map(String::from).collect()
+    }
+
+    fn record(answer: &str) -> Record {
+        Record {
+            year: 2022,
+            day: 10,
+            part: 2,
+            answer: answer.to_string(),
+            parse_time: Duration::from_micros(1500),
+            solve_time: Duration::from_millis(20),
+            peak_memory: 3 * 1024 * 1024,
+        }
+    }
+
+    #[test]
+    fn options() {
+        assert_eq!(Options::from_args(&args("14")), Options::default());
+        let options = Options::from_args(&args("all --format csv"));
+        assert_eq!(options.format, Format::Csv);
+        assert!(options.header().starts_with("year,day,part"));
+        assert_eq!(options.child_args(), args("--format csv --no-header"));
+        assert_eq!(Options::from_args(&options.child_args()).header(), "");
+        assert!(Options::default().child_args().is_empty());
+
+        let options = Options::from_args(&args("all --time-budget 250ms --memory-budget 2MiB"));
+        let budget = options.budget(Budget::new(Duration::from_secs(1), 1024));
+        assert_eq!(
+            budget,
+            Budget::new(Duration::from_millis(250), 2 * 1024 * 1024)
+        );
+        assert_eq!(
+            options.child_args(),
+            args("--time-budget 0.25s --memory-budget 2097152")
+        );
+        assert_eq!(Options::from_args(&options.child_args()), options);
+    }
+
+    #[test]
+    fn formats() {
+        let record = record("12");
+        assert_eq!(record.format(Format::Text), "Part 2: 12");
+        assert_eq!(
+            record.format(Format::Json),
+            r#"{"year":2022,"day":10,"part":2,"answer":"12","parse_ms":1.500,"solve_ms":20.000,"peak_bytes":3145728}"#
+        );
+        assert_eq!(
+            record.format(Format::Csv),
+            "2022,10,2,12,1.500,20.000,3145728"
+        );
+        assert_eq!(
+            record.format(Format::Table),
+            "2022   10     2  12                       1.50ms    20.00ms      3.0 MiB"
+        );
+    }
+
+    #[test]
+    fn drawings() {
+        let record = record("#..\n\"#\"");
+        assert_eq!(record.format(Format::Text), "Part 2:\n#..\n\"#\"");
+        assert!(record
+            .format(Format::Json)
+            .contains(r##""answer":"#..\n\"#\"""##));
+        assert!(record
+            .format(Format::Csv)
+            .contains("2,\"#..\n\"\"#\"\"\",1.500"));
+        assert!(record.format(Format::Table).contains("(2 lines)"));
+        assert_eq!(bytes(512), "512 B");
+    }
+}
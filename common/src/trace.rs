@@ -0,0 +1,252 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+#[cfg(feature = "trace")]
+use std::{
+    cell::RefCell,
+    sync::atomic::{AtomicU64, Ordering},
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+// Everything here does nothing unless the `trace` feature is on, so instrumentation can be left
+// in hot loops. Counts and timings from every thread are gathered, so parallel work is included.
+// Counters sit in the hottest loops, so each thread keeps a count of its own for each name, which
+// only that thread writes to and a report adds up, and counting never waits on another thread.
+
+/// Whether instrumentation has been compiled in.
+pub const ENABLED: bool = cfg!(feature = "trace");
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Timing {
+    pub calls: u64,
+    pub total: Duration,
+}
+
+/// Everything recorded since the start of the run, or the last reset.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    pub counters: BTreeMap<&'static str, u64>,
+    /// Keyed by the names of the spans they were nested in, joined with `/`.
+    pub timings: BTreeMap<String, Timing>,
+}
+
+impl Report {
+    pub fn is_empty(&self) -> bool {
+        self.counters.is_empty() && self.timings.is_empty()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self
+            .counters
+            .keys()
+            .map(|name| name.len())
+            .chain(self.timings.keys().map(String::len))
+            .max()
+            .unwrap_or(0);
+        if !self.counters.is_empty() {
+            writeln!(f, "Counters")?;
+            for (name, count) in &self.counters {
+                writeln!(f, "  {:<width$}  {:>12}", name, count)?;
+            }
+        }
+        if !self.timings.is_empty() {
+            writeln!(f, "Spans")?;
+            for (path, timing) in &self.timings {
+                writeln!(
+                    f,
+                    "  {:<width$}  {:>12}  {:?}",
+                    path,
+                    format!("{} calls", timing.calls),
+                    timing.total
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "trace")]
+static REPORT: Mutex<Report> = Mutex::new(Report {
+    counters: BTreeMap::new(),
+    timings: BTreeMap::new(),
+});
+
+/// Every count of every thread, kept after the thread ends.
+#[cfg(feature = "trace")]
+static COUNTS: Mutex<Vec<(&'static str, Arc<AtomicU64>)>> = Mutex::new(Vec::new());
+
+#[cfg(feature = "trace")]
+thread_local! {
+    static SPANS: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+    static COUNTERS: RefCell<Vec<(&'static str, Arc<AtomicU64>)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Adds to a named counter.
+#[inline]
+pub fn add(name: &'static str, n: u64) {
+    #[cfg(feature = "trace")]
+    COUNTERS.with(|counters| {
+        let mut counters = counters.borrow_mut();
+        // Names are nearly always the same literal, so comparing addresses settles most lookups
+        let found = counters
+            .iter()
+            .find(|(counter, _)| std::ptr::eq(*counter, name) || *counter == name);
+        let count = match found {
+            Some((_, count)) => count,
+            None => {
+                let count = Arc::new(AtomicU64::new(0));
+                COUNTS.lock().unwrap().push((name, Arc::clone(&count)));
+                counters.push((name, count));
+                &counters.last().unwrap().1
+            }
+        };
+        // Only this thread writes to its count, so there's no need for an atomic add
+        count.store(count.load(Ordering::Relaxed) + n, Ordering::Relaxed);
+    });
+    #[cfg(not(feature = "trace"))]
+    let _ = (name, n);
+}
+
+#[inline]
+pub fn count(name: &'static str) {
+    add(name, 1);
+}
+
+/// Times everything until it is dropped.
+/// Spans are meant for phases of a solution, as nesting them in a recursive function would
+/// give every depth its own entry. Counters suit recursion better.
+#[must_use = "A span stops timing as soon as it is dropped"]
+pub struct Span {
+    #[cfg(feature = "trace")]
+    path: String,
+    #[cfg(feature = "trace")]
+    start: Instant,
+}
+
+#[inline]
+pub fn span(name: &'static str) -> Span {
+    #[cfg(feature = "trace")]
+    {
+        let path = SPANS.with(|spans| {
+            let mut spans = spans.borrow_mut();
+            spans.push(name);
+            spans.join("/")
+        });
+        Span {
+            path,
+            start: Instant::now(),
+        }
+    }
+    #[cfg(not(feature = "trace"))]
+    {
+        let _ = name;
+        Span {}
+    }
+}
+
+#[cfg(feature = "trace")]
+impl Drop for Span {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        SPANS.with(|spans| spans.borrow_mut().pop());
+        let mut report = REPORT.lock().unwrap();
+        let timing = report
+            .timings
+            .entry(std::mem::take(&mut self.path))
+            .or_default();
+        timing.calls += 1;
+        timing.total += elapsed;
+    }
+}
+
+/// Runs `f` inside a span.
+#[inline]
+pub fn time<T>(name: &'static str, f: impl FnOnce() -> T) -> T {
+    let _span = span(name);
+    f()
+}
+
+pub fn report() -> Report {
+    #[cfg(feature = "trace")]
+    {
+        let mut report = REPORT.lock().unwrap().clone();
+        for (name, count) in COUNTS.lock().unwrap().iter() {
+            *report.counters.entry(name).or_default() += count.load(Ordering::Relaxed);
+        }
+        report
+    }
+    #[cfg(not(feature = "trace"))]
+    {
+        Report::default()
+    }
+}
+
+/// Meant for between runs, as a count still going on another thread could outlast it.
+pub fn reset() {
+    #[cfg(feature = "trace")]
+    {
+        *REPORT.lock().unwrap() = Report::default();
+        for (_, count) in COUNTS.lock().unwrap().iter() {
+            count.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(not(feature = "trace"))]
+    fn disabled() {
+        count("calls");
+        time("part 1", || add("states", 5));
+        assert!(report().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "trace")]
+    fn enabled() {
+        reset();
+        time("part 1", || {
+            for _ in 0..3 {
+                count("calls");
+                let _search = span("search");
+            }
+            add("states", 5);
+        });
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| (0..1000).for_each(|_| count("threads")));
+            }
+        });
+        let report = report();
+        assert_eq!(report.counters["calls"], 3);
+        assert_eq!(report.counters["threads"], 4000);
+        assert_eq!(report.counters["states"], 5);
+        assert_eq!(report.timings["part 1"].calls, 1);
+        assert_eq!(report.timings["part 1/search"].calls, 3);
+        assert!(report.timings["part 1"].total >= report.timings["part 1/search"].total);
+        assert!(report.to_string().starts_with("Counters\n  calls"));
+    }
+
+    #[test]
+    fn display() {
+        let mut report = Report::default();
+        report.counters.insert("pops", 42);
+        report.timings.insert(
+            "part 1".to_string(),
+            Timing {
+                calls: 1,
+                total: Duration::from_millis(3),
+            },
+        );
+        assert_eq!(
+            report.to_string(),
+            "Counters\n  pops              42\nSpans\n  part 1       1 calls  3ms\n"
+        );
+    }
+}
@@ -0,0 +1,349 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::{Index, IndexMut};
+
+/// A reference to a node in a `Tree<T>`, which can't be mixed up with ids from trees of other types.
+pub struct NodeId<T> {
+    index: usize,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> NodeId<T> {
+    fn new(index: usize) -> Self {
+        Self {
+            index,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self.index
+    }
+}
+
+// Implemented by hand because deriving them would require the same of `T`
+
+impl<T> Clone for NodeId<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for NodeId<T> {}
+
+impl<T> PartialEq for NodeId<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<T> Eq for NodeId<T> {}
+
+impl<T> PartialOrd for NodeId<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for NodeId<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.index.cmp(&other.index)
+    }
+}
+
+impl<T> Hash for NodeId<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state)
+    }
+}
+
+impl<T> fmt::Debug for NodeId<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NodeId({})", self.index)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Node<T> {
+    value: T,
+    parent: Option<NodeId<T>>,
+    children: Vec<NodeId<T>>,
+}
+
+/// Using an arena to own the nodes of a tree allows us to use ids as references,
+/// so nodes can point at their parent as well as their children.
+/// A tree can hold several roots, and nodes that are detached stay in the arena until it is dropped.
+#[derive(Clone, Debug)]
+pub struct Tree<T> {
+    nodes: Vec<Node<T>>,
+}
+
+impl<T> Default for Tree<T> {
+    fn default() -> Self {
+        Self { nodes: Vec::new() }
+    }
+}
+
+impl<T> Tree<T> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// The number of nodes in the arena, including any that have been detached.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Adds a node without a parent, such as a root.
+    pub fn add(&mut self, value: T) -> NodeId<T> {
+        let id = NodeId::new(self.nodes.len());
+        self.nodes.push(Node {
+            value,
+            parent: None,
+            children: Vec::new(),
+        });
+        id
+    }
+
+    /// Adds a node as the last child of `parent`.
+    pub fn add_child(&mut self, parent: NodeId<T>, value: T) -> NodeId<T> {
+        let id = self.add(value);
+        self.append(parent, id);
+        id
+    }
+
+    /// Attaches a node without a parent as the last child of `parent`.
+    pub fn append(&mut self, parent: NodeId<T>, child: NodeId<T>) {
+        assert!(self.parent(child).is_none(), "Node already has a parent");
+        self.nodes[child.index].parent = Some(parent);
+        self.nodes[parent.index].children.push(child);
+    }
+
+    /// Removes a node and its subtree from its parent.
+    pub fn detach(&mut self, id: NodeId<T>) {
+        if let Some(parent) = self.nodes[id.index].parent.take() {
+            self.nodes[parent.index].children.retain(|&c| c != id);
+        }
+    }
+
+    /// Detaches all the children of a node.
+    pub fn clear_children(&mut self, id: NodeId<T>) {
+        for child in std::mem::take(&mut self.nodes[id.index].children) {
+            self.nodes[child.index].parent = None;
+        }
+    }
+
+    /// Splices a node without a parent into the place of `old`, which is left detached.
+    pub fn replace(&mut self, old: NodeId<T>, new: NodeId<T>) {
+        assert!(self.parent(new).is_none(), "Node already has a parent");
+        if let Some(parent) = self.nodes[old.index].parent.take() {
+            for child in self.nodes[parent.index].children.iter_mut() {
+                if *child == old {
+                    *child = new;
+                }
+            }
+            self.nodes[new.index].parent = Some(parent);
+        }
+    }
+
+    pub fn get(&self, id: NodeId<T>) -> &T {
+        &self.nodes[id.index].value
+    }
+
+    pub fn get_mut(&mut self, id: NodeId<T>) -> &mut T {
+        &mut self.nodes[id.index].value
+    }
+
+    pub fn parent(&self, id: NodeId<T>) -> Option<NodeId<T>> {
+        self.nodes[id.index].parent
+    }
+
+    pub fn children(&self, id: NodeId<T>) -> &[NodeId<T>] {
+        &self.nodes[id.index].children
+    }
+
+    pub fn is_leaf(&self, id: NodeId<T>) -> bool {
+        self.children(id).is_empty()
+    }
+
+    /// The parent of a node, then its parent, and so on up to the root.
+    pub fn ancestors(&self, id: NodeId<T>) -> impl Iterator<Item = NodeId<T>> + '_ {
+        std::iter::successors(self.parent(id), |&p| self.parent(p))
+    }
+
+    pub fn depth(&self, id: NodeId<T>) -> usize {
+        self.ancestors(id).count()
+    }
+
+    pub fn root_of(&self, id: NodeId<T>) -> NodeId<T> {
+        self.ancestors(id).last().unwrap_or(id)
+    }
+
+    /// Every node in the subtree, with each parent before its children.
+    pub fn pre_order(&self, root: NodeId<T>) -> impl Iterator<Item = NodeId<T>> + '_ {
+        let mut stack = vec![root];
+        std::iter::from_fn(move || {
+            let id = stack.pop()?;
+            stack.extend(self.children(id).iter().rev());
+            Some(id)
+        })
+    }
+
+    /// Every node in the subtree, with each parent after its children.
+    pub fn post_order(&self, root: NodeId<T>) -> impl Iterator<Item = NodeId<T>> {
+        // Visiting parents first and children from right to left gives the reverse of a post-order
+        let mut order = Vec::new();
+        let mut stack = vec![root];
+        while let Some(id) = stack.pop() {
+            order.push(id);
+            stack.extend(self.children(id));
+        }
+        order.into_iter().rev()
+    }
+
+    /// Combines values up the tree from the leaves, giving each node `f` of its own value
+    /// and the combined values of its children.
+    pub fn aggregate<V: Clone>(
+        &self,
+        root: NodeId<T>,
+        mut f: impl FnMut(&T, &[V]) -> V,
+    ) -> HashMap<NodeId<T>, V> {
+        let mut results: HashMap<NodeId<T>, V> = HashMap::new();
+        let mut values = Vec::new();
+        for id in self.post_order(root) {
+            values.clear();
+            values.extend(self.children(id).iter().map(|c| results[c].clone()));
+            let value = f(self.get(id), &values);
+            results.insert(id, value);
+        }
+        results
+    }
+
+    /// Copies a subtree from another tree into this one, returning the id of the copy of its root.
+    /// The copy has no parent.
+    pub fn graft(&mut self, other: &Tree<T>, root: NodeId<T>) -> NodeId<T>
+    where
+        T: Clone,
+    {
+        let id = self.add(other.get(root).clone());
+        for &child in other.children(root) {
+            let copy = self.graft(other, child);
+            self.append(id, copy);
+        }
+        id
+    }
+
+    /// Whether two subtrees, possibly in different trees, have the same shape and values.
+    pub fn subtree_eq(&self, a: NodeId<T>, other: &Tree<T>, b: NodeId<T>) -> bool
+    where
+        T: PartialEq,
+    {
+        let (xs, ys) = (self.children(a), other.children(b));
+        self.get(a) == other.get(b)
+            && xs.len() == ys.len()
+            && xs
+                .iter()
+                .zip(ys)
+                .all(|(&x, &y)| self.subtree_eq(x, other, y))
+    }
+}
+
+impl<T> Index<NodeId<T>> for Tree<T> {
+    type Output = T;
+
+    fn index(&self, id: NodeId<T>) -> &T {
+        self.get(id)
+    }
+}
+
+impl<T> IndexMut<NodeId<T>> for Tree<T> {
+    fn index_mut(&mut self, id: NodeId<T>) -> &mut T {
+        self.get_mut(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //       a
+    //     / | \
+    //    b  c  d
+    //   / \     \
+    //  e   f     g
+    fn example() -> (Tree<char>, Vec<NodeId<char>>) {
+        let mut tree = Tree::new();
+        let a = tree.add('a');
+        let b = tree.add_child(a, 'b');
+        let e = tree.add_child(b, 'e');
+        let f = tree.add_child(b, 'f');
+        let c = tree.add_child(a, 'c');
+        let d = tree.add_child(a, 'd');
+        let g = tree.add_child(d, 'g');
+        (tree, vec![a, b, c, d, e, f, g])
+    }
+
+    fn values(tree: &Tree<char>, ids: impl Iterator<Item = NodeId<char>>) -> String {
+        ids.map(|id| tree[id]).collect()
+    }
+
+    #[test]
+    fn navigation() {
+        let (tree, ids) = example();
+        let (a, b, e, g) = (ids[0], ids[1], ids[4], ids[6]);
+        assert_eq!(tree.parent(e), Some(b));
+        assert_eq!(tree.parent(a), None);
+        assert_eq!(values(&tree, tree.children(a).iter().copied()), "bcd");
+        assert_eq!(values(&tree, tree.ancestors(g)), "da");
+        assert_eq!(tree.depth(e), 2);
+        assert_eq!(tree.root_of(g), a);
+        assert!(tree.is_leaf(e) && !tree.is_leaf(b));
+    }
+
+    #[test]
+    fn traversal() {
+        let (tree, ids) = example();
+        assert_eq!(values(&tree, tree.pre_order(ids[0])), "abefcdg");
+        assert_eq!(values(&tree, tree.post_order(ids[0])), "efbcgda");
+        assert_eq!(values(&tree, tree.pre_order(ids[3])), "dg");
+
+        let sizes = tree.aggregate(ids[0], |_, children: &[usize]| {
+            1 + children.iter().sum::<usize>()
+        });
+        assert_eq!(sizes[&ids[0]], 7);
+        assert_eq!(sizes[&ids[1]], 3);
+        assert_eq!(sizes[&ids[6]], 1);
+    }
+
+    #[test]
+    fn splicing() {
+        let (mut tree, ids) = example();
+        let (a, b, c, d) = (ids[0], ids[1], ids[2], ids[3]);
+
+        tree.detach(c);
+        assert_eq!(values(&tree, tree.pre_order(a)), "abefdg");
+        assert_eq!(tree.parent(c), None);
+
+        let x = tree.add('x');
+        tree.replace(b, x);
+        tree.append(x, b);
+        assert_eq!(values(&tree, tree.pre_order(a)), "axbefdg");
+
+        tree.clear_children(d);
+        tree[d] = 'z';
+        assert_eq!(values(&tree, tree.pre_order(a)), "axbefz");
+
+        let mut copy = Tree::new();
+        let root = copy.graft(&tree, a);
+        assert!(copy.subtree_eq(root, &tree, a));
+        assert!(!copy.subtree_eq(root, &tree, x));
+        assert_eq!(copy.len(), 6);
+    }
+}